@@ -0,0 +1,75 @@
+use crate::CellState;
+
+// A cell's state as stored, in as few bits as the states of its automaton
+// need. Widths divide a byte, so no cell straddles two.
+pub trait Cell: Copy + Into<u8> {
+    const BITS: usize;
+
+    // The cell from its stored bits, any above BITS ignored
+    fn from_bits(bits: u8) -> Self;
+}
+
+// Life and the other two state rules, live or not
+#[derive(Debug,PartialEq,Eq,Copy,Clone)]
+pub struct LifeCell(pub bool);
+
+impl From<LifeCell> for u8 {
+    fn from(item: LifeCell) -> Self {
+        item.0 as u8
+    }
+}
+
+impl Cell for LifeCell {
+    const BITS: usize = 1;
+
+    fn from_bits(bits: u8) -> Self {
+        LifeCell(bits & 1 == 1)
+    }
+}
+
+// Wireworld's empty cells, electron heads and tails, and wire, numbered as
+// DEAD, LIVE, TAIL and WIRE are
+#[derive(Debug,PartialEq,Eq,Copy,Clone)]
+pub struct WireCell(pub u8);
+
+impl From<WireCell> for u8 {
+    fn from(item: WireCell) -> Self {
+        item.0
+    }
+}
+
+impl Cell for WireCell {
+    const BITS: usize = 2;
+
+    fn from_bits(bits: u8) -> Self {
+        WireCell(bits & 3)
+    }
+}
+
+// Every state a board can hold
+impl Cell for CellState {
+    const BITS: usize = 4;
+
+    fn from_bits(bits: u8) -> Self {
+        CellState(bits & 15)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DEAD, LIVE, TAIL, WIRE};
+
+    #[test]
+    fn bits_come_back_as_stored() {
+        for s in [DEAD, LIVE] {
+            assert_eq!(u8::from(LifeCell::from_bits(s.0)), s.0);
+        }
+        for s in [DEAD, LIVE, TAIL, WIRE] {
+            assert_eq!(u8::from(WireCell::from_bits(s.0)), s.0);
+        }
+        for s in 0..16 {
+            assert_eq!(u8::from(CellState::from_bits(s)), s);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use::alloc::vec;
+
+use crate::{Graph, NodeType::Cell, Boundary, CellState};
+
+// HPP lattice gas. Each bit of a cell's state is a particle heading north 1,
+// east 2, south 4 or west 8, so an empty cell is dead.
+pub const NORTH: u8 = 1;
+pub const EAST: u8 = 2;
+pub const SOUTH: u8 = 4;
+pub const WEST: u8 = 8;
+
+const DIRECTIONS: [(u8, i16, i16); 4] = [(NORTH, 0, -1), (EAST, 1, 0), (SOUTH, 0, 1), (WEST, -1, 0)];
+
+// Combinations drawn with their own tile, the rest being these flipped
+const SHAPES: [u8; 8] = [
+    NORTH, EAST, NORTH | EAST, NORTH | SOUTH, EAST | WEST,
+    NORTH | EAST | SOUTH, NORTH | EAST | WEST, NORTH | EAST | SOUTH | WEST,
+];
+
+// Two particles meeting head on leave at right angles, any others pass
+// through each other
+fn collide(particles: u8) -> u8 {
+    match particles {
+        p if p == NORTH | SOUTH => EAST | WEST,
+        p if p == EAST | WEST => NORTH | SOUTH,
+        p => p,
+    }
+}
+
+fn reverse(direction: u8) -> u8 {
+    (direction << 2 | direction >> 2) & 15
+}
+
+// Which of the shapes a cell's particles are and whether it is flipped
+// across and down
+pub fn shape(particles: u8) -> (usize, bool, bool) {
+    let hflip = particles & WEST != 0 && particles & EAST == 0;
+    let vflip = particles & SOUTH != 0 && particles & NORTH == 0;
+    let mut unflipped = particles;
+    if hflip {
+        unflipped = unflipped & !WEST | EAST;
+    }
+    if vflip {
+        unflipped = unflipped & !SOUTH | NORTH;
+    }
+    (SHAPES.iter().position(|s| *s == unflipped).unwrap_or(0), hflip, vflip)
+}
+
+// Particles collide in each cell then all move on a cell. Off a torus those
+// reaching a reflective edge turn back and those reaching a dead one are
+// lost.
+pub fn step<M>(graph: &mut Graph<M>, width: u16, boundary: Boundary) {
+    let height = (graph.nodes.len() / width as usize) as u16;
+    let mut next = vec![0u8; graph.nodes.len()];
+    for (i, n) in graph.nodes.iter().enumerate() {
+        let particles = match n.state { Cell(s) => collide(s.into()), _ => 0 };
+        let (x, y) = ((i as u16 % width) as i16, (i as u16 / width) as i16);
+        for (direction, dx, dy) in DIRECTIONS {
+            if particles & direction == 0 {
+                continue;
+            }
+            let (tx, ty) = (x + dx, y + dy);
+            let inside = (0..width as i16).contains(&tx) && (0..height as i16).contains(&ty);
+            match boundary {
+                _ if inside => next[(ty as u16 * width + tx as u16) as usize] |= direction,
+                Boundary::Toroidal => {
+                    let (tx, ty) = (tx.rem_euclid(width as i16) as u16, ty.rem_euclid(height as i16) as u16);
+                    next[(ty * width + tx) as usize] |= direction;
+                },
+                Boundary::Reflective => next[i] |= reverse(direction),
+                Boundary::DeadEdge => (),
+            }
+        }
+    }
+    for (n, particles) in graph.nodes.iter_mut().zip(next) {
+        let state = CellState::from(particles as u16);
+        n.age = if matches!(n.state, Cell(s) if s == state) { n.age.saturating_add(1) } else { 0 };
+        n.state = Cell(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{new_world, Neighborhood, WIDTH, HEIGHT};
+
+    fn particles(graph: &Graph, node: usize) -> u8 {
+        match graph.nodes[node].state { Cell(s) => s.into(), _ => 0 }
+    }
+
+    // A head on pair turns to leave sideways, and a lone particle bounces
+    // back off a reflective edge
+    #[test]
+    fn particles_collide_and_bounce() {
+        let mut graph: Graph = new_world(WIDTH, HEIGHT, Boundary::Toroidal, Neighborhood::Moore);
+        let center = (5 * WIDTH + 5) as usize;
+        graph.nodes[center].state = Cell(CellState::from((NORTH | SOUTH) as u16));
+        step(&mut graph, WIDTH, Boundary::Toroidal);
+        assert_eq!(particles(&graph, center - 1), WEST);
+        assert_eq!(particles(&graph, center + 1), EAST);
+        assert_eq!(particles(&graph, center), 0);
+
+        let mut graph: Graph = new_world(WIDTH, HEIGHT, Boundary::Reflective, Neighborhood::Moore);
+        graph.nodes[3].state = Cell(CellState::from(NORTH as u16));
+        step(&mut graph, WIDTH, Boundary::Reflective);
+        assert_eq!(particles(&graph, 3), SOUTH);
+        step(&mut graph, WIDTH, Boundary::Reflective);
+        assert_eq!(particles(&graph, (WIDTH + 3) as usize), SOUTH);
+    }
+
+    #[test]
+    fn every_combination_has_a_shape() {
+        for particles in 1..16 {
+            let (i, hflip, vflip) = shape(particles);
+            let mut drawn = SHAPES[i];
+            if hflip {
+                drawn = drawn & !EAST | WEST;
+            }
+            if vflip {
+                drawn = drawn & !NORTH | SOUTH;
+            }
+            assert_eq!(drawn, particles);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use::alloc::vec::Vec;
+
+use core::marker::PhantomData;
+use core::mem::size_of;
+
+use crate::{Graph, CellState, NodeType::Cell};
+use crate::cell;
+
+// Ring buffer of previous boards, each cell taking the bits its type does
+pub struct History<C: cell::Cell = CellState> {
+    frames: Vec<u8>,
+    generations: Vec<u32>,
+    cells: usize,
+    frame_size: usize,
+    depth: usize,
+    next: usize,
+    len: usize,
+    cell: PhantomData<C>,
+}
+
+impl<C: cell::Cell> History<C> {
+
+    pub fn new(depth: usize, cells: usize) -> Self {
+        let mut history = History {
+            frames: Vec::new(),
+            generations: Vec::new(),
+            cells,
+            frame_size: (cells * C::BITS).div_ceil(8),
+            depth: 0,
+            next: 0,
+            len: 0,
+            cell: PhantomData,
+        };
+        history.resize(depth);
+        history
+    }
+
+    // The old buffer is freed before the new one is allocated so both are
+    // never held at once. Returns the depth actually allocated.
+    pub fn resize(&mut self, depth: usize) -> usize {
+        self.frames = Vec::new();
+        self.generations = Vec::new();
+        self.depth = 0;
+        self.next = 0;
+        self.len = 0;
+
+        let size = depth * self.frame_size;
+        if self.frames.try_reserve_exact(size).is_ok()
+            && self.generations.try_reserve_exact(depth).is_ok() {
+            self.frames.resize(size, 0);
+            self.generations.resize(depth, 0);
+            self.depth = depth;
+        } else {
+            self.frames = Vec::new();
+        }
+        self.depth
+    }
+
+    // Boards that can be held, which may be less than asked for
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn cells(&self) -> usize {
+        self.cells
+    }
+
+    pub fn push<M>(&mut self, graph: &Graph<M>, generation: u32) {
+        if self.depth == 0 {
+            return;
+        }
+        let start = self.next * self.frame_size;
+        let frame = &mut self.frames[start..start + self.frame_size];
+        frame.fill(0);
+        for (i, n) in graph.nodes.iter().enumerate() {
+            if let Cell(s) = n.state {
+                let bit = i * C::BITS;
+                let bits: u8 = C::from_bits(s.0).into();
+                frame[bit / 8] |= bits << (bit % 8);
+            }
+        }
+        self.generations[self.next] = generation;
+        self.next = (self.next + 1) % self.depth;
+        self.len = (self.len + 1).min(self.depth);
+    }
+
+    // Restores the most recent board, returning the generation it was taken at
+    pub fn pop<M>(&mut self, graph: &mut Graph<M>) -> Option<u32> {
+        if self.len == 0 {
+            return None;
+        }
+        self.next = (self.next + self.depth - 1) % self.depth;
+        self.len -= 1;
+
+        let start = self.next * self.frame_size;
+        let frame = &self.frames[start..start + self.frame_size];
+        for (i, n) in graph.nodes.iter_mut().enumerate() {
+            if let Cell(_) = n.state {
+                let bit = i * C::BITS;
+                n.state = Cell(CellState(C::from_bits(frame[bit / 8] >> (bit % 8)).into()));
+                n.age = 0;
+            }
+        }
+        Some(self.generations[self.next])
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        self.frames.capacity() + self.generations.capacity() * size_of::<u32>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{new_world, Boundary, Neighborhood};
+    use crate::cell::{LifeCell, WireCell};
+
+    fn keeps_every_state<C: cell::Cell>(states: usize) {
+        let mut graph: Graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        for (i, n) in graph.nodes.iter_mut().enumerate() {
+            n.state = Cell(CellState::from((i % states) as u16));
+        }
+        let mut history: History<C> = History::new(2, graph.nodes.len());
+        history.push(&graph, 7);
+        for n in graph.nodes.iter_mut() {
+            n.state = Cell(CellState::from(0));
+        }
+        assert_eq!(history.pop(&mut graph), Some(7));
+        for (i, n) in graph.nodes.iter().enumerate() {
+            assert!(matches!(n.state, Cell(s) if s.index() == i % states));
+        }
+    }
+
+    #[test]
+    fn keeps_every_state_of_each_width() {
+        keeps_every_state::<LifeCell>(2);
+        keeps_every_state::<WireCell>(4);
+        keeps_every_state::<CellState>(16);
+    }
+
+    // A byte holds eight Life boards' cells to Wireworld's four
+    #[test]
+    fn narrower_cells_take_less() {
+        let life: History<LifeCell> = History::new(4, 600);
+        let wire: History<WireCell> = History::new(4, 600);
+        let full: History = History::new(4, 600);
+        assert_eq!(life.frame_size * 2, wire.frame_size);
+        assert_eq!(wire.frame_size * 2, full.frame_size);
+    }
+}
@@ -0,0 +1,50 @@
+use::alloc::vec::Vec;
+
+use crate::{Graph, Rule, NodeType::Cell, LIVE};
+use crate::cell::Cell as _;
+use crate::prng::Prng;
+
+// A second board over the first, one flag per cell of it and sharing its
+// neighbors. It runs the same rules with each live cell below counting as
+// one more neighbor, so the first board seeds births on the second but
+// never the other way round.
+pub fn step<M, R: Rule>(graph: &Graph<M>, upper: &mut [bool], rule: &R, rng: &mut Prng) {
+    let counts: Vec<u16> = (0..upper.len())
+        .map(|i| {
+            let above = graph.successors(i).filter(|j| upper[*j]).count() as u16;
+            let below = matches!(graph.nodes[i].state, Cell(LIVE)) as u16;
+            (above + below).min(8)
+        })
+        .collect();
+    for (live, count) in upper.iter_mut().zip(counts) {
+        let next: u8 = rule.next_state(R::Cell::from_bits(*live as u8), count, rng).into();
+        *live = next == LIVE.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{new_world, rulestring, Boundary, Neighborhood};
+    use crate::prng::DEFAULT_SEED;
+    use alloc::vec;
+
+    // Two live cells above only make a third with the help of one below
+    #[test]
+    fn lower_cells_seed_births() {
+        let (rules, _) = rulestring::from_str("B3/S23").unwrap();
+        let mut graph: Graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        let mut upper = vec![false; graph.nodes.len()];
+        upper[8] = true;
+        upper[10] = true;
+        step(&graph, &mut upper, &rules, &mut Prng::new(DEFAULT_SEED));
+        assert!(!upper[9]);
+
+        let mut upper = vec![false; graph.nodes.len()];
+        upper[8] = true;
+        upper[10] = true;
+        graph.nodes[9].state = Cell(LIVE);
+        step(&graph, &mut upper, &rules, &mut Prng::new(DEFAULT_SEED));
+        assert!(upper[9]);
+    }
+}
@@ -0,0 +1,908 @@
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+pub mod cell;
+pub mod prng;
+pub mod order;
+pub mod rulestring;
+pub mod pattern;
+pub mod history;
+pub mod turmite;
+pub mod margolus;
+pub mod layers;
+pub mod gas;
+pub mod species;
+pub mod wator;
+pub mod sandpile;
+pub mod table;
+
+use::alloc::{vec, vec::Vec, collections::VecDeque};
+
+use core::ops::Not;
+use core::mem::size_of;
+
+use crate::prng::Prng;
+use crate::order::UpdateOrder;
+use crate::cell::{Cell as _, LifeCell, WireCell};
+
+// A board the size of the screen in tiles, for the tests
+#[cfg(test)]
+const WIDTH: u16 = 30;
+#[cfg(test)]
+const HEIGHT: u16 = 20;
+
+// Boards and the menus laid out over them are both graphs, the edges of a
+// menu taking the cursor about and those of a board joining each cell to
+// its neighbors
+pub struct Graph<M = ()> {
+    pub nodes: Vec<NodeData<M>>,
+    pub edges: Vec<EdgeData>,
+}
+
+/// A board with no menu over it, as an embedding game would use
+///
+/// ```
+/// use automata::{Grid, Boundary, Neighborhood, CellState, NodeType::Cell, new_world, LIVE};
+///
+/// let mut grid: Grid = new_world(8, 6, Boundary::Toroidal, Neighborhood::Moore);
+/// grid.nodes[8 + 1].state = Cell(LIVE);
+/// assert_eq!(grid.nodes.len(), 8 * 6);
+/// assert_eq!(grid.living_neighbors_count_of(2 * 8 + 2), 1);
+/// assert!(matches!(grid.nodes[0].state, Cell(CellState(0))));
+/// ```
+pub type Grid = Graph<()>;
+
+pub type NodeIndex = usize;
+
+// A small number rather than an enum so automata with more than two
+// states share the board, history and drawing code with Life
+#[derive(Debug,PartialEq,Eq,Copy,Clone)]
+pub struct CellState(pub u8);
+
+pub const DEAD: CellState = CellState(0);
+pub const LIVE: CellState = CellState(1);
+
+// Wireworld electron heads are live cells, so counting living neighbors
+// counts heads
+pub const TAIL: CellState = CellState(2);
+pub const WIRE: CellState = CellState(3);
+
+// Brian's Brain cells rest for a generation after firing
+pub const RESTING: CellState = CellState(2);
+
+// Immigration cells are live in one of two colors, the other being LIVE
+pub const RED: CellState = CellState(2);
+
+// Forest fire trees are live cells, burning for a generation once caught
+pub const BURNING: CellState = CellState(2);
+
+// Rock paper scissors species, rock being the live cells
+pub const PAPER: CellState = CellState(2);
+pub const SCISSORS: CellState = CellState(3);
+
+// Wa-Tor fish are live cells, hunted by sharks
+pub const FISH: CellState = LIVE;
+pub const SHARK: CellState = CellState(2);
+
+// Four bits of state per cell at most
+pub const MAX_STATES: usize = 16;
+
+impl CellState {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl From<u16> for CellState {
+    fn from(item: u16) -> Self {
+        if (item as usize) < MAX_STATES { CellState(item as u8) } else { DEAD }
+    }
+}
+
+impl From<CellState> for u8 {
+    fn from(item: CellState) -> Self {
+        item.0
+    }
+}
+
+// Toggling any other state brings the cell back to life
+impl Not for CellState {
+    type Output = Self;
+    fn not(self) -> Self::Output {
+        if self == LIVE { DEAD } else { LIVE }
+    }
+}
+
+use crate::NodeType::*;
+
+#[derive(PartialEq,Debug,Copy,Clone)]
+pub enum Boundary {
+    // Reflective edges count the cells along the edge again in place of
+    // the ones beyond it
+    Toroidal, DeadEdge, Reflective
+}
+
+impl Boundary {
+
+    pub fn next(self) -> Self {
+        match self {
+            Boundary::Toroidal => Boundary::DeadEdge,
+            Boundary::DeadEdge => Boundary::Reflective,
+            Boundary::Reflective => Boundary::Toroidal,
+        }
+    }
+}
+
+// The way an edge goes across the screen, for whatever moves a cursor
+// along it
+#[derive(PartialEq,Debug,Copy,Clone)]
+pub enum Direction {
+    Up, Down, Left, Right
+}
+
+// Hex cells are on rows with every odd row half a cell to the right, von
+// Neumann cells only touch along their sides
+#[derive(PartialEq,Debug,Copy,Clone)]
+pub enum Neighborhood {
+    Moore, Hex, VonNeumann
+}
+
+impl Neighborhood {
+
+    pub fn next(self) -> Self {
+        match self {
+            Neighborhood::Moore => Neighborhood::Hex,
+            Neighborhood::Hex => Neighborhood::VonNeumann,
+            Neighborhood::VonNeumann => Neighborhood::Moore,
+        }
+    }
+
+    // The highest count a rule can see
+    pub fn size(self) -> u16 {
+        match self {
+            Neighborhood::Moore => 8,
+            Neighborhood::Hex => 6,
+            Neighborhood::VonNeumann => 4,
+        }
+    }
+
+    // Whether a cell on row y touches the one (dx, dy) away. A hex row
+    // leans toward the diagonal on the side it is shifted to.
+    pub fn touches(self, y: i32, dx: i32, dy: i32) -> bool {
+        let away = if y % 2 == 1 { -1 } else { 1 };
+        (dx, dy) != (0, 0) && match self {
+            Neighborhood::Moore => true,
+            Neighborhood::Hex => dy == 0 || dx != away,
+            Neighborhood::VonNeumann => dx == 0 || dy == 0,
+        }
+    }
+}
+
+#[derive(PartialEq,Debug,Copy,Clone)]
+pub enum NodeType<M = ()> {
+    Cell(CellState),
+    // An entry in a menu, whatever the menu says it is
+    Menu(M),
+    // Never changes and never counts as a live neighbor
+    Wall,
+    // Never changes and always counts as one
+    Emitter,
+}
+
+#[derive(Debug)]
+pub struct NodeData<M = ()> {
+    pub state: NodeType<M>,
+    pub age: u16,
+    // The live state last in the cell, for a territory map
+    pub owner: u8,
+    pub x: u16,
+    pub y: u16,
+    pub first_outgoing_edge: Option<EdgeIndex>
+}
+
+pub type EdgeIndex = usize;
+
+pub struct EdgeData {
+    pub direction: Option<Direction>,
+    pub weight: u8,
+    pub target: NodeIndex,
+    pub next_outgoing_edge: Option<EdgeIndex>
+}
+
+impl<M> Graph<M> {
+
+    pub fn new() -> Self {
+        Graph { nodes: Vec::new(), edges: Vec::new() }
+    }
+
+    pub fn add_node(&mut self, x:u16, y:u16, state:NodeType<M>) -> NodeIndex {
+        let index = self.nodes.len();
+        self.nodes.push(NodeData { x,y,state,age: 0,owner: 0,first_outgoing_edge: None });
+        index
+    }
+
+    pub fn add_edge(&mut self, source: NodeIndex, target: NodeIndex, direction: Option<Direction>) {
+        let edge_index = self.edges.len();
+        let node_data = &mut self.nodes[source];
+        self.edges.push(EdgeData {
+            direction,
+            weight: 1,
+            target,
+            next_outgoing_edge: node_data.first_outgoing_edge
+        });
+        node_data.first_outgoing_edge = Some(edge_index);
+    }
+
+    pub fn successors(&self, source: NodeIndex) -> Successors<'_, M> {
+        let first_outgoing_edge = self.nodes[source].first_outgoing_edge;
+        Successors { graph: self, current_edge_index: first_outgoing_edge }
+    }
+
+    // The node an edge going the given way leads to, if there is one
+    pub fn towards(&self, source: NodeIndex, direction: Direction) -> Option<NodeIndex> {
+        let mut edge = self.nodes[source].first_outgoing_edge;
+        while let Some(e) = edge {
+            if self.edges[e].direction == Some(direction) {
+                return Some(self.edges[e].target);
+            }
+            edge = self.edges[e].next_outgoing_edge;
+        }
+        None
+    }
+
+    pub fn living_neighbors_count_of(&self, source: NodeIndex) -> u16 {
+        let mut n = 0;
+        for e in self.successors(source) {
+            if self.is_live(e) {
+                n += 1;
+            }
+        }
+        n
+    }
+
+    pub fn is_live(&self, node: NodeIndex) -> bool {
+        matches!(self.nodes[node].state, Cell(LIVE) | Emitter)
+    }
+
+    // Live neighbors, each counting for the weight of its edge
+    pub fn weighted_neighbors_count_of(&self, source: NodeIndex) -> u16 {
+        let mut n = 0;
+        let mut edge = self.nodes[source].first_outgoing_edge;
+        while let Some(e) = edge {
+            if self.is_live(self.edges[e].target) {
+                n += self.edges[e].weight as u16;
+            }
+            edge = self.edges[e].next_outgoing_edge;
+        }
+        n
+    }
+
+    pub fn neighbors_in_state(&self, source: NodeIndex, state: CellState) -> u16 {
+        self.successors(source)
+            .filter(|e| match self.nodes[*e].state {
+                Cell(s) => s == state,
+                Emitter => state == LIVE,
+                _ => false,
+            })
+            .count() as u16
+    }
+
+    // Breadth first out from a node, so the first live cell found is one of
+    // the fewest steps away
+    pub fn nearest_live(&self, source: NodeIndex) -> Option<NodeIndex> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut queue = VecDeque::from([source]);
+        seen[source] = true;
+        while let Some(node) = queue.pop_front() {
+            if node != source && matches!(self.nodes[node].state, Cell(LIVE)) {
+                return Some(node);
+            }
+            for next in self.successors(node) {
+                if !seen[next] {
+                    seen[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        self.nodes.capacity() * size_of::<NodeData<M>>()
+            + self.edges.capacity() * size_of::<EdgeData>()
+    }
+
+}
+
+impl<M> Default for Graph<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Successors<'graph, M> {
+    graph: &'graph Graph<M>,
+    current_edge_index: Option<EdgeIndex>
+}
+
+impl<'graph, M> Iterator for Successors<'graph, M> {
+    type Item = NodeIndex;
+
+    fn next(&mut self) -> Option<NodeIndex> {
+        match self.current_edge_index {
+            None => None,
+            Some(edge_num) => {
+                let edge = &self.graph.edges[edge_num];
+                self.current_edge_index = edge.next_outgoing_edge;
+                Some(edge.target)
+            }
+        }
+    }
+}
+
+pub fn new_world<M>(width: u16, height: u16, boundary: Boundary, neighborhood: Neighborhood) -> Graph<M> {
+    let mut graph = Graph::new();
+    for i in 0..width*height {
+        graph.add_node(i%width, i/width, Cell(DEAD));
+    }
+    let wrap = boundary == Boundary::Toroidal;
+    for i in 0..width {
+    for j in 0..height {
+        let wraps_right = i+1 == width;
+        let wraps_left  = i == 0;
+        let wraps_down  = j+1 == height;
+        let n_right      = (i + 1) % width + j*width;
+        let n_down       = ((j+1) % height)*width+i;
+        let n_down_right = ((j+1) % height)*width+(i+1)%width;
+        let n_down_left  = ((j+1) % height)*width+(i+width-1)%width;
+        let n = j*width+i;
+        if wrap || !wraps_right {
+            graph.add_edge(n.into(), n_right.into(), Some(Direction::Right));
+            graph.add_edge(n_right.into(), n.into(), Some(Direction::Left));
+        }
+        if wrap || !wraps_down {
+            graph.add_edge(n.into(), n_down.into(), Some(Direction::Down));
+            graph.add_edge(n_down.into(), n.into(), Some(Direction::Up));
+        }
+        // A hex row only touches the row below on the side it leans to
+        let odd = j % 2 == 1;
+        let (down_right, down_left) = match neighborhood {
+            Neighborhood::Moore => (true, true),
+            Neighborhood::Hex => (odd, !odd),
+            Neighborhood::VonNeumann => (false, false),
+        };
+        if down_right && (wrap || !(wraps_right || wraps_down)) {
+            graph.add_edge(n.into(), n_down_right.into(), None);
+            graph.add_edge(n_down_right.into(), n.into(), None);
+        }
+        if down_left && (wrap || !(wraps_left || wraps_down)) {
+            graph.add_edge(n.into(), n_down_left.into(), None);
+            graph.add_edge(n_down_left.into(), n.into(), None);
+        }
+    }}
+    if boundary == Boundary::Reflective {
+        reflect_edges(&mut graph, width, height, neighborhood);
+    }
+    graph
+}
+
+// Each neighbor beyond the edge is stood in for by the nearest cell on the
+// board, which may be the cell itself
+fn reflect_edges<M>(graph: &mut Graph<M>, width: u16, height: u16, neighborhood: Neighborhood) {
+    let (w, h) = (width as i32, height as i32);
+    for n in 0..graph.nodes.len() {
+        let (x, y) = (n as i32 % w, n as i32 / w);
+        for dy in -1..=1 {
+        for dx in -1..=1 {
+            let (nx, ny) = (x + dx, y + dy);
+            let inside = (0..w).contains(&nx) && (0..h).contains(&ny);
+            if inside || !neighborhood.touches(y, dx, dy) {
+                continue;
+            }
+            graph.add_edge(n, (ny.clamp(0, h - 1) * w + nx.clamp(0, w - 1)) as NodeIndex, None);
+        }}
+    }
+}
+
+// Weighs each edge of a board for the place around its source it stands
+// for. Stand-ins beyond a reflective edge may share a target, but as edges
+// to the same cell count together it doesn't matter which gets which weight.
+pub fn weigh_edges<M>(graph: &mut Graph<M>, width: u16, boundary: Boundary, neighborhood: Neighborhood,
+                      weights: &[u8; 9]) {
+    let w = width as i32;
+    let h = (graph.nodes.len() / width as usize) as i32;
+    for n in 0..graph.nodes.len() {
+        let (x, y) = (n as i32 % w, n as i32 / w);
+        let mut places = Vec::new();
+        for dy in -1..=1 {
+        for dx in -1..=1 {
+            if !neighborhood.touches(y, dx, dy) {
+                continue;
+            }
+            let (nx, ny) = (x + dx, y + dy);
+            let (nx, ny) = match boundary {
+                Boundary::Toroidal => (nx.rem_euclid(w), ny.rem_euclid(h)),
+                Boundary::Reflective => (nx.clamp(0, w - 1), ny.clamp(0, h - 1)),
+                Boundary::DeadEdge => (nx, ny),
+            };
+            places.push(((ny * w + nx) as NodeIndex, weights[((dy + 1) * 3 + dx + 1) as usize]));
+        }}
+        let mut edge = graph.nodes[n].first_outgoing_edge;
+        while let Some(e) = edge {
+            if let Some(i) = places.iter().position(|(target, _)| *target == graph.edges[e].target) {
+                graph.edges[e].weight = places.swap_remove(i).1;
+            }
+            edge = graph.edges[e].next_outgoing_edge;
+        }
+    }
+}
+
+/// How the cells of a board change from one generation to the next, going
+/// by each cell's state and its count of live neighbors. The rule's cell
+/// type sets how many bits a state takes.
+///
+/// ```
+/// use automata::Rule;
+/// use automata::cell::LifeCell;
+/// use automata::prng::Prng;
+///
+/// // Birth and survival rows, a 1 where the count brings the cell to life
+/// let life = [[0, 0, 0, 1, 0, 0, 0, 0, 0], [0, 0, 1, 1, 0, 0, 0, 0, 0]];
+/// let mut rng = Prng::new(1);
+/// assert_eq!(life.next_state(LifeCell(false), 3, &mut rng), LifeCell(true));
+/// assert_eq!(life.next_state(LifeCell(true), 4, &mut rng), LifeCell(false));
+///
+/// // Or one of its own, here a cell lives on any odd count
+/// struct Parity;
+/// impl Rule for Parity {
+///     type Cell = LifeCell;
+///     fn next_state(&self, _: LifeCell, neighbors: u16, _: &mut Prng) -> LifeCell {
+///         LifeCell(neighbors % 2 == 1)
+///     }
+/// }
+/// assert_eq!(Parity.next_state(LifeCell(true), 1, &mut rng), LifeCell(true));
+/// ```
+pub trait Rule {
+    type Cell: cell::Cell;
+
+    fn next_state(&self, state: Self::Cell, neighbors: u16, rng: &mut Prng) -> Self::Cell;
+
+    fn neighbors<M>(&self, graph: &Graph<M>, node: NodeIndex, _width: u16, _boundary: Boundary) -> u16 {
+        graph.living_neighbors_count_of(node)
+    }
+
+    // The state a dead cell coming to life takes
+    fn newborn<M>(&self, _graph: &Graph<M>, _node: NodeIndex) -> CellState {
+        LIVE
+    }
+
+    // The next state once the cell's age is taken into account
+    fn outlived(&self, _state: CellState, next: CellState, _age: u16) -> CellState {
+        next
+    }
+
+    fn order(&self) -> UpdateOrder {
+        UpdateOrder::Synchronous
+    }
+
+    // Cells from the nearest live one a dead cell can come to life, None
+    // when it can anywhere
+    fn reach(&self) -> Option<i32> {
+        None
+    }
+}
+
+// Birth and survival rows as the config menu shows them, for two states
+impl Rule for [[u16; 9]; 2] {
+    type Cell = LifeCell;
+
+    fn next_state(&self, state: LifeCell, neighbors: u16, _rng: &mut Prng) -> LifeCell {
+        LifeCell(self[state.0 as usize][neighbors as usize] == 1)
+    }
+
+    fn reach(&self) -> Option<i32> {
+        (self[0][0] == 0).then_some(1)
+    }
+}
+
+// Life rules where a cell that does not survive decays through the states
+// after LIVE before dying, blocking births as it does
+pub struct Generations {
+    pub rules: [[u16; 9]; 2],
+    pub states: u8,
+}
+
+impl Rule for Generations {
+    type Cell = CellState;
+
+    fn next_state(&self, state: CellState, neighbors: u16, rng: &mut Prng) -> CellState {
+        match state {
+            DEAD | LIVE => match self.rules.next_state(LifeCell(state == LIVE), neighbors, rng) {
+                LifeCell(true) => LIVE,
+                LifeCell(false) if state == LIVE && self.states > 2 => CellState(2),
+                LifeCell(false) => DEAD,
+            },
+            CellState(s) => if s + 1 < self.states { CellState(s + 1) } else { DEAD },
+        }
+    }
+
+    fn reach(&self) -> Option<i32> {
+        self.rules.reach()
+    }
+}
+
+// Electron heads become tails and tails wire again, while wire carries a
+// head on from one or two heads beside it
+pub struct Wireworld;
+
+impl Rule for Wireworld {
+    type Cell = WireCell;
+
+    fn next_state(&self, state: WireCell, neighbors: u16, _rng: &mut Prng) -> WireCell {
+        let next = match CellState(state.0) {
+            LIVE => TAIL,
+            TAIL => WIRE,
+            WIRE if neighbors == 1 || neighbors == 2 => LIVE,
+            WIRE => WIRE,
+            _ => DEAD,
+        };
+        WireCell(next.0)
+    }
+}
+
+// Left, top, right and bottom of the cells that aren't dead
+pub fn live_bounds<M>(graph: &Graph<M>) -> Option<(i32, i32, i32, i32)> {
+    graph.nodes.iter()
+        .filter(|n| matches!(n.state, Cell(s) if s != DEAD) || matches!(n.state, Emitter))
+        .fold(None, |bounds, n| {
+            let (x, y) = (n.x as i32, n.y as i32);
+            Some(match bounds {
+                None => (x, y, x, y),
+                Some((l, t, r, b)) => (l.min(x), t.min(y), r.max(x), b.max(y)),
+            })
+        })
+}
+
+// The part of a board whose neighbors are worth counting. When nothing is
+// born from no neighbors, a dead cell out of reach of every other cell
+// stays dead, so only those around the live cells need counting. None when
+// every cell does, as with cells changing in place.
+pub fn active_region<M, R: Rule>(graph: &Graph<M>, rule: &R, width: u16, boundary: Boundary)
+                                 -> Option<(i32, i32, i32, i32)> {
+    if rule.order().in_place() {
+        return None;
+    }
+    let reach = rule.reach()?;
+    let Some((left, top, right, bottom)) = live_bounds(graph) else {
+        return Some((0, 0, -1, -1));
+    };
+    let (w, h) = (width as i32, (graph.nodes.len() / width as usize) as i32);
+    // Round a torus the reach comes back in from the far edge
+    let wraps = boundary == Boundary::Toroidal;
+    let (left, right) = match (left - reach, right + reach) {
+        (l, r) if wraps && (l < 0 || r >= w) => (0, w - 1),
+        span => span,
+    };
+    let (top, bottom) = match (top - reach, bottom + reach) {
+        (t, b) if wraps && (t < 0 || b >= h) => (0, h - 1),
+        span => span,
+    };
+    Some((left, top, right, bottom))
+}
+
+pub fn in_region<M>(region: Option<(i32, i32, i32, i32)>, n: &NodeData<M>) -> bool {
+    region.is_none_or(|(left, top, right, bottom)| {
+        (left..=right).contains(&(n.x as i32)) && (top..=bottom).contains(&(n.y as i32))
+    })
+}
+
+// A cell's neighbor count and the state it would be born in, as worked
+// out for every cell before a generation changes any
+type Counted = (u16, CellState);
+
+// Bytes the counts of a board take from the heap while it steps
+pub fn step_memory<M>(graph: &Graph<M>) -> usize {
+    graph.nodes.len() * size_of::<Counted>()
+}
+
+// One generation of a board
+pub fn step_graph<M, R: Rule>(graph: &mut Graph<M>, rule: &R, width: u16, boundary: Boundary, rng: &mut Prng) {
+    step_graph_with(graph, rule, width, boundary, rng, |_, _, _| ());
+}
+
+// One generation of a board, handing each cell on once it has moved to
+// the next along with the state it had, for whatever draws or counts them.
+// Its age goes by the state it is left in, so the visit can change that too.
+pub fn step_graph_with<M, R: Rule>(graph: &mut Graph<M>, rule: &R, width: u16, boundary: Boundary,
+                                   rng: &mut Prng, mut visit: impl FnMut(NodeIndex, &mut NodeData<M>, CellState)) {
+    let region = active_region(graph, rule, width, boundary);
+    let mut neighbors: Vec<Counted> = (0..graph.nodes.len())
+        .map(|i| if in_region(region, &graph.nodes[i]) {
+            (rule.neighbors(graph, i, width, boundary), rule.newborn(graph, i))
+        } else {
+            (0, LIVE)
+        })
+        .collect();
+    let order = rule.order();
+    for i in order.cells(graph.nodes.len(), rng) {
+        // Out of step, from the board as far as it has got
+        if order.in_place() {
+            neighbors[i] = (rule.neighbors(graph, i, width, boundary), rule.newborn(graph, i));
+        }
+        let (count, color) = neighbors[i];
+        let n = &mut graph.nodes[i];
+        if let Cell(s) = n.state {
+            let next = match CellState(rule.next_state(R::Cell::from_bits(s.0), count, rng).into()) {
+                LIVE if s == DEAD => color,
+                next => next,
+            };
+            let next = rule.outlived(s, next, n.age);
+            if next != DEAD || s != DEAD {
+                n.owner = if next != DEAD { next.0 } else { s.0 };
+            }
+            n.state = Cell(next);
+            visit(i, n, s);
+            n.age = if matches!(n.state, Cell(next) if next == s) { n.age.saturating_add(1) } else { 0 };
+        }
+    }
+}
+
+/// The cells of one board stepped some generations on into another of the
+/// same size, leaving the first as it was
+///
+/// ```
+/// use automata::{Grid, Boundary, Neighborhood, NodeType::Cell, new_world, simulate_into, LIVE};
+/// use automata::prng::Prng;
+///
+/// let life = [[0, 0, 0, 1, 0, 0, 0, 0, 0], [0, 0, 1, 1, 0, 0, 0, 0, 0]];
+/// let mut board: Grid = new_world(5, 5, Boundary::Toroidal, Neighborhood::Moore);
+/// for i in [7, 12, 17] {
+///     board.nodes[i].state = Cell(LIVE);
+/// }
+/// let live = |g: &Grid| -> Vec<usize> {
+///     (0..g.nodes.len()).filter(|&i| matches!(g.nodes[i].state, Cell(LIVE))).collect()
+/// };
+///
+/// // A blinker lies down after a generation and stands back up after two
+/// let mut ahead: Grid = new_world(5, 5, Boundary::Toroidal, Neighborhood::Moore);
+/// simulate_into(&board, &mut ahead, &life, 5, Boundary::Toroidal, 1, &mut Prng::new(1));
+/// assert_eq!(live(&ahead), [11, 12, 13]);
+/// simulate_into(&board, &mut ahead, &life, 5, Boundary::Toroidal, 2, &mut Prng::new(1));
+/// assert_eq!(live(&ahead), live(&board));
+/// ```
+pub fn simulate_into<M, R: Rule>(from: &Graph<M>, into: &mut Graph<M>, rule: &R,
+                                 width: u16, boundary: Boundary, generations: u32, rng: &mut Prng) {
+    for (copy, n) in into.nodes.iter_mut().zip(&from.nodes) {
+        if let Cell(s) = n.state {
+            copy.state = Cell(s);
+            copy.age = n.age;
+        }
+    }
+    for _ in 0..generations {
+        step_graph(into, rule, width, boundary, rng);
+    }
+}
+
+// Live cells within two steps across or down, counted from the positions
+// as the edges for them would take too much memory
+pub fn range_count<M>(graph: &Graph<M>, node: NodeIndex, width: u16, boundary: Boundary) -> u16 {
+    let w = width as i32;
+    let h = (graph.nodes.len() / width as usize) as i32;
+    let (x, y) = (node as i32 % w, node as i32 / w);
+    let mut count = 0;
+    for dy in -2..=2 {
+    for dx in -2..=2 {
+        let (mut nx, mut ny) = (x + dx, y + dy);
+        let inside = (0..w).contains(&nx) && (0..h).contains(&ny);
+        if (dx, dy) == (0, 0) || !(inside || boundary != Boundary::DeadEdge) {
+            continue;
+        }
+        if boundary == Boundary::Reflective {
+            (nx, ny) = (nx.clamp(0, w - 1), ny.clamp(0, h - 1));
+        }
+        if graph.is_live((ny.rem_euclid(h) * w + nx.rem_euclid(w)) as usize) {
+            count += 1;
+        }
+    }}
+    count
+}
+
+// The new generation goes in below the bottom row and every row moves up
+pub fn step_elementary<M>(graph: &mut Graph<M>, rule: u8, width: u16, boundary: Boundary) {
+    let width = width as usize;
+    let bottom = graph.nodes.len() - width;
+    let live: Vec<bool> = graph.nodes[bottom..].iter()
+        .map(|n| matches!(n.state, Cell(LIVE)))
+        .collect();
+    for i in 0..bottom {
+        let below = match graph.nodes[i + width].state { Cell(s) => s, _ => DEAD };
+        graph.nodes[i].state = Cell(below);
+        graph.nodes[i].age = 0;
+    }
+    let beyond = |wrapped: usize, edge: usize| match boundary {
+        Boundary::Toroidal => live[wrapped],
+        Boundary::DeadEdge => false,
+        Boundary::Reflective => live[edge],
+    };
+    for x in 0..width {
+        let left = if x > 0 { live[x - 1] } else { beyond(width - 1, 0) };
+        let right = if x + 1 < width { live[x + 1] } else { beyond(0, width - 1) };
+        let neighborhood = (left as u8) << 2 | (live[x] as u8) << 1 | right as u8;
+        let n = &mut graph.nodes[bottom + x];
+        n.state = Cell(if rule >> neighborhood & 1 == 1 { LIVE } else { DEAD });
+        n.age = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Counted in moves along the edges, so a diagonal is as close as a
+    // neighbor beside it, and the starting cell itself never counts
+    #[test]
+    fn finds_the_nearest_live_cell() {
+        let mut graph: Graph = new_world(WIDTH, HEIGHT, Boundary::Toroidal, Neighborhood::Moore);
+        let at = |x: u16, y: u16| (y * WIDTH + x) as NodeIndex;
+        assert_eq!(graph.nearest_live(at(5, 5)), None);
+        graph.nodes[at(5, 5)].state = Cell(LIVE);
+        graph.nodes[at(9, 9)].state = Cell(LIVE);
+        graph.nodes[at(2, 8)].state = Cell(LIVE);
+        assert_eq!(graph.nearest_live(at(5, 5)), Some(at(2, 8)));
+        assert_eq!(graph.nearest_live(at(8, 8)), Some(at(9, 9)));
+        // Round the back of the torus
+        assert_eq!(graph.nearest_live(at(WIDTH - 1, 8)), Some(at(2, 8)));
+    }
+
+    // A lone cell is seen from the 24 around it, on both sides of an edge
+    // only round a torus
+    #[test]
+    fn range_counts_two_cells_out() {
+        for boundary in [Boundary::Toroidal, Boundary::DeadEdge] {
+            let mut graph: Graph = new_world(8, 8, boundary, Neighborhood::Moore);
+            graph.nodes[8 + 7].state = Cell(LIVE);
+            for node in 0..graph.nodes.len() {
+                let (x, y) = (node as i32 % 8, node as i32 / 8);
+                let (dx, dy) = (x - 7, y - 1);
+                let wrapped = dx.abs().min(8 - dx.abs()).max(dy.abs().min(8 - dy.abs()));
+                let near = if boundary == Boundary::Toroidal { wrapped } else { dx.abs().max(dy.abs()) };
+                let expected = (node != 8 + 7 && near <= 2) as u16;
+                assert_eq!(range_count(&graph, node, 8, boundary), expected);
+            }
+        }
+    }
+
+    // Rule 90 splits a single cell in two and keeps the old row above
+    #[test]
+    fn elementary_rows_scroll_up() {
+        let mut graph: Graph = new_world(WIDTH, HEIGHT, Boundary::Toroidal, Neighborhood::Moore);
+        let bottom = ((HEIGHT - 1) * WIDTH) as usize;
+        graph.nodes[bottom].state = Cell(LIVE);
+        step_elementary(&mut graph, 90, WIDTH, Boundary::Toroidal);
+        let live: Vec<NodeIndex> = (0..graph.nodes.len())
+            .filter(|i| matches!(graph.nodes[*i].state, Cell(LIVE)))
+            .collect();
+        assert_eq!(live, [bottom - WIDTH as usize, bottom + 1, bottom + WIDTH as usize - 1]);
+
+        // Nothing comes round from the other side at a dead edge
+        let mut graph: Graph = new_world(WIDTH, HEIGHT, Boundary::DeadEdge, Neighborhood::Moore);
+        graph.nodes[bottom].state = Cell(LIVE);
+        step_elementary(&mut graph, 90, WIDTH, Boundary::DeadEdge);
+        assert!(matches!(graph.nodes[bottom + WIDTH as usize - 1].state, Cell(DEAD)));
+
+        // A reflective edge sees the cell as its own neighbor, so it lives on
+        let mut graph: Graph = new_world(WIDTH, HEIGHT, Boundary::Reflective, Neighborhood::Moore);
+        graph.nodes[bottom].state = Cell(LIVE);
+        step_elementary(&mut graph, 90, WIDTH, Boundary::Reflective);
+        assert!(matches!(graph.nodes[bottom].state, Cell(LIVE)));
+    }
+
+    // A lone live cell under B3/S23/C3 decays for a generation first
+    #[test]
+    fn generations_decay_before_dying() {
+        let rule = Generations { rules: crate::rulestring::from_str("B3/S23").unwrap().0, states: 3 };
+        let mut graph: Graph = new_world(WIDTH, HEIGHT, Boundary::Toroidal, Neighborhood::Moore);
+        let mut rng = Prng::new(crate::prng::DEFAULT_SEED);
+        graph.nodes[0].state = Cell(LIVE);
+        step_graph(&mut graph, &rule, WIDTH, Boundary::Toroidal, &mut rng);
+        assert!(matches!(graph.nodes[0].state, Cell(CellState(2))));
+        step_graph(&mut graph, &rule, WIDTH, Boundary::Toroidal, &mut rng);
+        assert!(matches!(graph.nodes[0].state, Cell(DEAD)));
+    }
+
+    // A head runs along a wire, leaving a tail behind it
+    #[test]
+    fn heads_run_along_wire() {
+        let mut graph: Graph = new_world(WIDTH, HEIGHT, Boundary::Toroidal, Neighborhood::Moore);
+        let mut rng = Prng::new(crate::prng::DEFAULT_SEED);
+        for (i, s) in [TAIL, LIVE, WIRE, WIRE].into_iter().enumerate() {
+            graph.nodes[i].state = Cell(s);
+        }
+        step_graph(&mut graph, &Wireworld, WIDTH, Boundary::Toroidal, &mut rng);
+        let states: Vec<CellState> = (0..4)
+            .map(|i| match graph.nodes[i].state { Cell(s) => s, _ => DEAD })
+            .collect();
+        assert_eq!(states, [WIRE, TAIL, LIVE, WIRE]);
+    }
+
+    // Every small board shape, so the wrapping of each edge and corner
+    // gets its turn
+    fn each_world(mut check: impl FnMut(&Graph, u16, u16, Boundary, Neighborhood)) {
+        for neighborhood in [Neighborhood::Moore, Neighborhood::Hex, Neighborhood::VonNeumann] {
+        for boundary in [Boundary::Toroidal, Boundary::DeadEdge, Boundary::Reflective] {
+        for width in 1..=8 {
+        for height in 1..=8 {
+            let graph = new_world(width, height, boundary, neighborhood);
+            check(&graph, width, height, boundary, neighborhood);
+        }}}}
+    }
+
+    // Except where a reflected hex edge mirrors a cell onto a row leaning
+    // the other way, which only happens on the edge and does happen
+    #[test]
+    fn neighbors_are_symmetric() {
+        let mut one_way = false;
+        each_world(|graph, width, height, boundary, neighborhood| {
+            let on_edge = |n: NodeIndex| {
+                let (x, y) = (n as u16 % width, n as u16 / width);
+                x == 0 || y == 0 || x == width - 1 || y == height - 1
+            };
+            for a in 0..graph.nodes.len() {
+                for b in graph.successors(a) {
+                    let there = graph.successors(a).filter(|n| *n == b).count();
+                    let back = graph.successors(b).filter(|n| *n == a).count();
+                    if there != back {
+                        assert!(boundary == Boundary::Reflective && neighborhood == Neighborhood::Hex);
+                        assert!(on_edge(a) && on_edge(b));
+                        one_way = true;
+                    }
+                }
+            }
+        });
+        assert!(one_way);
+    }
+
+    // Shapes of at least 3x3 never wrap round onto the same cell twice, so
+    // there the neighbors can be compared with the eight around each cell,
+    // the six of a hex cell or the four beside one. Hex rows only line up
+    // round a torus of even height.
+    #[test]
+    fn neighbors_match_coordinates() {
+        each_world(|graph, width, height, boundary, neighborhood| {
+            let hex = neighborhood == Neighborhood::Hex;
+            if width < 3 || height < 3 || (hex && boundary == Boundary::Toroidal && height % 2 == 1) {
+                return;
+            }
+            let (w, h) = (width as i32, height as i32);
+            for a in 0..graph.nodes.len() {
+                let (x, y) = (a as i32 % w, a as i32 / w);
+                // The diagonal a hex row does not lean toward
+                let away = if y % 2 == 1 { -1 } else { 1 };
+                let mut expected = Vec::new();
+                for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (nx, ny) = (x + dx, y + dy);
+                    let inside = (0..w).contains(&nx) && (0..h).contains(&ny);
+                    let touching = match neighborhood {
+                        Neighborhood::Moore => true,
+                        Neighborhood::Hex => dy == 0 || dx != away,
+                        Neighborhood::VonNeumann => dx == 0 || dy == 0,
+                    };
+                    if (dx, dy) == (0, 0) || !touching {
+                        continue;
+                    }
+                    match boundary {
+                        Boundary::Toroidal => expected.push((ny.rem_euclid(h) * w + nx.rem_euclid(w)) as NodeIndex),
+                        Boundary::DeadEdge if !inside => (),
+                        _ => expected.push((ny.clamp(0, h - 1) * w + nx.clamp(0, w - 1)) as NodeIndex),
+                    }
+                }}
+                let mut actual: Vec<NodeIndex> = graph.successors(a).collect();
+                expected.sort();
+                actual.sort();
+                assert_eq!(actual, expected);
+            }
+        });
+    }
+}
@@ -0,0 +1,66 @@
+use::alloc::vec::Vec;
+
+use crate::NodeIndex;
+use crate::prng::Prng;
+
+// Which cells see the board as it was and which see it part way through a
+// generation. Out of step, each cell changes in place as soon as it is
+// worked out, so those after it count it as it is now.
+#[derive(PartialEq,Debug,Copy,Clone)]
+pub enum UpdateOrder {
+    Synchronous,
+    // Every cell once a generation, shuffled anew each time
+    Random,
+    // Across each row from the top left
+    Scan,
+}
+
+impl UpdateOrder {
+
+    pub fn next(self) -> Self {
+        match self {
+            UpdateOrder::Synchronous => UpdateOrder::Random,
+            UpdateOrder::Random => UpdateOrder::Scan,
+            UpdateOrder::Scan => UpdateOrder::Synchronous,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            UpdateOrder::Synchronous => "  ",
+            UpdateOrder::Random => "RN",
+            UpdateOrder::Scan => "SC",
+        }
+    }
+
+    pub fn in_place(self) -> bool {
+        self != UpdateOrder::Synchronous
+    }
+
+    // The cells of a board in the order they are updated
+    pub fn cells(self, count: usize, rng: &mut Prng) -> Vec<NodeIndex> {
+        let mut cells: Vec<NodeIndex> = (0..count).collect();
+        if self == UpdateOrder::Random {
+            for i in (1..count).rev() {
+                cells.swap(i, rng.below(i as u32 + 1) as usize);
+            }
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prng::DEFAULT_SEED;
+
+    // Shuffled, but still every cell exactly once
+    #[test]
+    fn random_order_visits_every_cell() {
+        let mut rng = Prng::new(DEFAULT_SEED);
+        let mut cells = UpdateOrder::Random.cells(100, &mut rng);
+        assert_ne!(cells, UpdateOrder::Scan.cells(100, &mut rng));
+        cells.sort();
+        assert_eq!(cells, UpdateOrder::Scan.cells(100, &mut rng));
+    }
+}
@@ -0,0 +1,322 @@
+use crate::{Graph, NodeType::Cell, CellState, LIVE, TAIL, WIRE};
+
+pub const STAMP_SIZE: u16 = 16;
+
+pub struct Pattern {
+    pub width: u16,
+    pub height: u16,
+    pub cells: &'static [(u16, u16)],
+}
+
+pub const GLIDER: Pattern = Pattern {
+    width: 3,
+    height: 3,
+    cells: &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+};
+
+pub const BLINKER: Pattern = Pattern {
+    width: 3,
+    height: 1,
+    cells: &[(0, 0), (1, 0), (2, 0)],
+};
+
+pub const LWSS: Pattern = Pattern {
+    width: 5,
+    height: 4,
+    cells: &[(1, 0), (4, 0), (0, 1), (0, 2), (4, 2), (0, 3), (1, 3), (2, 3), (3, 3)],
+};
+
+// Methuselahs, small patterns that take a long time to settle
+
+pub const R_PENTOMINO: Pattern = Pattern {
+    width: 3,
+    height: 3,
+    cells: &[(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)],
+};
+
+pub const DIEHARD: Pattern = Pattern {
+    width: 8,
+    height: 3,
+    cells: &[(6, 0), (0, 1), (1, 1), (1, 2), (5, 2), (6, 2), (7, 2)],
+};
+
+pub const ACORN: Pattern = Pattern {
+    width: 7,
+    height: 3,
+    cells: &[(1, 0), (3, 1), (0, 2), (1, 2), (4, 2), (5, 2), (6, 2)],
+};
+
+pub const PI_HEPTOMINO: Pattern = Pattern {
+    width: 3,
+    height: 3,
+    cells: &[(0, 0), (1, 0), (2, 0), (0, 1), (2, 1), (0, 2), (2, 2)],
+};
+
+// Patterns in ROM, listed before the stamp slots in the pattern browser
+pub const PATTERNS: [(&str, &Pattern); 7] = [
+    ("GLIDER", &GLIDER),
+    ("BLINKER", &BLINKER),
+    ("LWSS", &LWSS),
+    ("R-PENT", &R_PENTOMINO),
+    ("DIEHARD", &DIEHARD),
+    ("ACORN", &ACORN),
+    ("PI-HEPT", &PI_HEPTOMINO),
+];
+
+// What a kiosk cycles through, each a rule with a pattern in the middle of
+// the board or a random soup when there is none
+pub struct Show {
+    pub name: &'static str,
+    pub rule: &'static str,
+    pub pattern: Option<&'static Pattern>,
+}
+
+pub const SHOWS: [Show; 5] = [
+    Show { name: "R-PENTOMINO", rule: "B3/S23", pattern: Some(&R_PENTOMINO) },
+    Show { name: "HIGHLIFE", rule: "B36/S23", pattern: None },
+    Show { name: "ACORN", rule: "B3/S23", pattern: Some(&ACORN) },
+    Show { name: "DAY AND NIGHT", rule: "B3678/S34678", pattern: None },
+    Show { name: "PI-HEPTOMINO", rule: "B3/S23", pattern: Some(&PI_HEPTOMINO) },
+];
+
+// Four gliders flying abreast, far enough apart to keep clear of each other
+pub const GLIDER_SALUTE: Pattern = Pattern {
+    width: 18,
+    height: 18,
+    cells: &[(16, 0), (17, 1), (15, 2), (16, 2), (17, 2),
+             (11, 5), (12, 6), (10, 7), (11, 7), (12, 7),
+             (6, 10), (7, 11), (5, 12), (6, 12), (7, 12),
+             (1, 15), (2, 16), (0, 17), (1, 17), (2, 17)],
+};
+
+// The show a hidden button code puts on, with a card to go over it
+pub const TRIBUTE: Show = Show { name: "JOHN CONWAY", rule: "B3/S23", pattern: Some(&GLIDER_SALUTE) };
+pub const TRIBUTE_CARD: [&str; 3] = ["IN MEMORY OF", "JOHN HORTON CONWAY", "1937 - 2020"];
+
+// A Wireworld component, one string per row with '#' for wire and 'H' and
+// 'T' for the head and tail of an electron. Signals come in on the left
+// edge and leave on the right.
+pub struct Circuit {
+    pub width: u16,
+    pub height: u16,
+    pub rows: &'static [&'static str],
+}
+
+// A loop with an electron going round it, ticking the output once a lap
+pub const CLOCK_6: Circuit = Circuit {
+    width: 5,
+    height: 3,
+    rows: &[".HT..",
+            "#..##",
+            ".##.."],
+};
+
+pub const CLOCK_8: Circuit = Circuit {
+    width: 6,
+    height: 3,
+    rows: &[".HT#..",
+            "#...##",
+            ".###.."],
+};
+
+pub const CLOCK_12: Circuit = Circuit {
+    width: 7,
+    height: 4,
+    rows: &[".HT##..",
+            "#....#.",
+            "#....##",
+            ".####.."],
+};
+
+pub const CLOCK_16: Circuit = Circuit {
+    width: 8,
+    height: 5,
+    rows: &[".HT###..",
+            "#.....#.",
+            "#.....##",
+            "#.....#.",
+            ".#####.."],
+};
+
+// Passes signals left to right only
+pub const DIODE: Circuit = Circuit {
+    width: 5,
+    height: 3,
+    rows: &[".##..",
+            "##.##",
+            ".##.."],
+};
+
+// Inputs on the top and bottom rows, output in the middle
+pub const OR: Circuit = Circuit {
+    width: 4,
+    height: 3,
+    rows: &["#...",
+            ".###",
+            "#..."],
+};
+
+// Two electrons arriving together cancel out
+pub const XOR: Circuit = Circuit {
+    width: 5,
+    height: 5,
+    rows: &["#....",
+            ".##..",
+            "..###",
+            ".##..",
+            "#...."],
+};
+
+// The top input passes unless the bottom one arrives with it
+pub const AND_NOT: Circuit = Circuit {
+    width: 5,
+    height: 4,
+    rows: &["#....",
+            ".##..",
+            ".####",
+            "#.#.."],
+};
+
+// Wireworld components, listed in the pattern browser in place of the Life
+// patterns
+pub const CIRCUITS: [(&str, &Circuit); 8] = [
+    ("CLOCK6", &CLOCK_6),
+    ("CLOCK8", &CLOCK_8),
+    ("CLOCK12", &CLOCK_12),
+    ("CLOCK16", &CLOCK_16),
+    ("DIODE", &DIODE),
+    ("OR", &OR),
+    ("XOR", &XOR),
+    ("AND-NOT", &AND_NOT),
+];
+
+impl Circuit {
+
+    pub fn state(&self, x: u16, y: u16) -> Option<CellState> {
+        match self.rows[y as usize].as_bytes()[x as usize] {
+            b'#' => Some(WIRE),
+            b'H' => Some(LIVE),
+            b'T' => Some(TAIL),
+            _ => None,
+        }
+    }
+}
+
+// Sets the circuit's cells in the world graph, wrapping at the edges, and
+// leaves the empty ones alone
+pub fn place_circuit<M>(graph: &mut Graph<M>, width: u16, circuit: &Circuit, x: u16, y: u16) {
+    let height = (graph.nodes.len() / width as usize) as u16;
+    for j in 0..circuit.height {
+    for i in 0..circuit.width {
+        if let Some(s) = circuit.state(i, j) {
+            let n = &mut graph.nodes[(((y + j) % height) * width + (x + i) % width) as usize];
+            n.state = Cell(s);
+            n.age = 0;
+        }
+    }}
+}
+
+impl Pattern {
+
+    // Orientation bits 0-1 rotate clockwise by quarter turns, bit 2 mirrors
+    pub fn transform(&self, cell: (u16, u16), orientation: u8) -> (u16, u16) {
+        let (mut x, mut y) = cell;
+        let (mut w, mut h) = (self.width, self.height);
+        if orientation & 4 != 0 {
+            x = w - 1 - x;
+        }
+        for _ in 0..(orientation & 3) {
+            (x, y) = (h - 1 - y, x);
+            (w, h) = (h, w);
+        }
+        (x, y)
+    }
+
+    // Width and height once rotated
+    pub fn size(&self, orientation: u8) -> (u16, u16) {
+        if orientation & 1 != 0 {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        }
+    }
+}
+
+// Sets the pattern's cells live on a board, wrapping at the edges
+pub fn stamp<M>(graph: &mut Graph<M>, width: u16, pattern: &Pattern, x: u16, y: u16, orientation: u8) {
+    let height = (graph.nodes.len() / width as usize) as u16;
+    for &cell in pattern.cells {
+        let (dx, dy) = pattern.transform(cell, orientation);
+        let i = ((y + dy) % height) * width + (x + dx) % width;
+        let n = &mut graph.nodes[i as usize];
+        n.state = Cell(LIVE);
+        n.age = 0;
+    }
+}
+
+// A pattern of up to 16x16 cells, one bit per cell, that can be kept in SRAM
+#[derive(Copy,Clone,PartialEq)]
+pub struct Stamp {
+    pub width: u16,
+    pub height: u16,
+    pub rows: [u16; STAMP_SIZE as usize],
+}
+
+impl Stamp {
+
+    pub fn is_live(&self, x: u16, y: u16) -> bool {
+        self.rows[y as usize] & (1 << x) != 0
+    }
+
+    // The smallest stamp holding every live cell of a graph, None if empty
+    pub fn from_graph<M>(graph: &Graph<M>, x: u16, y: u16) -> Option<Self> {
+        let live = || graph.nodes.iter()
+            .filter(|n| matches!(n.state, Cell(LIVE)))
+            .map(|n| (n.x - x, n.y - y));
+        let left = live().map(|(i, _)| i).min()?;
+        let top = live().map(|(_, j)| j).min()?;
+        let right = live().map(|(i, _)| i).max()?;
+        let bottom = live().map(|(_, j)| j).max()?;
+
+        let mut stamp = Stamp {
+            width: (right - left + 1).min(STAMP_SIZE),
+            height: (bottom - top + 1).min(STAMP_SIZE),
+            rows: [0; STAMP_SIZE as usize],
+        };
+        for (i, j) in live() {
+            if i - left < STAMP_SIZE && j - top < STAMP_SIZE {
+                stamp.rows[(j - top) as usize] |= 1 << (i - left);
+            }
+        }
+        Some(stamp)
+    }
+
+    pub fn oriented(pattern: &Pattern, orientation: u8) -> Self {
+        let (width, height) = pattern.size(orientation);
+        let mut stamp = Stamp { width, height, rows: [0; STAMP_SIZE as usize] };
+        for &cell in pattern.cells {
+            let (x, y) = pattern.transform(cell, orientation);
+            stamp.rows[y as usize] |= 1 << x;
+        }
+        stamp
+    }
+}
+
+impl From<&Pattern> for Stamp {
+    fn from(pattern: &Pattern) -> Self {
+        Stamp::oriented(pattern, 0)
+    }
+}
+
+// Sets the stamp's live cells on a board, wrapping at the edges
+pub fn place<M>(graph: &mut Graph<M>, width: u16, stamp: &Stamp, x: u16, y: u16) {
+    let height = (graph.nodes.len() / width as usize) as u16;
+    for j in 0..stamp.height {
+    for i in 0..stamp.width {
+        if stamp.is_live(i, j) {
+            let n = &mut graph.nodes[(((y + j) % height) * width + (x + i) % width) as usize];
+            n.state = Cell(LIVE);
+            n.age = 0;
+        }
+    }}
+}
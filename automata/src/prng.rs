@@ -0,0 +1,80 @@
+// Xorshift generator whose whole state is one word, so anything random
+// can be replayed by saving and restoring it
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct Prng {
+    state: u32,
+}
+
+pub const DEFAULT_SEED: u32 = 0x2545_f491;
+
+impl Prng {
+
+    // Zero would only ever produce zeros
+    pub fn new(seed: u32) -> Self {
+        Prng { state: if seed == 0 { DEFAULT_SEED } else { seed } }
+    }
+
+    pub fn state(&self) -> u32 {
+        self.state
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    // Uniform enough in 0..n for the small ranges used on screen
+    pub fn below(&mut self, n: u32) -> u32 {
+        if n == 0 {
+            0
+        } else {
+            ((self.next_u32() as u64 * n as u64) >> 32) as u32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = Prng::new(1234);
+        let mut b = Prng::new(1234);
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+    }
+
+    #[test]
+    fn restored_state_continues_sequence() {
+        let mut a = Prng::new(99);
+        for _ in 0..10 {
+            a.next_u32();
+        }
+        let mut b = Prng::new(a.state());
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+    }
+
+    #[test]
+    fn zero_seed_is_replaced() {
+        let mut rng = Prng::new(0);
+        assert_eq!(rng.state(), DEFAULT_SEED);
+        assert_ne!(rng.next_u32(), 0);
+    }
+
+    #[test]
+    fn below_stays_in_range() {
+        let mut rng = Prng::new(7);
+        for n in 1..50 {
+            assert!(rng.below(n) < n);
+        }
+        assert_eq!(rng.below(0), 0);
+    }
+}
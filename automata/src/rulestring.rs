@@ -0,0 +1,153 @@
+use::alloc::string::String;
+use::alloc::format;
+
+use crate::MAX_STATES;
+
+// Rules the config menu can be set to, names short enough for the space
+// beside the rules
+pub const PRESETS: [(&str, &str); 10] = [
+    ("LIFE", "B3/S23"),
+    ("HIGHLIFE", "B36/S23"),
+    ("SEEDS", "B2/S"),
+    ("DAY+NIGHT", "B3678/S34678"),
+    ("MAZE", "B3/S12345"),
+    ("REPLICATOR", "B1357/S1357"),
+    ("DIAMOEBA", "B35678/S5678"),
+    ("2X2", "B36/S125"),
+    // Vichniac's votes of a cell and its neighbors, plain and twisted
+    ("MAJORITY", "B5678/S45678"),
+    ("ANNEAL", "B4678/S35678"),
+];
+
+// Canonical birth/survival notation, e.g. "B3/S23" for Conway's Life, with
+// the state count after a C for Generations rules like "B2/S345/C4"
+pub fn to_string(rules: &[[u16; 9]; 2], states: u8) -> String {
+    let mut s = String::from("B");
+    for (n, r) in rules[0].iter().enumerate() {
+        if *r != 0 {
+            s.push(char::from(b'0' + n as u8));
+        }
+    }
+    s.push_str("/S");
+    for (n, r) in rules[1].iter().enumerate() {
+        if *r != 0 {
+            s.push(char::from(b'0' + n as u8));
+        }
+    }
+    if states > 2 {
+        s.push_str(&format!("/C{}", states));
+    }
+    s
+}
+
+// Digits of a neighbor count list, None on anything else
+fn parse_counts(digits: &str) -> Option<[u16; 9]> {
+    let mut counts = [0; 9];
+    for c in digits.chars() {
+        let n = c.to_digit(10)? as usize;
+        *counts.get_mut(n)? = 1;
+    }
+    Some(counts)
+}
+
+// State counts from two up to what a cell can hold
+fn parse_states(digits: &str) -> Option<u8> {
+    let states: u8 = digits.parse().ok()?;
+    if (2..=MAX_STATES as u8).contains(&states) { Some(states) } else { None }
+}
+
+// Reads "B3/S23" in either order and any case, optionally followed by
+// "/C4", or the survival/birth form "23/3" with the Generations state count
+// as in "345/2/4". Returns the rules and the number of states.
+pub fn from_str(s: &str) -> Option<([[u16; 9]; 2], u8)> {
+    let mut parts = s.split('/');
+    let first = parts.next()?;
+    let second = parts.next()?;
+    let third = parts.next();
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let tagged = |part: &str, tag: char| -> Option<[u16; 9]> {
+        let mut chars = part.chars();
+        if chars.next()?.to_ascii_uppercase() == tag {
+            parse_counts(chars.as_str())
+        } else {
+            None
+        }
+    };
+    let counted = |part: Option<&str>| -> Option<u8> {
+        match part {
+            None => Some(2),
+            Some(p) if p.len() > 1 && p[..1].eq_ignore_ascii_case("C") => parse_states(&p[1..]),
+            Some(_) => None,
+        }
+    };
+    if let (Some(b), Some(s)) = (tagged(first, 'B'), tagged(second, 'S')) {
+        return Some(([b, s], counted(third)?));
+    }
+    if let (Some(s), Some(b)) = (tagged(first, 'S'), tagged(second, 'B')) {
+        return Some(([b, s], counted(third)?));
+    }
+    let states = match third {
+        Some(states) => parse_states(states)?,
+        None => 2,
+    };
+    Some(([parse_counts(second)?, parse_counts(first)?], states))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIFE: [[u16; 9]; 2] = [[0,0,0,1,0,0,0,0,0], [0,0,1,1,0,0,0,0,0]];
+    const HIGHLIFE: [[u16; 9]; 2] = [[0,0,0,1,0,0,1,0,0], [0,0,1,1,0,0,0,0,0]];
+    const STAR_WARS: [[u16; 9]; 2] = [[0,0,1,0,0,0,0,0,0], [0,0,0,1,1,1,0,0,0]];
+
+    #[test]
+    fn life_is_canonical() {
+        assert_eq!(to_string(&LIFE, 2), "B3/S23");
+        assert_eq!(to_string(&HIGHLIFE, 2), "B36/S23");
+        assert_eq!(to_string(&STAR_WARS, 4), "B2/S345/C4");
+    }
+
+    #[test]
+    fn round_trip() {
+        for rules in [LIFE, HIGHLIFE, [[0; 9]; 2], [[1; 9]; 2]] {
+            for states in [2, 3, 16] {
+                assert_eq!(from_str(&to_string(&rules, states)), Some((rules, states)));
+            }
+        }
+    }
+
+    #[test]
+    fn other_forms() {
+        assert_eq!(from_str("b3/s23"), Some((LIFE, 2)));
+        assert_eq!(from_str("S23/B3"), Some((LIFE, 2)));
+        assert_eq!(from_str("23/3"), Some((LIFE, 2)));
+        assert_eq!(from_str("23/3/2"), Some((LIFE, 2)));
+        assert_eq!(from_str("23/36/2"), Some((HIGHLIFE, 2)));
+        assert_eq!(from_str("345/2/4"), Some((STAR_WARS, 4)));
+        assert_eq!(from_str("b2/s345/c4"), Some((STAR_WARS, 4)));
+    }
+
+    #[test]
+    fn presets_are_canonical() {
+        for (_, rule) in PRESETS {
+            let (rules, states) = from_str(rule).unwrap();
+            assert_eq!(to_string(&rules, states), rule);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_strings() {
+        assert_eq!(from_str(""), None);
+        assert_eq!(from_str("B3"), None);
+        assert_eq!(from_str("B9/S23"), None);
+        assert_eq!(from_str("B3/S2x"), None);
+        assert_eq!(from_str("23/3/1"), None);
+        assert_eq!(from_str("23/3/17"), None);
+        assert_eq!(from_str("B3/S23/2"), None);
+        assert_eq!(from_str("B3/S23/C"), None);
+    }
+}
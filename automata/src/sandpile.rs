@@ -0,0 +1,60 @@
+use::alloc::vec;
+
+use crate::{Graph, NodeType::Cell, CellState, MAX_STATES};
+
+// Grains a cell holds before it topples
+pub const TOPPLE: u16 = 4;
+
+// Every cell with TOPPLE grains or more gives one to each cell beside it,
+// those going past a dead edge being lost. One wave a generation, so a big
+// avalanche plays out over many frames rather than holding one up.
+pub fn step<M>(graph: &mut Graph<M>) {
+    let mut gained = vec![0u16; graph.nodes.len()];
+    for (i, n) in graph.nodes.iter().enumerate() {
+        if !matches!(n.state, Cell(s) if s.index() as u16 >= TOPPLE) {
+            continue;
+        }
+        let mut maybe_edge = n.first_outgoing_edge;
+        while let Some(e) = maybe_edge {
+            if graph.edges[e].direction.is_some() {
+                gained[graph.edges[e].target] += 1;
+            }
+            maybe_edge = graph.edges[e].next_outgoing_edge;
+        }
+        gained[i] = gained[i].wrapping_sub(TOPPLE);
+    }
+    for (n, gain) in graph.nodes.iter_mut().zip(gained) {
+        if let Cell(s) = n.state {
+            let grains = (s.index() as u16).wrapping_add(gain).min(MAX_STATES as u16 - 1);
+            n.age = if gain == 0 { n.age.saturating_add(1) } else { 0 };
+            n.state = Cell(CellState::from(grains));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{new_world, Boundary, Neighborhood};
+
+    fn grains(graph: &Graph, node: usize) -> usize {
+        match graph.nodes[node].state { Cell(s) => s.index(), _ => 0 }
+    }
+
+    // A full cell gives a grain to each side, and one in the corner loses
+    // two of them over the dead edges
+    #[test]
+    fn full_cells_topple() {
+        let mut graph: Graph = new_world(8, 8, Boundary::DeadEdge, Neighborhood::Moore);
+        graph.nodes[9].state = Cell(CellState::from(TOPPLE));
+        graph.nodes[63].state = Cell(CellState::from(TOPPLE + 1));
+        step(&mut graph);
+        assert_eq!(grains(&graph, 9), 0);
+        for side in [1, 8, 10, 17] {
+            assert_eq!(grains(&graph, side), 1);
+        }
+        assert_eq!(grains(&graph, 0), 0);
+        assert_eq!(grains(&graph, 63), 1);
+        assert_eq!((0..64).map(|i| grains(&graph, i)).sum::<usize>(), 7);
+    }
+}
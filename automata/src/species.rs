@@ -0,0 +1,62 @@
+use::alloc::vec::Vec;
+
+use crate::{Graph, NodeType::Cell, CellState, DEAD, LIVE, PAPER, SCISSORS};
+
+// Rock, paper and scissors, each beaten by the next and the last by the first
+pub const SPECIES: [CellState; 3] = [LIVE, PAPER, SCISSORS];
+
+// A cell goes over to the species that beats it once at least `threshold`
+// of its neighbors are of it. Empty ground is taken by whichever species
+// has the most neighbors there, as long as it has enough and no other ties
+// with it.
+pub fn step<M>(graph: &mut Graph<M>, threshold: u16) {
+    let counts: Vec<[u16; 3]> = (0..graph.nodes.len())
+        .map(|i| SPECIES.map(|s| graph.neighbors_in_state(i, s)))
+        .collect();
+    for (n, count) in graph.nodes.iter_mut().zip(counts) {
+        let Cell(s) = n.state else {
+            continue;
+        };
+        let next = match SPECIES.iter().position(|species| *species == s) {
+            Some(i) => {
+                let winner = (i + 1) % SPECIES.len();
+                if count[winner] >= threshold { SPECIES[winner] } else { s }
+            },
+            None => {
+                let most = count.iter().copied().max().unwrap_or(0);
+                let leaders = count.iter().filter(|c| **c == most).count();
+                match count.iter().position(|c| *c == most) {
+                    Some(i) if most >= threshold && leaders == 1 => SPECIES[i],
+                    _ => DEAD,
+                }
+            },
+        };
+        n.age = if next == s { n.age.saturating_add(1) } else { 0 };
+        n.state = Cell(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{new_world, Boundary, Neighborhood};
+
+    // Paper around rock takes it, but rock only takes scissors and paper
+    // only takes empty ground with enough of them about
+    #[test]
+    fn each_species_is_beaten_by_the_next() {
+        let mut graph: Graph = new_world(8, 8, Boundary::DeadEdge, Neighborhood::Moore);
+        graph.nodes[9].state = Cell(LIVE);
+        for i in [0, 1, 2] {
+            graph.nodes[i].state = Cell(PAPER);
+        }
+        graph.nodes[30].state = Cell(SCISSORS);
+        graph.nodes[31].state = Cell(LIVE);
+        step(&mut graph, 3);
+        assert!(matches!(graph.nodes[9].state, Cell(PAPER)));
+        assert!(matches!(graph.nodes[30].state, Cell(SCISSORS)));
+        assert!(matches!(graph.nodes[10].state, Cell(DEAD)));
+        step(&mut graph, 2);
+        assert!(matches!(graph.nodes[10].state, Cell(PAPER)));
+    }
+}
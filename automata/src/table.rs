@@ -0,0 +1,72 @@
+use::alloc::vec::Vec;
+
+use crate::{Graph, NodeType::Cell, CellState, DEAD, LIVE};
+
+// States a table has rows for
+pub const MAX: usize = 8;
+
+// The state each state goes to for each count of live neighbors
+pub type Table = [[u8; 9]; MAX];
+
+// Life, with every state past live fading a step at a time. Any step past
+// the last state in use is back to dead.
+pub const DEFAULT: Table = [
+    [0, 0, 0, 1, 0, 0, 0, 0, 0],
+    [2, 2, 1, 1, 2, 2, 2, 2, 2],
+    [3; 9],
+    [4; 9],
+    [5; 9],
+    [6; 9],
+    [7; 9],
+    [8; 9],
+];
+
+// What a cell in `state` with `count` live neighbors goes to
+pub fn next(table: &Table, states: u8, state: usize, count: usize) -> u8 {
+    match table[state][count] {
+        s if s < states => s,
+        _ => 0,
+    }
+}
+
+pub fn step<M>(graph: &mut Graph<M>, table: &Table, states: u8) {
+    let counts: Vec<u16> = (0..graph.nodes.len())
+        .map(|i| graph.neighbors_in_state(i, LIVE))
+        .collect();
+    for (n, count) in graph.nodes.iter_mut().zip(counts) {
+        let Cell(s) = n.state else {
+            continue;
+        };
+        let state = s.index().min(MAX - 1);
+        let next = match next(table, states, state, (count as usize).min(8)) {
+            0 => DEAD,
+            s => CellState::from(s as u16),
+        };
+        n.age = if next == s { n.age.saturating_add(1) } else { 0 };
+        n.state = Cell(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{new_world, Boundary, Neighborhood};
+
+    // With two states the table is Life, and with more the dying fade
+    #[test]
+    fn the_default_table_is_life() {
+        let mut graph: Graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        for i in [9, 10, 11] {
+            graph.nodes[i].state = Cell(LIVE);
+        }
+        step(&mut graph, &DEFAULT, 2);
+        for i in [2, 10, 18] {
+            assert!(matches!(graph.nodes[i].state, Cell(LIVE)));
+        }
+        assert!(matches!(graph.nodes[9].state, Cell(DEAD)));
+        step(&mut graph, &DEFAULT, 3);
+        assert!(matches!(graph.nodes[2].state, Cell(CellState(2))));
+        step(&mut graph, &DEFAULT, 3);
+        assert!(matches!(graph.nodes[2].state, Cell(DEAD)));
+    }
+}
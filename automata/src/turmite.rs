@@ -0,0 +1,234 @@
+use crate::{Graph, NodeIndex, NodeType::Cell, CellState, Direction, DEAD};
+
+// Clockwise from up, so a turn is a number of quarter turns to add
+pub const HEADINGS: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub enum Turn {
+    Ahead,
+    Right,
+    Back,
+    Left,
+}
+
+impl Turn {
+
+    pub fn next(self) -> Self {
+        match self {
+            Turn::Ahead => Turn::Right,
+            Turn::Right => Turn::Back,
+            Turn::Back => Turn::Left,
+            Turn::Left => Turn::Ahead,
+        }
+    }
+
+    // The usual turmite notation, U for a U-turn
+    pub fn letter(self) -> &'static str {
+        match self {
+            Turn::Ahead => "N",
+            Turn::Right => "R",
+            Turn::Back => "U",
+            Turn::Left => "L",
+        }
+    }
+}
+
+// What an ant in each of its states does on a dead or painted cell: the
+// color it leaves behind, the way it turns and the state it goes to
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub struct Transition {
+    pub color: u8,
+    pub turn: Turn,
+    pub state: u8,
+}
+
+pub type Turmite = [[Transition; 2]; TURMITE_STATES];
+
+pub const TURMITE_STATES: usize = 2;
+
+// Langton's ant only needs the one state
+pub const LANGTONS_ANT: Turmite = [
+    [Transition { color: 1, turn: Turn::Right, state: 0 },
+     Transition { color: 0, turn: Turn::Left, state: 0 }],
+    [Transition { color: 1, turn: Turn::Right, state: 1 },
+     Transition { color: 0, turn: Turn::Left, state: 1 }],
+];
+
+// Longest highway that can be seen; Langton's ant builds one every 104 steps
+pub const HIGHWAY_PERIOD_LIMIT: usize = 128;
+// Times round the cycle before it counts, so a chance repeat in the
+// chaotic phase is not taken for one
+const HIGHWAY_REPEATS: u32 = 3;
+
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub struct Highway {
+    // Generation the ant's moves started repeating
+    pub start: u32,
+    pub period: u32,
+}
+
+// Remembers an ant's recent headings and cells to catch it settling into a
+// cycle of moves that carries it off somewhere
+#[derive(Copy,Clone,Debug)]
+pub struct HighwayWatch {
+    headings: [u8; HIGHWAY_PERIOD_LIMIT + 1],
+    nodes: [NodeIndex; HIGHWAY_PERIOD_LIMIT + 1],
+    // For each period, the run of moves matching the one that far back
+    runs: [u32; HIGHWAY_PERIOD_LIMIT + 1],
+    steps: usize,
+    found: bool,
+}
+
+impl HighwayWatch {
+
+    pub fn new() -> Self {
+        HighwayWatch {
+            headings: [0; HIGHWAY_PERIOD_LIMIT + 1],
+            nodes: [0; HIGHWAY_PERIOD_LIMIT + 1],
+            runs: [0; HIGHWAY_PERIOD_LIMIT + 1],
+            steps: 0,
+            found: false,
+        }
+    }
+
+    // Called after each step, returning the highway the first time one shows.
+    // The shortest period wins, as its multiples repeat too.
+    pub fn step(&mut self, heading: usize, node: NodeIndex, generation: u32) -> Option<Highway> {
+        let len = self.headings.len();
+        let now = self.steps % len;
+        self.headings[now] = heading as u8;
+        self.nodes[now] = node;
+        self.steps += 1;
+
+        let mut highway = None;
+        for period in 1..len.min(self.steps) {
+            let then = (now + len - period) % len;
+            self.runs[period] = if self.headings[then] == heading as u8 { self.runs[period] + 1 } else { 0 };
+            // Turning on the spot or pinned to an edge goes nowhere
+            if highway.is_none() && !self.found && self.nodes[then] != node
+                && self.runs[period] >= HIGHWAY_REPEATS * period as u32 {
+                self.found = true;
+                highway = Some(Highway {
+                    start: generation.wrapping_sub(self.runs[period] + period as u32 - 1),
+                    period: period as u32,
+                });
+            }
+        }
+        highway
+    }
+}
+
+impl Default for HighwayWatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Copy,Clone,Debug)]
+pub struct Ant {
+    pub node: NodeIndex,
+    pub heading: usize,
+    pub state: u8,
+    // The cell state it paints with, so each ant's trail can be told apart
+    pub paint: CellState,
+    pub highway: HighwayWatch,
+}
+
+impl Ant {
+
+    pub fn new(node: NodeIndex, paint: CellState) -> Self {
+        Ant { node, heading: 0, state: 0, paint, highway: HighwayWatch::new() }
+    }
+
+    // Writes the cell under the ant, turns and steps forward. An ant facing
+    // a dead edge stays put until it turns away.
+    pub fn step<M>(&mut self, graph: &mut Graph<M>, turmite: &Turmite) {
+        let n = &mut graph.nodes[self.node];
+        // Any ant's paint counts the same
+        let color = match n.state { Cell(DEAD) => 0, _ => 1 };
+        let t = turmite[self.state as usize][color];
+        n.state = Cell(if t.color == 1 { self.paint } else { DEAD });
+        n.age = 0;
+        self.heading = (self.heading + t.turn as usize) % HEADINGS.len();
+        self.state = t.state;
+
+        if let Some(next) = graph.towards(self.node, HEADINGS[self.heading]) {
+            self.node = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use crate::{new_world, Boundary, Neighborhood, LIVE};
+
+    // Four right turns on a blank board paint a block and bring the ant
+    // back where it started, facing the same way
+    #[test]
+    fn langtons_ant_paints_a_block() {
+        let mut graph: Graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        let mut ant = Ant::new(3 * 8 + 3, LIVE);
+        for _ in 0..4 {
+            ant.step(&mut graph, &LANGTONS_ANT);
+        }
+        let live: Vec<NodeIndex> = graph.nodes.iter().enumerate()
+            .filter(|(_, n)| matches!(n.state, Cell(LIVE)))
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(live, [3 * 8 + 3, 3 * 8 + 4, 4 * 8 + 3, 4 * 8 + 4]);
+        assert_eq!(ant.node, 3 * 8 + 3);
+        assert_eq!(ant.heading, 0);
+    }
+
+    #[test]
+    fn ant_waits_at_a_dead_edge() {
+        let mut graph: Graph = new_world(8, 8, Boundary::DeadEdge, Neighborhood::Moore);
+        // Facing right on a live cell in the top row, so it turns left
+        // to face off the board
+        let mut ant = Ant { heading: 1, ..Ant::new(0, LIVE) };
+        graph.nodes[0].state = Cell(LIVE);
+        ant.step(&mut graph, &LANGTONS_ANT);
+        assert_eq!(ant.node, 0);
+        assert_eq!(ant.heading, 0);
+    }
+
+    // Walking over another ant's trail takes it over
+    #[test]
+    fn ants_paint_their_own_color() {
+        let mut graph: Graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        let mut first = Ant::new(0, LIVE);
+        let mut second = Ant::new(0, CellState::from(2));
+        first.step(&mut graph, &LANGTONS_ANT);
+        assert!(matches!(graph.nodes[0].state, Cell(LIVE)));
+        // On a painted cell Langton's ant clears it
+        second.step(&mut graph, &LANGTONS_ANT);
+        assert!(matches!(graph.nodes[0].state, Cell(DEAD)));
+        second.node = 0;
+        second.step(&mut graph, &LANGTONS_ANT);
+        assert!(matches!(graph.nodes[0].state, Cell(s) if s.index() == 2));
+    }
+
+    // An ant circling the same four cells repeats its moves but goes nowhere
+    #[test]
+    fn highways_have_to_go_somewhere() {
+        let mut watch = HighwayWatch::new();
+        let mut found = None;
+        for generation in 1..200u32 {
+            let heading = generation as usize % 4;
+            found = found.or(watch.step(heading, heading, generation));
+        }
+        assert_eq!(found, None);
+
+        // Two steps right then one up, over and over, after six at random
+        let mut watch = HighwayWatch::new();
+        let mut node = 500;
+        let moves = [2, 3, 3, 0, 2, 1].into_iter().chain([1, 1, 0].into_iter().cycle());
+        for (generation, heading) in (1..40).zip(moves) {
+            node = if heading == 0 { node - 20 } else { node + 1 };
+            found = found.or(watch.step(heading, node, generation));
+        }
+        assert_eq!(found, Some(Highway { start: 7, period: 3 }));
+    }
+}
@@ -0,0 +1,148 @@
+use::alloc::vec;
+use::alloc::vec::Vec;
+
+use crate::{Graph, NodeIndex, NodeType::Cell, CellState, DEAD, FISH, SHARK};
+use crate::order::UpdateOrder;
+use crate::prng::Prng;
+
+// Generations a fish or shark swims between giving birth, and those a
+// shark lasts without eating
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct Rules {
+    pub fish_breed: u16,
+    pub shark_breed: u16,
+    pub starve: u16,
+}
+
+pub const RULES: Rules = Rules { fish_breed: 3, shark_breed: 10, starve: 3 };
+pub const LIMIT: u16 = 99;
+
+// What each cell held after the last generation, how long since what is in
+// it gave birth and how long since a shark there ate. Cells painted or
+// loaded since then start over with both at none.
+pub struct Ocean {
+    seen: Vec<CellState>,
+    breed: Vec<u16>,
+    hunger: Vec<u16>,
+}
+
+impl Ocean {
+
+    pub fn new(cells: usize) -> Self {
+        Ocean { seen: vec![DEAD; cells], breed: vec![0; cells], hunger: vec![0; cells] }
+    }
+
+    fn settle<M>(&mut self, graph: &Graph<M>) {
+        for (i, n) in graph.nodes.iter().enumerate() {
+            if !matches!(n.state, Cell(s) if s == self.seen[i]) {
+                self.breed[i] = 0;
+                self.hunger[i] = 0;
+            }
+        }
+    }
+}
+
+// The cells a step along each side away that hold `state`
+fn around<M>(graph: &Graph<M>, node: NodeIndex, state: CellState) -> Vec<NodeIndex> {
+    let mut cells = Vec::new();
+    let mut maybe_edge = graph.nodes[node].first_outgoing_edge;
+    while let Some(e) = maybe_edge {
+        let target = graph.edges[e].target;
+        if graph.edges[e].direction.is_some() && matches!(graph.nodes[target].state, Cell(s) if s == state) {
+            cells.push(target);
+        }
+        maybe_edge = graph.edges[e].next_outgoing_edge;
+    }
+    cells
+}
+
+// Every fish swims to open water beside it, then every shark to a fish
+// beside it if there is one, eating it, or else to open water. Each in
+// turn, in a new order every generation. One that has swum long enough
+// leaves a young one behind, and a shark that has gone too long without
+// eating dies.
+pub fn step<M>(graph: &mut Graph<M>, ocean: &mut Ocean, rules: &Rules, rng: &mut Prng) {
+    ocean.settle(graph);
+    let mut moved = vec![false; graph.nodes.len()];
+    for (kind, breed_after) in [(FISH, rules.fish_breed), (SHARK, rules.shark_breed)] {
+        for i in UpdateOrder::Random.cells(graph.nodes.len(), rng) {
+            if moved[i] || !matches!(graph.nodes[i].state, Cell(s) if s == kind) {
+                continue;
+            }
+            let prey = if kind == SHARK { around(graph, i, FISH) } else { Vec::new() };
+            let hunger = if prey.is_empty() && kind == SHARK { ocean.hunger[i] + 1 } else { 0 };
+            if hunger >= rules.starve && kind == SHARK {
+                graph.nodes[i].state = Cell(DEAD);
+                continue;
+            }
+            let choices = if prey.is_empty() { around(graph, i, DEAD) } else { prey };
+            let breed = ocean.breed[i].saturating_add(1);
+            if choices.is_empty() {
+                ocean.breed[i] = breed;
+                ocean.hunger[i] = hunger;
+                continue;
+            }
+            let to = choices[rng.below(choices.len() as u32) as usize];
+            let births = breed >= breed_after;
+            graph.nodes[to].state = Cell(kind);
+            ocean.breed[to] = if births { 0 } else { breed };
+            ocean.hunger[to] = hunger;
+            moved[to] = true;
+            graph.nodes[i].state = Cell(if births { kind } else { DEAD });
+            ocean.breed[i] = 0;
+            ocean.hunger[i] = 0;
+        }
+    }
+    for (i, n) in graph.nodes.iter_mut().enumerate() {
+        let Cell(s) = n.state else {
+            continue;
+        };
+        n.age = if s == ocean.seen[i] && !moved[i] { n.age.saturating_add(1) } else { 0 };
+        ocean.seen[i] = s;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{new_world, Boundary, Neighborhood};
+    use crate::prng::DEFAULT_SEED;
+
+    fn count(graph: &Graph, state: CellState) -> usize {
+        graph.nodes.iter().filter(|n| matches!(n.state, Cell(s) if s == state)).count()
+    }
+
+    // A shark beside a fish eats it, and one with nothing to eat starves
+    #[test]
+    fn sharks_eat_or_starve() {
+        let mut graph: Graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        let mut ocean = Ocean::new(graph.nodes.len());
+        let mut rng = Prng::new(DEFAULT_SEED);
+        let rules = Rules { fish_breed: LIMIT, shark_breed: LIMIT, starve: 3 };
+        graph.nodes[9].state = Cell(SHARK);
+        graph.nodes[10].state = Cell(FISH);
+        // Walled in by sharks so the fish can't swim off first
+        for i in [2, 11, 18] {
+            graph.nodes[i].state = Cell(SHARK);
+        }
+        step(&mut graph, &mut ocean, &rules, &mut rng);
+        assert_eq!(count(&graph, FISH), 0);
+        for _ in 0..rules.starve {
+            step(&mut graph, &mut ocean, &rules, &mut rng);
+        }
+        assert_eq!(count(&graph, SHARK), 0);
+    }
+
+    // Fish fill open water, there being nothing to eat them
+    #[test]
+    fn fish_breed() {
+        let mut graph: Graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        let mut ocean = Ocean::new(graph.nodes.len());
+        let mut rng = Prng::new(DEFAULT_SEED);
+        graph.nodes[0].state = Cell(FISH);
+        for _ in 0..RULES.fish_breed {
+            step(&mut graph, &mut ocean, &RULES, &mut rng);
+        }
+        assert_eq!(count(&graph, FISH), 2);
+    }
+}
@@ -0,0 +1,118 @@
+use::alloc::{string::String, format};
+
+use agb::sound::dmg::{Sound, DutyCycle, EnvelopeSettings, SoundDirection, SweepSettings};
+
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub enum AlarmAction {
+    Pause,
+    Beep,
+    Both,
+}
+
+impl AlarmAction {
+
+    pub fn next(self) -> Self {
+        match self {
+            AlarmAction::Pause => AlarmAction::Beep,
+            AlarmAction::Beep => AlarmAction::Both,
+            AlarmAction::Both => AlarmAction::Pause,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AlarmAction::Pause => "PAUSE",
+            AlarmAction::Beep => "BEEP ",
+            AlarmAction::Both => "BOTH ",
+        }
+    }
+
+    pub fn pauses(self) -> bool {
+        self != AlarmAction::Beep
+    }
+
+    pub fn beeps(self) -> bool {
+        self != AlarmAction::Pause
+    }
+}
+
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub enum Alarm {
+    Above(u16),
+    Below(u16),
+    Zero,
+}
+
+impl Alarm {
+
+    pub fn describe(self) -> String {
+        match self {
+            Alarm::Above(limit) => format!("POPULATION ABOVE {}", limit),
+            Alarm::Below(limit) => format!("POPULATION BELOW {}", limit),
+            Alarm::Zero => String::from("POPULATION ZERO"),
+        }
+    }
+}
+
+// Population limits that stop a long run at the interesting moment. A limit
+// of 0 is off.
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub struct Alarms {
+    pub above: u16,
+    pub below: u16,
+    pub zero: bool,
+    pub action: AlarmAction,
+}
+
+impl Alarms {
+
+    pub fn new() -> Self {
+        Alarms { above: 0, below: 0, zero: false, action: AlarmAction::Pause }
+    }
+
+    pub fn armed(&self) -> bool {
+        self.above > 0 || self.below > 0 || self.zero
+    }
+
+    // Only goes off on the generation a limit is crossed, so a run resumed
+    // past it carries on. Dying out wins over falling below.
+    pub fn check(&self, before: u16, after: u16) -> Option<Alarm> {
+        if self.zero && after == 0 && before != 0 {
+            Some(Alarm::Zero)
+        } else if self.below > 0 && after < self.below && before >= self.below {
+            Some(Alarm::Below(self.below))
+        } else if self.above > 0 && after > self.above && before <= self.above {
+            Some(Alarm::Above(self.above))
+        } else {
+            None
+        }
+    }
+}
+
+// A short falling tone on the first square wave channel
+pub fn beep(sound: &Sound) {
+    sound.channel1().play_sound(
+        1750,
+        Some(32),
+        &SweepSettings::new(2, SoundDirection::Decrease, 2),
+        &EnvelopeSettings::new(2, SoundDirection::Decrease, 15),
+        DutyCycle::Half,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn alarms_go_off_when_crossed(_gba: &mut agb::Gba) {
+        let alarms = Alarms { above: 100, below: 10, zero: true, action: AlarmAction::Pause };
+        assert_eq!(alarms.check(100, 101), Some(Alarm::Above(100)));
+        assert_eq!(alarms.check(101, 120), None);
+        assert_eq!(alarms.check(10, 9), Some(Alarm::Below(10)));
+        assert_eq!(alarms.check(9, 5), None);
+        assert_eq!(alarms.check(12, 0), Some(Alarm::Zero));
+        assert_eq!(alarms.check(0, 0), None);
+        assert_eq!(Alarms::new().check(100, 0), None);
+    }
+}
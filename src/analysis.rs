@@ -0,0 +1,396 @@
+use::alloc::{vec, vec::Vec, string::String, format};
+
+use crate::{Graph, NodeIndex, NodeType::Cell, LIVE, WIDTH, HEIGHT};
+use crate::pattern::{Pattern, Stamp, R_PENTOMINO, DIEHARD, ACORN, PI_HEPTOMINO};
+
+// Generations to wait for the board to repeat before giving up
+pub const SHIP_TRACK_LIMIT: u16 = 64;
+
+#[derive(Debug,Copy,Clone)]
+pub struct Velocity {
+    pub dx: i16,
+    pub dy: i16,
+    pub period: u16,
+}
+
+fn gcd(a: u16, b: u16) -> u16 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+impl Velocity {
+
+    // Speed in the usual notation, e.g. "C/4 DIAGONAL" for a glider
+    pub fn describe(&self) -> String {
+        if self.dx == 0 && self.dy == 0 {
+            return format!("P{} OSCILLATOR", self.period);
+        }
+        let (ax, ay) = (self.dx.unsigned_abs(), self.dy.unsigned_abs());
+        let distance = ax.max(ay);
+        let g = gcd(distance, self.period);
+        let direction = if ax == 0 || ay == 0 {
+            "ORTHOGONAL"
+        } else if ax == ay {
+            "DIAGONAL"
+        } else {
+            "OBLIQUE"
+        };
+        if distance / g == 1 {
+            format!("C/{} {}", self.period / g, direction)
+        } else {
+            format!("{}C/{} {}", distance / g, self.period / g, direction)
+        }
+    }
+}
+
+fn live_cells(graph: &Graph) -> Vec<NodeIndex> {
+    graph.nodes.iter()
+        .enumerate()
+        .filter(|(_, n)| matches!(n.state, Cell(LIVE)))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+// Watches for the whole board reappearing, possibly translated
+pub struct ShipTracker {
+    cells: Vec<NodeIndex>,
+    elapsed: u16,
+}
+
+impl ShipTracker {
+
+    pub fn new(graph: &Graph) -> Option<Self> {
+        let cells = live_cells(graph);
+        if cells.is_empty() {
+            None
+        } else {
+            Some(ShipTracker { cells, elapsed: 0 })
+        }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.elapsed >= SHIP_TRACK_LIMIT
+    }
+
+    // Call once per generation
+    pub fn step(&mut self, graph: &Graph) -> Option<Velocity> {
+        self.elapsed += 1;
+        let current = live_cells(graph);
+        if current.len() != self.cells.len() {
+            return None;
+        }
+        let mut live = vec![false; graph.nodes.len()];
+        for &i in &current {
+            live[i] = true;
+        }
+
+        let (w, h) = (WIDTH as i16, HEIGHT as i16);
+        let first = self.cells[0];
+        for &candidate in &current {
+            let dx = candidate as i16 % w - first as i16 % w;
+            let dy = candidate as i16 / w - first as i16 / w;
+            let matches = self.cells.iter().all(|&i| {
+                let x = (i as i16 % w + dx).rem_euclid(w);
+                let y = (i as i16 / w + dy).rem_euclid(h);
+                live[(y * w + x) as usize]
+            });
+            if matches {
+                // Shortest way round the torus
+                let dx = (dx + w / 2).rem_euclid(w) - w / 2;
+                let dy = (dy + h / 2).rem_euclid(h) - h / 2;
+                return Some(Velocity { dx, dy, period: self.elapsed });
+            }
+        }
+        None
+    }
+}
+
+pub enum Search {
+    Searching,
+    Found,
+    Impossible,
+    OutOfBudget,
+}
+
+const UNSET: u8 = 2;
+
+// Backtracking search for a board that evolves into the region, run a few
+// steps at a time so it can be spread across frames
+pub struct PredecessorSearch {
+    width: usize,
+    target: Vec<bool>,
+    // The region plus a one cell border, row by row
+    cells: Vec<u8>,
+    pos: usize,
+    budget: u32,
+}
+
+impl PredecessorSearch {
+
+    pub fn new(graph: &Graph, x: u16, y: u16, width: u16, height: u16, budget: u32) -> Self {
+        let mut target = Vec::with_capacity((width * height).into());
+        for j in 0..height {
+        for i in 0..width {
+            let node = ((y + j) % HEIGHT) * WIDTH + (x + i) % WIDTH;
+            target.push(matches!(graph.nodes[node as usize].state, Cell(LIVE)));
+        }}
+        let (width, height) = (width as usize, height as usize);
+        PredecessorSearch {
+            width,
+            target,
+            cells: vec![UNSET; (width + 2) * (height + 2)],
+            pos: 0,
+            budget,
+        }
+    }
+
+    // Once the bottom right neighbor of a region cell is set its whole
+    // neighborhood is known, so that is when it gets checked
+    fn consistent(&self, rules: &[[u16; 9]; 2]) -> bool {
+        let w = self.width + 2;
+        let (px, py) = (self.pos % w, self.pos / w);
+        if px < 2 || py < 2 {
+            return true;
+        }
+        let mut neighbors = 0;
+        for j in py-2..=py {
+        for i in px-2..=px {
+            if (i, j) != (px-1, py-1) {
+                neighbors += self.cells[j * w + i] as usize;
+            }
+        }}
+        let centre = self.cells[(py-1) * w + px-1] as usize;
+        let live = rules[centre][neighbors] != 0;
+        live == self.target[(py-2) * self.width + px-2]
+    }
+
+    pub fn run(&mut self, rules: &[[u16; 9]; 2], steps: u32) -> Search {
+        for _ in 0..steps {
+            if self.pos == self.cells.len() {
+                return Search::Found;
+            }
+            if self.budget == 0 {
+                return Search::OutOfBudget;
+            }
+            self.budget -= 1;
+
+            let cell = &mut self.cells[self.pos];
+            if *cell == 1 {
+                // Both states tried, go back a cell
+                *cell = UNSET;
+                if self.pos == 0 {
+                    return Search::Impossible;
+                }
+                self.pos -= 1;
+                continue;
+            }
+            *cell = if *cell == UNSET { 0 } else { 1 };
+            if self.consistent(rules) {
+                self.pos += 1;
+            }
+        }
+        if self.pos == self.cells.len() { Search::Found } else { Search::Searching }
+    }
+}
+
+// FNV-1a over the live cells and the rules, short enough to read out so
+// two boards can be compared by eye
+pub fn board_hash(graph: &Graph, rules: &[[u16; 9]; 2]) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    let mut feed = |byte: u8| {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    };
+    for chunk in graph.nodes.chunks(8) {
+        let byte = chunk.iter()
+            .enumerate()
+            .filter(|(_, n)| matches!(n.state, Cell(LIVE)))
+            .fold(0, |b, (i, _)| b | 1 << i);
+        feed(byte);
+    }
+    for r in rules.iter().flatten() {
+        feed(*r as u8);
+    }
+    hash
+}
+
+// Boards remembered when looking for the end of a pattern's life, enough
+// to catch the common oscillators
+const LIFESPAN_WINDOW: usize = 12;
+pub const LIFESPAN_LIMIT: u32 = 20000;
+
+pub struct Methuselah {
+    pub name: &'static str,
+    pub pattern: &'static Pattern,
+    // Generations to stabilize on an unbounded plane and what is left
+    pub lifespan: u32,
+    pub population: u16,
+}
+
+pub const METHUSELAHS: [Methuselah; 4] = [
+    Methuselah { name: "R-PENT", pattern: &R_PENTOMINO, lifespan: 1103, population: 116 },
+    Methuselah { name: "DIEHARD", pattern: &DIEHARD, lifespan: 130, population: 0 },
+    Methuselah { name: "ACORN", pattern: &ACORN, lifespan: 5206, population: 633 },
+    Methuselah { name: "PI-HEPT", pattern: &PI_HEPTOMINO, lifespan: 173, population: 55 },
+];
+
+// The methuselah the board holds on its own, in any orientation
+fn identify(graph: &Graph) -> Option<&'static Methuselah> {
+    let board = Stamp::from_graph(graph, 0, 0)?;
+    METHUSELAHS.iter()
+        .find(|m| (0..8).any(|o| Stamp::oriented(m.pattern, o) == board))
+}
+
+pub enum Lifespan {
+    Running,
+    Stable { lifespan: u32, population: u16 },
+    GaveUp,
+}
+
+// Runs a pattern until the board settles into a still life or oscillator
+pub struct LifespanTracker {
+    start: u32,
+    recent: Vec<(u32, u32)>,
+    pub methuselah: Option<&'static Methuselah>,
+}
+
+impl LifespanTracker {
+
+    pub fn new(graph: &Graph, rules: &[[u16; 9]; 2], generation: u32) -> Self {
+        LifespanTracker {
+            start: generation,
+            recent: vec![(generation, board_hash(graph, rules))],
+            methuselah: identify(graph),
+        }
+    }
+
+    // Call once per generation
+    pub fn step(&mut self, graph: &Graph, rules: &[[u16; 9]; 2], generation: u32) -> Lifespan {
+        let hash = board_hash(graph, rules);
+        if let Some((settled, _)) = self.recent.iter().find(|(_, h)| *h == hash) {
+            return Lifespan::Stable {
+                lifespan: settled.wrapping_sub(self.start),
+                population: live_cells(graph).len() as u16,
+            };
+        }
+        if generation.wrapping_sub(self.start) >= LIFESPAN_LIMIT {
+            return Lifespan::GaveUp;
+        }
+        if self.recent.len() == LIFESPAN_WINDOW {
+            self.recent.remove(0);
+        }
+        self.recent.push((generation, hash));
+        Lifespan::Running
+    }
+}
+
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub enum Goal {
+    Generation,
+    Stable,
+    Above,
+    Below,
+}
+
+impl Goal {
+
+    pub fn next(self) -> Self {
+        match self {
+            Goal::Generation => Goal::Stable,
+            Goal::Stable => Goal::Above,
+            Goal::Above => Goal::Below,
+            Goal::Below => Goal::Generation,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Goal::Generation => "GEN   ",
+            Goal::Stable => "STABLE",
+            Goal::Above => "POP > ",
+            Goal::Below => "POP < ",
+        }
+    }
+}
+
+// Runs flat out, only drawing every so many generations, until the goal is
+// reached. The target is a generation or a population depending on the goal.
+pub struct RunUntil {
+    goal: Goal,
+    target: u32,
+    every: u32,
+    lifespan: Option<LifespanTracker>,
+}
+
+impl RunUntil {
+
+    pub fn new(goal: Goal, target: u32, every: u16, graph: &Graph, rules: &[[u16; 9]; 2],
+               generation: u32) -> Self {
+        RunUntil {
+            goal,
+            target,
+            every: every.max(1) as u32,
+            lifespan: (goal == Goal::Stable).then(|| LifespanTracker::new(graph, rules, generation)),
+        }
+    }
+
+    pub fn every(&self) -> u32 {
+        self.every
+    }
+
+    // Call once per generation, returning the report when it is time to stop
+    pub fn step(&mut self, graph: &Graph, rules: &[[u16; 9]; 2], generation: u32) -> Option<String> {
+        let population = live_cells(graph).len() as u32;
+        match (self.goal, &mut self.lifespan) {
+            (Goal::Generation, _) if generation >= self.target =>
+                Some(format!("GEN {} POP {}", generation, population)),
+            (Goal::Stable, Some(tracker)) => match tracker.step(graph, rules, generation) {
+                Lifespan::Running => None,
+                Lifespan::Stable { lifespan, population } =>
+                    Some(format!("SETTLED AFTER {} POP {}", lifespan, population)),
+                Lifespan::GaveUp => Some(String::from("DID NOT SETTLE")),
+            },
+            (Goal::Above, _) if population > self.target =>
+                Some(format!("POP {} AT GEN {}", population, generation)),
+            (Goal::Below, _) if population < self.target =>
+                Some(format!("POP {} AT GEN {}", population, generation)),
+            _ => None,
+        }
+    }
+}
+
+// Counts the electron heads passing through one Wireworld cell
+pub struct SignalProbe {
+    pub node: NodeIndex,
+    heads: u32,
+    last: Option<u32>,
+    period: Option<u32>,
+}
+
+impl SignalProbe {
+
+    pub fn new(node: NodeIndex) -> Self {
+        SignalProbe { node, heads: 0, last: None, period: None }
+    }
+
+    // Call once per generation, true when a head is on the probe
+    pub fn step(&mut self, graph: &Graph, generation: u32) -> bool {
+        if !matches!(graph.nodes[self.node].state, Cell(LIVE)) {
+            return false;
+        }
+        // Undo can take the generation back past the last head
+        self.period = match self.last {
+            Some(last) if generation > last => Some(generation - last),
+            _ => None,
+        };
+        self.last = Some(generation);
+        self.heads += 1;
+        true
+    }
+
+    pub fn describe(&self) -> String {
+        match self.period {
+            Some(period) => format!("HEADS {} PERIOD {}", self.heads, period),
+            None => format!("HEADS {}", self.heads),
+        }
+    }
+}
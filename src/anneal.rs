@@ -0,0 +1,41 @@
+use crate::CHANCE_ONE;
+
+// Temperatures are in tenths, the hottest being close to a coin toss
+pub const TEMPERATURE_MAX: u16 = 99;
+// Any more and most votes would go the other way
+pub const TWIST_MAX: u16 = 2;
+
+// Two to the power of each eighth, out of 256
+const EIGHTHS: [u32; 8] = [256, 279, 304, 332, 362, 395, 431, 470];
+// Past two to the eighth going against the vote is too unlikely to count
+const EIGHTHS_MAX: u32 = 64;
+
+// Chance out of CHANCE_ONE of a cell going against a vote it lost or won by
+// `margin`, one over one plus two to the power of twice the margin over the
+// temperature. Frozen at zero, so the vote always wins.
+pub fn against(margin: u16, temperature: u16) -> u16 {
+    if temperature == 0 {
+        return 0;
+    }
+    let eighths = margin as u32 * 2 * 8 * 10 / temperature as u32;
+    if eighths >= EIGHTHS_MAX {
+        return 0;
+    }
+    let power = EIGHTHS[eighths as usize % 8] << (eighths / 8);
+    (CHANCE_ONE as u32 * 256 / (256 + power)) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn closer_votes_and_hotter_cells_are_noisier(_gba: &mut agb::Gba) {
+        assert_eq!(against(0, 10), CHANCE_ONE / 2);
+        assert_eq!(against(1, 10), CHANCE_ONE / 5);
+        assert!(against(3, 10) < against(1, 10));
+        assert!(against(1, 5) < against(1, 10));
+        assert_eq!(against(1, 0), 0);
+        assert_eq!(against(9, 1), 0);
+    }
+}
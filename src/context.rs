@@ -0,0 +1,58 @@
+use::alloc::string::String;
+
+use agb::save::{SaveManager, Error};
+
+// What the game was last doing, kept in SRAM so it can still be read after
+// a crash and reset, or a soft reset from holding A, B, START and SELECT.
+// agb installs the panic handler itself, so this is shown on the memory
+// page in place of a custom panic screen.
+const CONTEXT_OFFSET: usize = 8200;
+const CONTEXT_MAGIC: u8 = b'X';
+const NAME_LENGTH: usize = 10;
+const CONTEXT_BYTES: usize = 1 + NAME_LENGTH + 4 + 1;
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum SaveStatus {
+    None,
+    Saved,
+    Failed,
+}
+
+pub struct Context {
+    pub state: String,
+    pub generation: u32,
+    pub save: SaveStatus,
+}
+
+pub fn write(save: &mut SaveManager, state: &str, generation: u32, status: SaveStatus) -> Result<(), Error> {
+    let mut access = save.access()?;
+    let mut bytes = [0u8; CONTEXT_BYTES];
+    bytes[0] = CONTEXT_MAGIC;
+    for (b, c) in bytes[1..1 + NAME_LENGTH].iter_mut().zip(state.bytes()) {
+        *b = c;
+    }
+    bytes[1 + NAME_LENGTH..5 + NAME_LENGTH].copy_from_slice(&generation.to_le_bytes());
+    bytes[5 + NAME_LENGTH] = status as u8;
+    access.prepare_write(CONTEXT_OFFSET..CONTEXT_OFFSET + CONTEXT_BYTES)?
+          .write(CONTEXT_OFFSET, &bytes)?;
+    Ok(())
+}
+
+pub fn read(save: &mut SaveManager) -> Result<Option<Context>, Error> {
+    let mut access = save.access()?;
+    let mut bytes = [0u8; CONTEXT_BYTES];
+    access.read(CONTEXT_OFFSET, &mut bytes)?;
+    if bytes[0] != CONTEXT_MAGIC {
+        return Ok(None);
+    }
+    let n = 1 + NAME_LENGTH;
+    Ok(Some(Context {
+        state: bytes[1..n].iter().take_while(|b| **b != 0).map(|b| *b as char).collect(),
+        generation: u32::from_le_bytes([bytes[n], bytes[n+1], bytes[n+2], bytes[n+3]]),
+        save: match bytes[n+4] {
+            1 => SaveStatus::Saved,
+            2 => SaveStatus::Failed,
+            _ => SaveStatus::None,
+        },
+    }))
+}
@@ -0,0 +1,101 @@
+use agb::display::{palette16::Palette16, tiled::VRamManager};
+
+use crate::background_tiles;
+
+// Decaying Life cells are all drawn with the first shade tile, each in the
+// palette of the phase it started decaying on. The phase palettes move on
+// a shade every generation, so a cell fading out is only drawn again once
+// it is dead.
+
+// Red and blue of the purples in the shade tiles, darkest first
+const LEVELS: [u16; 7] = [4, 7, 11, 15, 19, 23, 27];
+// Purples of the highlight, edge, face and shadow of the first shade tile,
+// each of the other shades being a purple darker
+const FIRST_SHADE: [usize; 4] = [6, 4, 5, 3];
+const SHADES: usize = 4;
+const PALETTE_COUNT: usize = 16;
+
+fn purple(level: usize) -> u16 {
+    LEVELS[level] | LEVELS[level] << 10
+}
+
+// The palette the first shade tile is drawn with and where its purples are
+fn shade_palette() -> Option<(&'static Palette16, [usize; 4])> {
+    background_tiles::PALETTES.iter().find_map(|palette| {
+        let mut colours = [0; 4];
+        for (colour, level) in colours.iter_mut().zip(FIRST_SHADE) {
+            *colour = (0..16).find(|c| palette.colour(*c) == purple(level))?;
+        }
+        Some((palette, colours))
+    })
+}
+
+// One phase for each palette the tiles leave spare
+pub fn phases() -> usize {
+    match shade_palette() {
+        Some(_) => PALETTE_COUNT - background_tiles::PALETTES.len(),
+        None => 0,
+    }
+}
+
+// Palette for a cell that has been decaying for `age` generations
+pub fn palette(rotation: u8, phases: u8, age: usize) -> u8 {
+    let phase = (rotation as usize + phases as usize - age % phases as usize) % phases as usize;
+    (PALETTE_COUNT - phases as usize + phase) as u8
+}
+
+// Shades each phase palette for the age of the cells drawn with it, those
+// past the last decaying state keeping the shade they had
+pub fn set_palettes(vram: &mut VRamManager, rotation: u8, phases: u8, decaying: usize) {
+    let Some((source, colours)) = shade_palette() else {
+        return;
+    };
+    for phase in 0..phases {
+        let age = (rotation as usize + phases as usize - phase as usize) % phases as usize;
+        if age >= decaying {
+            continue;
+        }
+        let shade = age * SHADES / decaying;
+        let mut shaded = source.clone();
+        for (colour, level) in colours.iter().zip(FIRST_SHADE) {
+            shaded.update_colour(*colour, purple(level - shade));
+        }
+        vram.set_background_palette((PALETTE_COUNT as u8 - phases) + phase, &shaded);
+    }
+}
+
+// Ground last held by live and by red cells is drawn with the first shade
+// tile in the last two palettes, at its darkest and in blue or in red
+pub const TERRITORY_PALETTES: [u8; 2] = [PALETTE_COUNT as u8 - 2, PALETTE_COUNT as u8 - 1];
+
+pub fn set_territory_palettes(vram: &mut VRamManager) {
+    let Some((source, colours)) = shade_palette() else {
+        return;
+    };
+    for (palette, channel) in TERRITORY_PALETTES.iter().zip([0x1f << 10, 0x1f]) {
+        let mut tinted = source.clone();
+        for (colour, level) in colours.iter().zip(FIRST_SHADE) {
+            tinted.update_colour(*colour, purple(level - (SHADES - 1)) & channel);
+        }
+        vram.set_background_palette(*palette, &tinted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A cell keeps its palette while it ages along with the rotation
+    #[test_case]
+    fn decaying_cells_keep_their_palette(_gba: &mut agb::Gba) {
+        let phases = 6;
+        for rotation in 0..phases {
+            for age in 0..phases as usize - 1 {
+                let next = (rotation + 1) % phases;
+                assert_eq!(palette(rotation, phases, age), palette(next, phases, age + 1));
+            }
+        }
+        assert_eq!(palette(0, phases, 0), 16 - phases);
+        assert_ne!(palette(0, phases, 0), palette(0, phases, 1));
+    }
+}
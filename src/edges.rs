@@ -0,0 +1,117 @@
+use agb::display::tiled::{RegularMap, VRamManager};
+
+use crate::{background_tiles, new_world, step_graph, Boundary, Graph, Neighborhood, Settings, NodeType::Cell, LIVE};
+use crate::prng::{Prng, DEFAULT_SEED};
+
+// A glider on a small Life board run into the corner, to show what each
+// boundary does with it. Each cell is two pixels square, so the board is
+// two tiles across.
+pub const PREVIEW_SIZE: u16 = 8;
+pub const PREVIEW_TILES: u16 = PREVIEW_SIZE / 4;
+// Back to the start once the glider has crossed the board
+const PREVIEW_GENERATIONS: u16 = 32;
+const PREVIEW_FRAME_TIME: u16 = 6;
+
+const GLIDER: [(u16, u16); 5] = [(2, 1), (3, 2), (1, 3), (2, 3), (3, 3)];
+
+// The grey of dead cells and the blue of live ones
+const DEAD_COLOUR: u16 = 0x1ce7;
+const LIVE_COLOUR: u16 = 0x7e80;
+
+pub struct EdgePreview {
+    graph: Graph,
+    boundary: Boundary,
+    settings: Settings,
+    generation: u16,
+    frames: u16,
+}
+
+impl EdgePreview {
+
+    pub fn new(boundary: Boundary) -> Self {
+        let mut graph = new_world(PREVIEW_SIZE, PREVIEW_SIZE, boundary, Neighborhood::Moore);
+        for (x, y) in GLIDER {
+            graph.nodes[(y * PREVIEW_SIZE + x) as usize].state = Cell(LIVE);
+        }
+        EdgePreview { graph, boundary, settings: Settings::new(), generation: 0, frames: 0 }
+    }
+
+    // Call once a frame, starting over when the boundary is changed
+    pub fn update(&mut self, boundary: Boundary) {
+        if boundary != self.boundary || self.generation == PREVIEW_GENERATIONS {
+            *self = EdgePreview::new(boundary);
+        }
+        self.frames += 1;
+        if self.frames == PREVIEW_FRAME_TIME {
+            self.frames = 0;
+            self.step();
+        }
+    }
+
+    fn step(&mut self) {
+        step_graph(&mut self.graph, &self.settings, PREVIEW_SIZE, self.boundary, &mut Prng::new(DEFAULT_SEED));
+        self.generation += 1;
+    }
+
+    fn live(&self, x: u16, y: u16) -> bool {
+        matches!(self.graph.nodes[(y * PREVIEW_SIZE + x) as usize].state, Cell(LIVE))
+    }
+
+    // Drawn into new tiles each time, the map keeping them only as long as
+    // it shows them
+    pub fn draw(&self, bg: &mut RegularMap, vram: &mut VRamManager, x: u16, y: u16) {
+        let Some((palette, dead, live)) = colours() else {
+            return;
+        };
+        for ty in 0..PREVIEW_TILES {
+        for tx in 0..PREVIEW_TILES {
+            let tile = vram.new_dynamic_tile().fill_with(dead);
+            for (row, data) in tile.tile_data.iter_mut().enumerate() {
+                let cy = ty * 4 + row as u16 / 2;
+                for pixel in 0..8 {
+                    let cx = tx * 4 + pixel / 2;
+                    if self.live(cx, cy) {
+                        *data = *data & !(0xf << (pixel * 4)) | (live as u32) << (pixel * 4);
+                    }
+                }
+            }
+            bg.set_tile(vram, (x + tx, y + ty), &tile.tile_set(), tile.tile_setting().palette(palette));
+            vram.remove_dynamic_tile(tile);
+        }}
+    }
+}
+
+// A palette with both colours and where they are in it
+pub fn colours() -> Option<(u8, u8, u8)> {
+    background_tiles::PALETTES.iter().enumerate().find_map(|(i, palette)| {
+        let dead = (0..16).find(|c| palette.colour(*c) == DEAD_COLOUR)?;
+        let live = (0..16).find(|c| palette.colour(*c) == LIVE_COLOUR)?;
+        Some((i as u8, dead as u8, live as u8))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // By the time it starts over the glider is back where it began on a
+    // torus and gone from a board with dead edges
+    #[test_case]
+    fn gliders_cross_the_preview(_gba: &mut agb::Gba) {
+        let cells = |preview: &EdgePreview| (0..PREVIEW_SIZE * PREVIEW_SIZE)
+            .filter(|i| preview.live(i % PREVIEW_SIZE, i / PREVIEW_SIZE))
+            .collect::<alloc::vec::Vec<u16>>();
+        let mut torus = EdgePreview::new(Boundary::Toroidal);
+        let start = cells(&torus);
+        for _ in 0..PREVIEW_GENERATIONS {
+            torus.step();
+        }
+        assert_eq!(cells(&torus), start);
+
+        let mut dead = EdgePreview::new(Boundary::DeadEdge);
+        for _ in 0..PREVIEW_GENERATIONS {
+            dead.step();
+        }
+        assert_ne!(cells(&dead), start);
+    }
+}
@@ -0,0 +1,62 @@
+use::alloc::vec::Vec;
+use agb::fixnum::Num;
+
+use crate::{Graph, NodeType::Cell, BURNING, DEAD, LIVE};
+use crate::prng::Prng;
+
+// Chance of a cell growing a tree or being struck each generation, out of one
+pub type Chance = Num<u32, 16>;
+const CHANCE_BITS: u32 = 16;
+
+fn happens(chance: Chance, rng: &mut Prng) -> bool {
+    Chance::from_raw(rng.next_u32() >> (32 - CHANCE_BITS)) < chance
+}
+
+// Drossel-Schwabl forest fire over empty ground, trees and burning trees.
+// Fires burn out in a generation, trees next to one catch and others are
+// struck now and then, and trees grow back on the empty ground.
+pub fn step(graph: &mut Graph, growth: Chance, lightning: Chance, rng: &mut Prng) {
+    let near_fire: Vec<bool> = (0..graph.nodes.len())
+        .map(|i| graph.successors(i).any(|j| matches!(graph.nodes[j].state, Cell(BURNING))))
+        .collect();
+    for (n, near) in graph.nodes.iter_mut().zip(near_fire) {
+        if let Cell(s) = n.state {
+            let next = match s {
+                BURNING => DEAD,
+                LIVE if near || happens(lightning, rng) => BURNING,
+                DEAD if happens(growth, rng) => LIVE,
+                s => s,
+            };
+            n.age = if next == s { n.age.saturating_add(1) } else { 0 };
+            n.state = Cell(next);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{new_world, Boundary, Neighborhood};
+    use crate::prng::DEFAULT_SEED;
+
+    // A fire spreads along a row of trees a cell each generation and
+    // leaves empty ground, with nothing growing or struck at no chance
+    #[test_case]
+    fn fire_spreads_and_burns_out(_gba: &mut agb::Gba) {
+        let mut graph = new_world(8, 8, Boundary::DeadEdge, Neighborhood::VonNeumann);
+        graph.nodes[0].state = Cell(BURNING);
+        for i in 1..8 {
+            graph.nodes[i].state = Cell(LIVE);
+        }
+        let mut rng = Prng::new(DEFAULT_SEED);
+        let none = Chance::from_raw(0);
+        step(&mut graph, none, none, &mut rng);
+        assert!(matches!(graph.nodes[0].state, Cell(DEAD)));
+        assert!(matches!(graph.nodes[1].state, Cell(BURNING)));
+        assert!(matches!(graph.nodes[2].state, Cell(LIVE)));
+        for _ in 0..7 {
+            step(&mut graph, none, none, &mut rng);
+        }
+        assert!(graph.nodes.iter().all(|n| matches!(n.state, Cell(DEAD))));
+    }
+}
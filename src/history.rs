@@ -0,0 +1,133 @@
+use::alloc::vec::Vec;
+
+use automata::{Graph, CellState};
+use automata::cell::{LifeCell, WireCell};
+use automata::history;
+
+use crate::analysis::Velocity;
+
+// Previous boards for rewinding, each cell stored in as few bits as the
+// number of states allows. The width is fixed by the cell type, so a change
+// in states starts a history of another type.
+pub enum History {
+    Life(history::History<LifeCell>),
+    Wire(history::History<WireCell>),
+    Full(history::History<CellState>),
+}
+
+impl History {
+
+    pub fn new(depth: usize, cells: usize, states: u8) -> Self {
+        match states {
+            0..=2 => History::Life(history::History::new(depth, cells)),
+            3..=4 => History::Wire(history::History::new(depth, cells)),
+            _ => History::Full(history::History::new(depth, cells)),
+        }
+    }
+
+    // Returns the depth actually allocated
+    pub fn resize(&mut self, depth: usize) -> usize {
+        match self {
+            History::Life(h) => h.resize(depth),
+            History::Wire(h) => h.resize(depth),
+            History::Full(h) => h.resize(depth),
+        }
+    }
+
+    // A new width drops the stored boards, the old ones being freed before
+    // the new are allocated. Returns the depth actually allocated, which may
+    // be less for wider cells.
+    pub fn set_states(&mut self, states: u8) -> usize {
+        let (depth, cells) = match self {
+            History::Life(h) => (h.depth(), h.cells()),
+            History::Wire(h) => (h.depth(), h.cells()),
+            History::Full(h) => (h.depth(), h.cells()),
+        };
+        *self = History::new(0, cells, states);
+        self.resize(depth)
+    }
+
+    pub fn push<M>(&mut self, graph: &Graph<M>, generation: u32) {
+        match self {
+            History::Life(h) => h.push(graph, generation),
+            History::Wire(h) => h.push(graph, generation),
+            History::Full(h) => h.push(graph, generation),
+        }
+    }
+
+    // Restores the most recent board, returning the generation it was taken at
+    pub fn pop<M>(&mut self, graph: &mut Graph<M>) -> Option<u32> {
+        match self {
+            History::Life(h) => h.pop(graph),
+            History::Wire(h) => h.pop(graph),
+            History::Full(h) => h.pop(graph),
+        }
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        match self {
+            History::Life(h) => h.memory_usage(),
+            History::Wire(h) => h.memory_usage(),
+            History::Full(h) => h.memory_usage(),
+        }
+    }
+}
+
+pub const EVENT_LOG_SIZE: usize = 8;
+
+#[derive(Copy,Clone)]
+pub enum LogEntry {
+    // Rules and the number of states
+    Rule([[u16; 9]; 2], u8),
+    Velocity(Velocity),
+    // Generations until the board settled and the population left
+    Lifespan(u32, u16),
+    // An ant's highway, logged at the generation it began, and its period
+    Highway(u32),
+}
+
+// The most recent rule changes and measurements along with their generation
+pub struct EventLog {
+    entries: Vec<(u32, LogEntry)>,
+}
+
+impl EventLog {
+
+    pub fn new() -> Self {
+        EventLog { entries: Vec::with_capacity(EVENT_LOG_SIZE) }
+    }
+
+    pub fn record(&mut self, generation: u32, entry: LogEntry) {
+        if self.entries.len() == EVENT_LOG_SIZE {
+            self.entries.remove(0);
+        }
+        self.entries.push((generation, entry));
+    }
+
+    pub fn entries(&self) -> &[(u32, LogEntry)] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use automata::{new_world, Boundary, Neighborhood, NodeType::Cell, WIRE};
+
+    // Going from Life to Wireworld widens the cells yet keeps the depth
+    #[test_case]
+    fn states_pick_the_cell_width(_gba: &mut agb::Gba) {
+        let mut graph: Graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        let mut history = History::new(4, graph.nodes.len(), 2);
+        let narrow = history.memory_usage();
+        assert!(matches!(history, History::Life(_)));
+        assert_eq!(history.set_states(4), 4);
+        assert!(matches!(history, History::Wire(_)));
+        assert!(history.memory_usage() > narrow);
+        graph.nodes[5].state = Cell(WIRE);
+        history.push(&graph, 3);
+        graph.nodes[5].state = Cell(CellState(0));
+        assert_eq!(history.pop(&mut graph), Some(3));
+        assert!(matches!(graph.nodes[5].state, Cell(WIRE)));
+    }
+}
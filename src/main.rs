@@ -7,25 +7,212 @@
 
 extern crate alloc;
 
+mod text;
+mod history;
+mod analysis;
+mod sharecode;
+mod repeat;
+mod samples;
+mod preferences;
+mod context;
+mod recording;
+mod alarm;
+mod timelapse;
+mod cycle;
+mod edges;
+mod fire;
+mod anneal;
+mod sparkline;
+
 const WIDTH  : u16 = 30;
 const HEIGHT : u16 = 20;
 const TILE_SIZE : u16 = 8;
 
+const EWRAM_SIZE : usize = 256 * 1024;
+// Blocks taken when measuring the free heap, and the size below which what
+// is left isn't worth counting
+const FREE_PROBES : usize = 16;
+const FREE_SCRAP : usize = 64;
+const IWRAM_SIZE : usize = 32 * 1024;
+
+const HISTORY_DEPTHS : [u16; 5] = [0, 16, 32, 64, 128];
+
+const GLIDER_BATCH : u16 = 8;
+
+const MARKER_COUNT : usize = 8;
+
+const ANT_COUNT : usize = 8;
+
+const INSPECT_WIDTH : u16 = 11;
+const INSPECT_HEIGHT : u16 = 6;
+
+const TOAST_FRAMES : u16 = 120;
+
+const LAB_SIZE : u16 = 16;
+
+// Cells within two steps of one in Larger than Life
+const RANGE_NEIGHBORS : u8 = 24;
+const LAB_X : u16 = (WIDTH - LAB_SIZE) / 2;
+const LAB_Y : u16 = 2;
+
+const SRAM_SIZE : usize = 32 * 1024;
+const SRAM_ROW_BYTES : usize = 8;
+const SRAM_PAGE_ROWS : usize = 8;
+
+// Generations between updates of the context kept in SRAM
+const CONTEXT_INTERVAL : u32 = 64;
+
+// The board as it was last, with the board cursor and generation ahead of a
+// copy of the saved world, so it can be carried on after the power is off
+const SESSION_OFFSET : usize = 16384;
+const SESSION_MAGIC : u8 = b'R';
+const SESSION_HEADER : usize = 8;
+const SESSION_FRAMES : u32 = 600;
+
+// Boots that started without reaching the main loop
+const BOOT_OFFSET : usize = 8192;
+const SAFE_MODE_BOOTS : u8 = 2;
+
+// The saved world, at the start of SRAM
+const WORLD_OFFSET : usize = 0;
+// Name of the saved world, stored just after it
+const SAVE_NAME_OFFSET : usize = 640;
+const SAVE_NAME_LENGTH : usize = 28;
+
+// User stamps are kept in SRAM after the saved world
+const STAMP_SLOTS : usize = 4;
+const STAMP_OFFSET : usize = 1024;
+const STAMP_BYTES : usize = 2 + 2 * STAMP_SIZE as usize;
+
+const CODE_ROW_LENGTH : usize = 20;
+
+const REPEAT_MAX : u16 = 60;
+// Furthest in pixels the echo of the board is drawn from it
+const ECHO_MAX : u16 = 7;
+// Sixteenths of the echo mixed with the board beneath
+const ECHO_WEIGHT : u8 = 5;
+// and of the second board in the layers mode, which shares its background
+const LAYER_WEIGHT : u8 = 11;
+// Most sixteenths of the board shown through the menu windows
+const SEE_THROUGH_MAX : u16 = 12;
+// Adaptive speed sensitivity, and the changed cells it takes at the lowest
+// to slow a generation by a quarter of the interval
+const ADAPT_MAX : u16 = 4;
+const ADAPT_CELLS : u32 = 32;
+// Most random cells brought to life at a time, and most generations apart
+const NOISE_MAX : u16 = 99;
+const LAYER_TILE : u16 = 64;
+
+// Timer ticks at 16KHz, half a second for one generation is too long
+const GENERATION_BUDGET : u16 = 8192;
+const WATCHDOG_STRIKES : u16 = 3;
+
+// A ramped run starts at a generation a second and reaches full speed
+// after ten
+const TIMER_SECOND : u32 = 16384;
+const RAMP_TICKS : u32 = 10 * TIMER_SECOND;
+// Cells from the edge a live one can come before the plane is moved
+const PLANE_MARGIN : i32 = 2;
+// Timer ticks a frame takes, 280896 cycles over the divider of 1024
+const FRAME_TICKS : u32 = 274;
+// Held together anywhere, starts the game over from boot
+const SOFT_RESET : [Button; 4] = [Button::A, Button::B, Button::START, Button::SELECT];
+// Frames between generations while steering, slow enough to edit between
+const STEER_FRAMES : u16 = 30;
+// Pressed while paused, puts on the tribute show
+const TRIBUTE_CODE : [Button; 10] = [Button::UP, Button::UP, Button::DOWN, Button::DOWN,
+                                     Button::LEFT, Button::RIGHT, Button::LEFT, Button::RIGHT,
+                                     Button::B, Button::A];
+
+// A kiosk moves on to the next show after this many generations, or after
+// a minute of a visitor leaving it paused
+const KIOSK_SHOW_GENERATIONS : u32 = 400;
+const KIOSK_IDLE_FRAMES : u32 = 60 * 60;
+
+const SAMPLE_INTERVALS : [u16; 4] = [0, 16, 64, 256];
+// Generations a live cell lasts at most, none for no limit
+const LIFESPANS : [u16; 7] = [0, 2, 3, 5, 10, 20, 50];
+const STATS_ROWS : u16 = 8;
+// Rule entries from 2 up are the chance out of 256 of a live cell next, while
+// 0 and 1 are never and always as in a rulestring
+const CHANCE_ONE : u16 = 256;
+const CHANCE_STEP : u16 = 16;
+// Cells from the cursor a gravity well reaches, and the chance out of
+// CHANCE_ONE it brings each dead one there to life a generation
+const WELL_RADIUS : i32 = 3;
+const WELL_CHANCE : u16 = 24;
+
+// Settings on the controls page, each a number stepped with left and right
+const CONTROL_ROWS : [&str; 10] = ["BOARD DELAY", "BOARD RATE", "MENU DELAY", "MENU RATE", "TURBO EVERY", "ECHO",
+                                   "SEE THROUGH", "ADAPTIVE", "NOISE CELLS", "NOISE EVERY"];
+
+// Analysis tools, each started from the board as it is
+const TOOLS : [&str; 9] = ["SHIP", "PRED", "LIFESPAN", "RUN UNTIL", "TIME LAPSE", "TRY RULE", "RANDOM RULE",
+                           "CLEAR ELECTRONS", "PROBE"];
+// Most generations run between frames drawn when running until a goal
+const UNTIL_EVERY_MAX : u16 = 99;
+// Frames each time lapse snapshot is shown for
+const LAPSE_FRAME_TIME : u16 = 6;
+// Forest fire chances out of 65536 to start with, about 0.05 and 0.0001,
+// and the step LEFT and RIGHT change them by
+const GROWTH : u32 = 3277;
+const LIGHTNING : u32 = 7;
+const FIRE_STEP : u32 = 7;
+// Generations a rule is tried out for before it is kept
+const TRY_GENERATIONS : u32 = 50;
+// Neighbor weights across and down from the top left, the middle one being
+// the cell itself, and the most each can be
+const EVEN_WEIGHTS : [u8; 9] = [1, 1, 1, 1, 0, 1, 1, 1, 1];
+const WEIGHT_MAX : u8 = 3;
+
+const PREDECESSOR_SIZE : u16 = 3;
+const PREDECESSOR_BUDGET : u32 = 200_000;
+const PREDECESSOR_STEPS : u32 = 500;
+
+// The way along the edges of a graph each button of the D-pad goes
+const DIRECTIONS : [(Button, Direction); 4] = [(Button::UP, Direction::Up), (Button::DOWN, Direction::Down),
+                                              (Button::LEFT, Direction::Left), (Button::RIGHT, Direction::Right)];
+
 use::agb::{
     display::{
         object::{Object, Graphics, Tag, OamManaged},
-        tiled::{ RegularMap, RegularBackgroundSize, TiledMap, VRamManager},
+        tiled::{ RegularMap, RegularBackgroundSize, TiledMap, TileSetting, VRamManager},
+        blend::{Layer, BlendMode},
         Priority,
     },
     input::{Tri, Button},
     save::{SaveManager, Error},
+    fixnum::Num,
     include_background_gfx,
     include_aseprite,
 };
 
-use::alloc::{vec::Vec};
+use::alloc::{vec, vec::Vec, string::String, format};
+
+use core::mem::size_of;
+
+use automata::{NodeIndex, NodeType, CellState, Boundary, Neighborhood, Direction, Rule, Wireworld, new_world, weigh_edges, live_bounds, step_graph, step_graph_with, step_memory, simulate_into, step_elementary, range_count};
+use automata::{DEAD, LIVE, TAIL, WIRE, RESTING, RED, BURNING, PAPER, SCISSORS, SHARK, MAX_STATES};
+use automata::cell::{Cell as _, WireCell};
+use automata::{prng, pattern, rulestring, margolus, layers, gas, species, wator, sandpile, table};
+use automata::pattern::{GLIDER, PATTERNS, CIRCUITS, SHOWS, TRIBUTE, TRIBUTE_CARD, STAMP_SIZE, Show, Stamp, stamp, place, place_circuit};
+use automata::prng::{Prng, DEFAULT_SEED};
+use automata::turmite::{Ant, Turmite, LANGTONS_ANT, TURMITE_STATES};
 
-use core::ops::Not;
+use crate::text::{draw_text, draw_number};
+use crate::history::{History, EventLog, LogEntry};
+use crate::context::{Context, SaveStatus};
+use crate::repeat::{KeyRepeat, RepeatProfile};
+use crate::recording::Input;
+use automata::margolus::BLOCK_RULES;
+use crate::alarm::Alarms;
+use crate::edges::{EdgePreview, PREVIEW_TILES};
+use crate::fire::Chance;
+use crate::anneal::{TEMPERATURE_MAX, TWIST_MAX};
+use automata::order::UpdateOrder;
+use crate::timelapse::TimeLapse;
+use crate::sparkline::Sparkline;
+use crate::analysis::{ShipTracker, PredecessorSearch, Search, LifespanTracker, Lifespan, SignalProbe, RunUntil, Goal, board_hash};
 
 include_background_gfx!(background_tiles, "ff00ff",
     tiles =>  deduplicate "gfx/tiles.aseprite"
@@ -33,127 +220,120 @@ include_background_gfx!(background_tiles, "ff00ff",
 
 const SPRITES: &Graphics = include_aseprite!("gfx/sprites.aseprite");
 const CURSOR_SPRITE: &Tag = SPRITES.tags().get("Cursor");
+const MARKER_SPRITE: &Tag = SPRITES.tags().get("Marker");
+const MATERIAL_SPRITE: &Tag = SPRITES.tags().get("Material");
+const PROBE_SPRITE: &Tag = SPRITES.tags().get("Probe");
+const ANT_SPRITE: &Tag = SPRITES.tags().get("Ant");
 
-pub struct Graph {
-    nodes: Vec<NodeData>,
-    edges: Vec<EdgeData>,
-}
-
-pub type NodeIndex = usize;
-
-#[derive(Debug,PartialEq,Copy,Clone)]
-enum CellState {
-    Dead, Live
-}
+// What SELECT cycles through when drawing circuits
+const MATERIALS: [CellState; 4] = [WIRE, LIVE, TAIL, DEAD];
 
-impl From<u16> for CellState {
-    fn from(item: u16) -> Self {
-        match item {
-            0 => Dead,
-            1 => Live,
-            _ => Dead
-        }
-    }
-}
+const DEAD_TILE: u16 = 1;
+const LIVE_TILE: u16 = 2;
+// Shades of the live tile for cells decaying after death
+const DECAY_TILES: [u16; 4] = [58, 59, 60, 61];
+const WIRE_TILE: u16 = 62;
+const TAIL_TILE: u16 = 63;
+const HEX_DEAD_TILE: u16 = 68;
+const HEX_LIVE_TILE: u16 = 69;
+const TREE_TILE: u16 = 64;
+// Walls are drawn in the darkest shade, and emitters lit up
+const WALL_TILE: u16 = DECAY_TILES[3];
+const EMITTER_TILE: u16 = WIRE_TILE;
+// Lattice gas particles, in the order of the gas shapes
+const GAS_TILES: [u16; 8] = [70, 71, 72, 73, 74, 75, 76, 77];
+// Trail colors for the ants after the first, which paints live cells
+const ANT_TILES: [u16; ANT_COUNT - 1] = [TAIL_TILE, WIRE_TILE, DECAY_TILES[0], 64, 65, 66, 67];
 
-impl Not for CellState {
-    type Output = Self;
-    fn not(self) -> Self::Output {
-        match self {
-            Dead => Live,
-            Live => Dead
-        }
-    }
-}
+// Boards and menus alike, the menu entries being config menu items
+type Graph = automata::Graph<MenuType>;
+type NodeData = automata::NodeData<MenuType>;
 
 use crate::MenuType::*;
-use crate::NodeType::*;
-use crate::CellState::*;
+use automata::NodeType::*;
 
-#[derive(PartialEq,Debug)]
+#[derive(PartialEq,Debug,Copy,Clone)]
 enum MenuType {
-    New, Save, Load
-}
-
-#[derive(Debug)]
-enum NodeType {
-    Cell(CellState),
-    Menu(MenuType),
-}
-
-#[derive(Debug)]
-pub struct NodeData {
-    state: NodeType,
-    x: u16,
-    y: u16,
-    first_outgoing_edge: Option<EdgeIndex>
+    New, Save, Load, HistoryDepth, Edge, Gliders, Log, Marks, Goto, Follow, Tools, Lab, Stamps, Controls, Paint, Sample, Stats, States, Mode, Ants, TurmiteRules, Wolfram, Grid, Highway, Ramp, Kiosk, Preset
 }
 
-pub type EdgeIndex = usize;
-
-pub struct EdgeData {
-    direction: Option<Button>,
-    target: NodeIndex,
-    next_outgoing_edge: Option<EdgeIndex>
+// Generations paced by the timer keep to the clock and those paced by
+// frames keep to the screen, so only they speed up with an emulator's fast
+// forward
+#[derive(PartialEq,Debug,Copy,Clone)]
+enum Pacing {
+    Timer, Frames
 }
 
-impl Graph {
-    
-    pub fn new() -> Self {
-        Graph { nodes: Vec::new(), edges: Vec::new() }
-    }
-
-    pub fn add_node(&mut self, x:u16, y:u16, state:NodeType) -> NodeIndex {
-        let index = self.nodes.len();
-        self.nodes.push(NodeData { x,y,state,first_outgoing_edge: None });
-        index
-    }
-
-    pub fn add_edge(&mut self, source: NodeIndex, target: NodeIndex, direction: Option<Button>) {
-        let edge_index = self.edges.len();
-        let node_data = &mut self.nodes[source];
-        self.edges.push(EdgeData {
-            direction,
-            target: target,
-            next_outgoing_edge: node_data.first_outgoing_edge
-        });
-        node_data.first_outgoing_edge = Some(edge_index);
-    }
-    
-    pub fn successors(&self, source: NodeIndex) -> Successors {
-        let first_outgoing_edge = self.nodes[source].first_outgoing_edge;
-        Successors { graph: self, current_edge_index: first_outgoing_edge }
-    }
+impl Pacing {
 
-    pub fn living_neighbors_count_of(&self, source: NodeIndex) -> u16 {
-        let mut n = 0;
-        for e in self.successors(source) {
-            match self.nodes[e].state {
-                Cell(s) => n += s as u16,
-                _ => n = n,
-            }
+    fn next(self) -> Self {
+        match self {
+            Pacing::Timer => Pacing::Frames,
+            Pacing::Frames => Pacing::Timer,
         }
-        n
     }
-
 }
 
-pub struct Successors<'graph> {
-    graph: &'graph Graph,
-    current_edge_index: Option<EdgeIndex>
+#[derive(PartialEq,Debug,Copy,Clone)]
+enum Automaton {
+    // Birth and survival rules, with decaying states when there are more than two
+    Life,
+    Wireworld,
+    BriansBrain,
+    // Cells only change under the ants
+    Ant,
+    // Wolfram's one dimensional rules, the bottom row being the current
+    // generation and the rows above the ones before it
+    Elementary,
+    // Birth and survival over ranges of counts of the 24 cells within two
+    // steps rather than the eight around
+    LargerThanLife,
+    // Reversible rules on 2x2 blocks, the blocks shifting diagonally by a
+    // cell every other generation
+    Margolus,
+    // Life with two colors of live cell, each birth taking the color most
+    // of its parents have
+    Immigration,
+    // Life with a second board drawn over the first, seeded from it
+    Layers,
+    // HPP lattice gas, each cell holding a particle for each way out of it
+    LatticeGas,
+    // Trees growing on empty ground and burning down
+    ForestFire,
+    // Each cell goes with the vote of itself and its neighbors, or against
+    // it now and then the hotter it is
+    Anneal,
+    // Rock, paper and scissors, each cell taken by the species that beats it
+    Species,
+    // Fish and sharks swimming about, breeding, eating and starving
+    WaTor,
+    // Each cell a pile of grains, the state being how many
+    Sandpile,
+    // Whatever a table of each state against each count of live neighbors
+    // says
+    Table,
 }
 
-impl<'graph> Iterator for Successors<'graph> {
-    type Item = NodeIndex;
-
-    fn next(&mut self) -> Option<NodeIndex> {
-        match self.current_edge_index {
-            None => None,
-            Some(edge_num) => {
-                let edge = &self.graph.edges[edge_num];
-                self.current_edge_index = edge.next_outgoing_edge;
-                Some(edge.target)
-            }
+impl Automaton {
+    fn next(self) -> Self {
+        match self {
+            Automaton::Life => Automaton::Wireworld,
+            Automaton::Wireworld => Automaton::BriansBrain,
+            Automaton::BriansBrain => Automaton::Ant,
+            Automaton::Ant => Automaton::Elementary,
+            Automaton::Elementary => Automaton::LargerThanLife,
+            Automaton::LargerThanLife => Automaton::Margolus,
+            Automaton::Margolus => Automaton::Immigration,
+            Automaton::Immigration => Automaton::Layers,
+            Automaton::Layers => Automaton::LatticeGas,
+            Automaton::LatticeGas => Automaton::ForestFire,
+            Automaton::ForestFire => Automaton::Anneal,
+            Automaton::Anneal => Automaton::Species,
+            Automaton::Species => Automaton::WaTor,
+            Automaton::WaTor => Automaton::Sandpile,
+            Automaton::Sandpile => Automaton::Table,
+            Automaton::Table => Automaton::Life,
         }
     }
 }
@@ -194,23 +374,9 @@ impl<'a> Cursor<'a> {
     }
 
     fn move_cursor(&mut self, graph : &Graph, button : Button) {
-        let mut maybe_edge = graph.nodes[self.node].first_outgoing_edge;
-        loop {
-            if let Some(edge_index) = maybe_edge {
-                let b = graph.edges[edge_index].direction;
-                match b {
-                    Some(b) => {
-                        if b == button {
-                            self.node = graph.edges[edge_index].target;
-                            break;
-                        }
-                    },
-                    None => ()
-                }
-                maybe_edge = graph.edges[edge_index].next_outgoing_edge
-            } else {
-                break;
-            }
+        let direction = DIRECTIONS.iter().find(|(b, _)| *b == button);
+        if let Some(node) = direction.and_then(|(_, d)| graph.towards(self.node, *d)) {
+            self.node = node;
         }
         self.redraw(graph);
     }
@@ -221,37 +387,306 @@ impl<'a> Cursor<'a> {
     }
 }
 
-fn new_world(width: u16, height: u16) -> Graph {
-    let mut graph = Graph::new();
-    for i in 0..width*height {
-        graph.add_node(i%width, i/width, Cell(Dead));
-    }
-    for i in 0..width {
-    for j in 0..height {
-        let n_right      = (i + 1) % width + j*width;
-        let n_down       = ((((j+1) % height )*width))+i;
-        let n_down_right = ((((j+1) % height )*width))+((i+1)%width);
-        let n_down_left : usize =
-              (((j+1) % height )*width) as usize
-            + (i as isize -1 as isize).rem_euclid(width as isize) as usize;
-        let n = j*width+i;
-        graph.add_edge(n.into(), (n_right).into(), Some(Button::RIGHT));
-        graph.add_edge((n_right).into(), n.into(), Some(Button::LEFT));
-        graph.add_edge(n.into(), (n_down).into(), Some(Button::DOWN));
-        graph.add_edge((n_down).into(), n.into(), Some(Button::UP));
-        graph.add_edge(n.into(), (n_down_right).into(), None);
-        graph.add_edge((n_down_right).into(), n.into(), None);
-        graph.add_edge(n.into(), (n_down_left).into(), None);
-        graph.add_edge((n_down_left).into(), n.into(), None);
+// Navigation graph of the config menu, the rules grid above the options
+fn new_settings_graph(settings: &Settings) -> Graph {
+    // Settings Graph (Rules)
+    let mut graph_settings = Graph::new();
+    for j in 0..2 {
+    for i in 0..9 {
+        graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+i,
+            settings.window_y+settings.rules_offset_y+j,
+            Cell(((settings.rules[j as usize][i as usize] != 0) as u16).into())
+        );
+    }}
+    // Counts the neighborhood never reaches are left out, and ranges are
+    // edited in their own dialog opened from the first count
+    let counts = match settings.automaton {
+        Automaton::LargerThanLife => 1,
+        _ => settings.neighborhood.size() as usize + 1,
+    };
+    for j in 0..2 {
+    for i in 0..counts {
+        if i+1 < counts {
+            graph_settings.add_edge(j*9+i, j*9+i+1, Some(Direction::Right));
+            graph_settings.add_edge(j*9+i+1, j*9+i, Some(Direction::Left));
+        }
+        if j < 1 {
+            graph_settings.add_edge(j*9+i, (j+1)*9+i, Some(Direction::Down));
+            graph_settings.add_edge((j+1)*9+i, j*9+i, Some(Direction::Up));
+        }
     }}
+
+    //Settings Graph (New/Save/Load)
+    let node_new = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+3,
+            Menu(New));
+    let node_save = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+4,
+            Menu(Save));
+    let node_load = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+5,
+            Menu(Load));
+    let node_history = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+6,
+            Menu(HistoryDepth));
+    let node_edge = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+7,
+            Menu(Edge));
+    let node_gliders = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+8,
+            Menu(Gliders));
+    let node_log = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+9,
+            Menu(Log));
+    let node_marks = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+3,
+            Menu(Marks));
+    let node_goto = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+4,
+            Menu(Goto));
+    let node_follow = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+5,
+            Menu(Follow));
+    let node_tools = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+6,
+            Menu(Tools));
+    let node_lab = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+7,
+            Menu(Lab));
+    let node_stamps = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+8,
+            Menu(Stamps));
+    let node_controls = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+10,
+            Menu(Controls));
+    let node_paint = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+9,
+            Menu(Paint));
+    let node_sample = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+11,
+            Menu(Sample));
+    let node_stats = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+10,
+            Menu(Stats));
+    let node_states = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+11,
+            Menu(States));
+    let node_mode = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+12,
+            Menu(Mode));
+    let node_ants = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+12,
+            Menu(Ants));
+    let node_turmite = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+13,
+            Menu(TurmiteRules));
+    let node_wolfram = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+13,
+            Menu(Wolfram));
+    let node_grid = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+14,
+            Menu(Grid));
+    let node_highway = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+14,
+            Menu(Highway));
+    let node_ramp = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x,
+            settings.window_y+settings.rules_offset_y+15,
+            Menu(Ramp));
+    let node_kiosk = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y+15,
+            Menu(Kiosk));
+    // Beside the rules, reached from the end of either row
+    let node_preset = graph_settings.add_node(
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+            settings.window_y+settings.rules_offset_y,
+            Menu(Preset));
+    graph_settings.add_edge(counts-1, node_preset, Some(Direction::Right));
+    graph_settings.add_edge(9+counts-1, node_preset, Some(Direction::Right));
+    graph_settings.add_edge(node_preset, counts-1, Some(Direction::Left));
+    graph_settings.add_edge(node_preset, node_marks, Some(Direction::Down));
+    graph_settings.add_edge(node_new, 9, Some(Direction::Up));
+    graph_settings.add_edge(node_marks, node_preset, Some(Direction::Up));
+    // Up and down each column, then across between the columns
+    for column in [
+        &[node_new, node_save, node_load, node_history, node_edge,
+          node_gliders, node_log, node_controls, node_sample, node_mode, node_turmite, node_grid, node_ramp][..],
+        &[node_marks, node_goto, node_follow, node_tools, node_lab,
+          node_stamps, node_paint, node_stats, node_states, node_ants, node_wolfram, node_highway, node_kiosk][..],
+    ] {
+        for pair in column.windows(2) {
+            graph_settings.add_edge(pair[0], pair[1], Some(Direction::Down));
+            graph_settings.add_edge(pair[1], pair[0], Some(Direction::Up));
+        }
+    }
+    for (left, right) in [(node_new, node_marks), (node_save, node_goto), (node_load, node_follow),
+                          (node_history, node_tools), (node_edge, node_lab),
+                          (node_gliders, node_stamps), (node_log, node_paint),
+                          (node_controls, node_stats), (node_sample, node_states),
+                          (node_mode, node_ants), (node_turmite, node_wolfram),
+                          (node_grid, node_highway), (node_ramp, node_kiosk)] {
+        graph_settings.add_edge(left, right, Some(Direction::Right));
+        graph_settings.add_edge(right, left, Some(Direction::Left));
+    }
+    for n in 9..9+counts {
+        graph_settings.add_edge(n, node_new, Some(Direction::Down));
+    }
+    graph_settings
+}
+
+// A board for the settings, its edges weighed for them
+fn new_board(settings: &Settings) -> Graph {
+    let mut graph = new_world(WIDTH, HEIGHT, settings.boundary, settings.neighborhood);
+    weigh_edges(&mut graph, WIDTH, settings.boundary, settings.neighborhood, &settings.weights);
     graph
 }
 
-fn new_config_menu(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings) {
+fn shifted_node(node: NodeIndex, dx: i32, dy: i32) -> NodeIndex {
+    let x = (node as i32 % WIDTH as i32 + dx).rem_euclid(WIDTH as i32);
+    let y = (node as i32 / WIDTH as i32 + dy).rem_euclid(HEIGHT as i32);
+    (y * WIDTH as i32 + x) as NodeIndex
+}
+
+// Dead cells near the center come alive now and then, returning those that
+// did
+fn gravity_well(graph: &mut Graph, center: NodeIndex, rng: &mut Prng) -> Vec<NodeIndex> {
+    let (cx, cy) = (graph.nodes[center].x as i32, graph.nodes[center].y as i32);
+    let mut seeded = Vec::new();
+    for (i, n) in graph.nodes.iter_mut().enumerate() {
+        let (dx, dy) = (n.x as i32 - cx, n.y as i32 - cy);
+        if dx * dx + dy * dy <= WELL_RADIUS * WELL_RADIUS
+            && matches!(n.state, Cell(DEAD)) && by_chance(WELL_CHANCE, rng) {
+            n.state = Cell(LIVE);
+            n.age = 0;
+            seeded.push(i);
+        }
+    }
+    seeded
+}
+
+// Dead cells anywhere on the board brought to life, returning those that
+// were
+fn inject_noise(graph: &mut Graph, cells: u16, rng: &mut Prng) -> Vec<NodeIndex> {
+    let mut seeded = Vec::new();
+    for _ in 0..cells {
+        let i = rng.below(graph.nodes.len() as u32) as usize;
+        let n = &mut graph.nodes[i];
+        if matches!(n.state, Cell(DEAD)) {
+            n.state = Cell(LIVE);
+            n.age = 0;
+            seeded.push(i);
+        }
+    }
+    seeded
+}
+
+// How far to move the board to center its live cells, once any are within
+// PLANE_MARGIN of an edge. Whole pairs of rows keep hex rows leaning the
+// same way.
+fn plane_shift(graph: &Graph, neighborhood: Neighborhood) -> (i32, i32) {
+    let Some((left, top, right, bottom)) = live_bounds(graph) else {
+        return (0, 0);
+    };
+    let (w, h) = (WIDTH as i32, HEIGHT as i32);
+    if left >= PLANE_MARGIN && top >= PLANE_MARGIN
+        && right < w - PLANE_MARGIN && bottom < h - PLANE_MARGIN {
+        return (0, 0);
+    }
+    let dx = (w - 1 - left - right) / 2;
+    let dy = (h - 1 - top - bottom) / 2;
+    match neighborhood {
+        Neighborhood::Hex => (dx, dy - dy % 2),
+        Neighborhood::Moore | Neighborhood::VonNeumann => (dx, dy),
+    }
+}
+
+// Moves every cell of the world by (dx, dy), wrapping at the edges
+fn shift_world(graph: &mut Graph, dx: i32, dy: i32) {
+    let old: Vec<(NodeType<MenuType>, u16, u8)> = graph.nodes.iter()
+        .map(|n| (n.state, n.age, n.owner))
+        .collect();
+    for (i, (state, age, owner)) in old.into_iter().enumerate() {
+        let n = &mut graph.nodes[shifted_node(i, dx, dy)];
+        n.state = state;
+        n.age = age;
+        n.owner = owner;
+    }
+}
+
+// Outline the playfield when cells beyond the edge count as dead
+fn draw_world_edge(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings) {
+    let tileset = background_tiles::tiles.tiles;
+
+    for x in 0..WIDTH {
+    for y in 0..HEIGHT {
+        if let Some(tile) = world_edge_tile(settings, x, y) {
+            bg.set_tile(vram, (x, y), &tileset, tile);
+        }
+    }}
+}
+
+// What the outline has at a place, if it is along the edge
+fn world_edge_tile(settings: &Settings, x: u16, y: u16) -> Option<TileSetting> {
+    let bounded = settings.boundary != Boundary::Toroidal;
+    let left   = x == 0;
+    let right  = x == WIDTH-1;
+    let top    = y == 0;
+    let bottom = y == HEIGHT-1;
+    if !bounded {
+        Some(background_tiles::tiles.tile_settings[0])
+    } else if (left || right) && (top || bottom) {
+        Some(background_tiles::tiles.tile_settings[47].hflip(right).vflip(bottom))
+    } else if left || right {
+        Some(background_tiles::tiles.tile_settings[46].hflip(right))
+    } else if top || bottom {
+        Some(background_tiles::tiles.tile_settings[45].vflip(bottom))
+    } else {
+        None
+    }
+}
+
+// The sparkline sits over the top right corner of the outline, which is put
+// back once it goes
+fn hide_sparkline(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings) {
+    let tileset = background_tiles::tiles.tiles;
+    for x in WIDTH-sparkline::TILES..WIDTH {
+        let tile = world_edge_tile(settings, x, 0).unwrap_or(background_tiles::tiles.tile_settings[0]);
+        bg.set_tile(vram, (x, 0), &tileset, tile);
+    }
+}
+
+fn new_window(bg : &mut RegularMap, vram : &mut VRamManager,
+              window_x: u16, window_y: u16, window_width: u16, window_height: u16) {
     let tileset = background_tiles::tiles.tiles;
 
-    for x in settings.window_x..settings.window_x+settings.window_width-1 {
-    for y in settings.window_y..settings.window_y+settings.window_height-1 {
+    for x in window_x..window_x+window_width-1 {
+    for y in window_y..window_y+window_height-1 {
         bg.set_tile(
             vram,
             (x, y),
@@ -261,66 +696,313 @@ fn new_config_menu(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Set
     }}
 
     // Borders
-    for x in settings.window_x..settings.window_x+settings.window_width-1 {
+    for x in window_x..window_x+window_width-1 {
         bg.set_tile(
             vram,
-            (x, settings.window_y),
+            (x, window_y),
             &tileset,
             background_tiles::tiles.tile_settings[4],
         );
         bg.set_tile(
             vram,
-            (x, settings.window_y+settings.window_height-1),
+            (x, window_y+window_height-1),
             &tileset,
             background_tiles::tiles.tile_settings[4].vflip(true),
         );
     }
-    for y in settings.window_y..settings.window_y+settings.window_height-1 {
+    for y in window_y..window_y+window_height-1 {
         bg.set_tile(
             vram,
-            (settings.window_x, y),
+            (window_x, y),
             &tileset,
             background_tiles::tiles.tile_settings[5],
         );
         bg.set_tile(
             vram,
-            (settings.window_x+settings.window_width-1, y),
+            (window_x+window_width-1, y),
             &tileset,
             background_tiles::tiles.tile_settings[5].hflip(true),
         );
     }
     bg.set_tile(
         vram,
-        (settings.window_x, settings.window_y),
+        (window_x, window_y),
         &tileset,
         background_tiles::tiles.tile_settings[3],
     );
     bg.set_tile(
         vram,
-        (settings.window_x+settings.window_width-1, settings.window_y),
+        (window_x+window_width-1, window_y),
         &tileset,
         background_tiles::tiles.tile_settings[3].hflip(true),
     );
     bg.set_tile(
         vram,
-        (settings.window_x, settings.window_y+settings.window_height-1),
+        (window_x, window_y+window_height-1),
         &tileset,
         background_tiles::tiles.tile_settings[3].vflip(true),
     );
     bg.set_tile(
         vram,
-        (settings.window_x+settings.window_width-1, settings.window_y+settings.window_height-1),
+        (window_x+window_width-1, window_y+window_height-1),
         &tileset,
         background_tiles::tiles.tile_settings[3].hflip(true).vflip(true),
     );
+}
+
+// The board some generations on under the rules being edited, worked out on
+// a copy with the generator as it is so the board itself is left alone
+fn try_rule(graph: &Graph, settings: &Settings, mut rng: Prng) -> Graph {
+    let mut scratch = new_board(settings);
+    simulate_into(graph, &mut scratch, settings, WIDTH, settings.boundary, TRY_GENERATIONS, &mut rng);
+    scratch
+}
+
+// Each birth and survival count picked by a coin toss, except birth on no
+// neighbors, which would have the whole board flashing
+fn random_rules(rng: &mut Prng) -> [[u16; 9]; 2] {
+    let mut rules = [[0; 9]; 2];
+    for (row, counts) in rules.iter_mut().enumerate() {
+        for (n, r) in counts.iter_mut().enumerate() {
+            if row == 1 || n > 0 {
+                *r = rng.below(2) as u16;
+            }
+        }
+    }
+    rules
+}
+
+// A second order cell's next state is flipped if it was live the generation
+// before
+fn second_order_flip(next: CellState, was_live: bool) -> CellState {
+    if was_live { !next } else { next }
+}
+
+// The generation before a second order board, worked back from the board
+// and the one after it
+fn second_order_previous(graph: &Graph, after: &[bool], settings: &Settings, rng: &mut Prng) -> Vec<bool> {
+    (0..graph.nodes.len()).map(|i| {
+        let s = match graph.nodes[i].state { Cell(s) => s, _ => DEAD };
+        let count = settings.neighbors(graph, i, WIDTH, settings.boundary);
+        (settings.next_state(s, count, rng) == LIVE) != after[i]
+    }).collect()
+}
+
+// Blue wins a tie, as rules with even birth counts can have one
+fn majority_color<M>(graph: &automata::Graph<M>, node: NodeIndex) -> CellState {
+    let red = graph.neighbors_in_state(node, RED);
+    if red > graph.living_neighbors_count_of(node) { RED } else { LIVE }
+}
+
+// Color, turn and next state for each ant state on a dead and a live cell
+fn new_turmite_graph(settings: &Settings) -> Graph {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + 1;
+    let mut graph = Graph::new();
+    let fields = TURMITE_STATES * 6;
+    for i in 0..fields {
+        let (state, color, field) = ((i / 6) as u16, (i / 3 % 2) as u16, (i % 3) as u16);
+        graph.add_node(x + 4 + 7 * color + 2 * field, y + 4 + state, Cell(DEAD));
+    }
+    for i in 0..fields {
+        if i % 6 != 5 {
+            graph.add_edge(i, i + 1, Some(Direction::Right));
+            graph.add_edge(i + 1, i, Some(Direction::Left));
+        }
+        if i + 6 < fields {
+            graph.add_edge(i, i + 6, Some(Direction::Down));
+            graph.add_edge(i + 6, i, Some(Direction::Up));
+        }
+    }
+    graph
+}
+
+fn new_turmite_editor(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings) {
+    new_window(bg, vram,
+        settings.window_x, settings.window_y, settings.window_width, settings.window_height);
+
+    let x = settings.window_x + 2;
+    let y = settings.window_y + 1;
+    draw_text(bg, vram, x, y, "TURMITE");
+    draw_text(bg, vram, x+4, y+2, "DEAD");
+    draw_text(bg, vram, x+11, y+2, "LIVE");
+    draw_text(bg, vram, x+4, y+3, "C T S");
+    draw_text(bg, vram, x+11, y+3, "C T S");
+    for (state, row) in settings.turmite.iter().enumerate() {
+        let row_y = y + 4 + state as u16;
+        draw_text(bg, vram, x, row_y, "S");
+        draw_number(bg, vram, x+1, row_y, 1, state);
+        for (color, t) in row.iter().enumerate() {
+            let column = x + 4 + 7 * color as u16;
+            draw_number(bg, vram, column, row_y, 1, t.color as usize);
+            draw_text(bg, vram, column+2, row_y, t.turn.letter());
+            draw_number(bg, vram, column+4, row_y, 1, t.state as usize);
+        }
+    }
+}
+
+// A fresh board for a kiosk show, always in Life
+fn start_show(graph: &mut Graph, settings: &mut Settings, show: &Show, rng: &mut Prng) {
+    settings.set_automaton(Automaton::Life);
+    (settings.rules, settings.states) = rulestring::from_str(show.rule).unwrap();
+    settings.state_tiles();
+    for n in &mut graph.nodes {
+        n.state = Cell(if show.pattern.is_none() && rng.below(2) == 1 { LIVE } else { DEAD });
+        n.age = 0;
+    }
+    if let Some(p) = show.pattern {
+        stamp(graph, WIDTH, p, (WIDTH - p.width) / 2, (HEIGHT - p.height) / 2, 0);
+    }
+}
+
+// How far into the tribute code the buttons pressed so far have got, a
+// wrong one starting it over
+fn code_progress(progress: usize, pressed: Button) -> usize {
+    if pressed == TRIBUTE_CODE[progress] {
+        progress + 1
+    } else if pressed == TRIBUTE_CODE[0] {
+        1
+    } else {
+        0
+    }
+}
+
+// Timer ticks between generations part way through a ramp. The rate rather
+// than the interval goes up evenly, so the speeding up can be seen all along.
+fn ramp_interval(speed: u16, elapsed: u32) -> u16 {
+    let speed = speed as u64;
+    let elapsed = elapsed.min(RAMP_TICKS) as u64;
+    let second = TIMER_SECOND as u64;
+    let ramp = RAMP_TICKS as u64;
+    if speed >= second {
+        return speed as u16;
+    }
+    (second * ramp * speed / (ramp * speed + elapsed * (second - speed))) as u16
+}
+
+// Frames to wait in place of an interval in timer ticks, at least one
+fn interval_frames(interval: u16) -> u16 {
+    (interval as u32).div_ceil(FRAME_TICKS).max(1) as u16
+}
+
+// A quarter of the interval when nothing changed, a quarter more for each
+// ADAPT_CELLS changed cells over the sensitivity, up to four times as long
+fn adaptive_interval(interval: u16, activity: u16, sensitivity: u16) -> u16 {
+    if sensitivity == 0 {
+        return interval;
+    }
+    let quarters = (1 + activity as u32 * sensitivity as u32 / ADAPT_CELLS).min(16);
+    (interval as u32 * quarters / 4).min(u16::MAX as u32) as u16
+}
+
+// Cells that came alive or changed state in the last generation
+fn activity(graph: &Graph) -> u16 {
+    graph.nodes.iter()
+        .filter(|n| matches!(n.state, Cell(s) if s != DEAD) && n.age == 0)
+        .count() as u16
+}
+
+// A small bounded board drawn over the world
+fn new_lab(neighborhood: Neighborhood) -> Graph {
+    let mut lab = new_world(LAB_SIZE, LAB_SIZE, Boundary::DeadEdge, neighborhood);
+    for n in &mut lab.nodes {
+        n.x += LAB_X;
+        n.y += LAB_Y;
+    }
+    lab
+}
+
+fn new_lab_window(bg : &mut RegularMap, vram : &mut VRamManager, lab: &Graph, settings: &Settings) {
+    new_window(bg, vram, LAB_X-1, LAB_Y-1, LAB_SIZE+2, LAB_SIZE+2);
+    redraw_world(bg, vram, lab, settings);
+}
+
+fn redraw_world(bg : &mut RegularMap, vram : &mut VRamManager, graph: &Graph, settings: &Settings) {
+    let tileset = background_tiles::tiles.tiles;
+    settings.set_palettes(vram);
+    for n in &graph.nodes {
+        bg.set_tile(
+            vram,
+            (n.x, n.y),
+            &tileset,
+            settings.node_tile(n),
+        );
+    }
+}
+
+// The upper board of the layers mode, its dead cells left clear so the
+// lower one shows through
+fn draw_upper(bg : &mut RegularMap, vram : &mut VRamManager, graph: &Graph, upper: &[bool]) {
+    let tileset = background_tiles::tiles.tiles;
+    for (n, live) in graph.nodes.iter().zip(upper) {
+        let tile = if *live { LAYER_TILE } else { 0 };
+        bg.set_tile(
+            vram,
+            (n.x, n.y),
+            &tileset,
+            background_tiles::tiles.tile_settings[tile as usize],
+        );
+    }
+}
+
+// A time lapse snapshot blown back up to the board, each bit a 2x2 block
+fn draw_snapshot(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                 lapse: &TimeLapse, snapshot: usize) {
+    let tileset = background_tiles::tiles.tiles;
+    for y in 0..HEIGHT {
+    for x in 0..WIDTH {
+        let state = if lapse.live(snapshot, x / 2, y / 2) { LIVE } else { DEAD };
+        bg.set_tile(
+            vram,
+            (x, y),
+            &tileset,
+            background_tiles::tiles.tile_settings[settings.tiles[state.index()] as usize],
+        );
+    }}
+}
+
+// Under the rules, for the count the cursor is on
+fn draw_rule_chance(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                    entry: Option<u16>) {
+    let x = settings.window_x+settings.rules_offset_x;
+    let y = settings.window_y+settings.rules_offset_y+2;
+    match entry {
+        Some(entry) => {
+            draw_text(bg, vram, x, y, "CHANCE");
+            draw_number(bg, vram, x+7, y, 3, rule_chance(entry) as usize);
+            draw_text(bg, vram, x+10, y, "/256");
+        },
+        None => draw_text(bg, vram, x, y, "              "),
+    }
+}
+
+// Across the top of the window, for the automata the rules are used by
+fn draw_rulestring(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings) {
+    let width = (settings.window_width - 2) as usize;
+    let text = match settings.automaton {
+        Automaton::Life | Automaton::Immigration | Automaton::Layers => rulestring::to_string(&settings.rules, settings.states),
+        _ => String::new(),
+    };
+    draw_text(bg, vram, settings.window_x+1, settings.window_y+1,
+        &format!("{:<width$.width$}", text));
+}
+
+fn new_config_menu(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings) {
+    let tileset = background_tiles::tiles.tiles;
+
+    new_window(bg, vram,
+        settings.window_x, settings.window_y, settings.window_width, settings.window_height);
+    draw_rulestring(bg, vram, settings);
 
     // Rules
+    let ranges = settings.automaton == Automaton::LargerThanLife;
     for x in 0..=8 {
+        let tile = if !ranges && x <= settings.neighborhood.size() { 48+x as usize } else { 1 };
         bg.set_tile(
             vram,
             (settings.window_x+settings.rules_offset_x+x, settings.window_y+settings.rules_offset_y-1),
             &tileset,
-            background_tiles::tiles.tile_settings[48+x as usize],
+            background_tiles::tiles.tile_settings[tile],
         );
     }
     bg.set_tile(
@@ -337,6 +1019,16 @@ fn new_config_menu(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Set
     );
 
 
+    if ranges {
+        for (j, [low, high]) in settings.ranges.iter().enumerate() {
+            let (x, y) = (settings.window_x+settings.rules_offset_x, settings.window_y+settings.rules_offset_y+j as u16);
+            draw_number(bg, vram, x, y, 2, *low as usize);
+            draw_text(bg, vram, x+2, y, "-");
+            draw_number(bg, vram, x+3, y, 2, *high as usize);
+        }
+    }
+
+
     // New/Save/Load Menu
     for x in 0..3 {
         bg.set_tile(
@@ -362,177 +1054,1770 @@ fn new_config_menu(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Set
             background_tiles::tiles.tile_settings[28 + x as usize],
         );
     }
-    
 
+    // Options, in two columns
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x,
+        settings.window_y+settings.rules_offset_y+6,
+        "HIST");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x,
+        settings.window_y+settings.rules_offset_y+7,
+        "EDGE");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x,
+        settings.window_y+settings.rules_offset_y+8,
+        "GLIDERS");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x,
+        settings.window_y+settings.rules_offset_y+9,
+        "LOG");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x,
+        settings.window_y+settings.rules_offset_y+10,
+        "CONTROLS");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x,
+        settings.window_y+settings.rules_offset_y+11,
+        "SAMPLE");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x,
+        settings.window_y+settings.rules_offset_y+12,
+        "MODE");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x,
+        settings.window_y+settings.rules_offset_y+13,
+        "TURMITE");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+13,
+        "RULE");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x,
+        settings.window_y+settings.rules_offset_y+14,
+        "GRID");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x,
+        settings.window_y+settings.rules_offset_y+15,
+        "RAMP");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+15,
+        "KIOSK");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+3,
+        "MARKS");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+4,
+        "GOTO");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+5,
+        "FOLLOW");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+6,
+        "TOOLS");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+7,
+        "LAB");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+8,
+        "STAMPS");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+9,
+        "PAINT");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+10,
+        "STATS");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+11,
+        "STATES");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+12,
+        "ANTS");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y+14,
+        "HIGHWAY");
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x,
+        settings.window_y+settings.rules_offset_y,
+        "PRESET");
+    draw_config_options(bg, vram, settings);
 }
 
-struct Settings {
-    rules: [[u16;9];2],
-    speed: u16,
-    tiles: [u16;2],
+fn draw_config_options(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings) {
+    draw_number(bg, vram,
+        settings.window_x+settings.rules_offset_x+5,
+        settings.window_y+settings.rules_offset_y+6,
+        3, settings.history_depth as usize);
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+5,
+        settings.window_y+settings.rules_offset_y+7,
+        match settings.boundary {
+            Boundary::Toroidal => "WRAP ",
+            Boundary::DeadEdge if settings.plane => "PLANE",
+            Boundary::DeadEdge => "DEAD ",
+            Boundary::Reflective => "REFL ",
+        });
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+5,
+        settings.window_y+settings.rules_offset_y+14,
+        match settings.neighborhood {
+            Neighborhood::Moore => "SQ ",
+            Neighborhood::Hex => "HEX",
+            Neighborhood::VonNeumann => "VN ",
+        });
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+9,
+        settings.window_y+settings.rules_offset_y+14,
+        settings.update_order.name());
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+        settings.window_y+settings.rules_offset_y+5,
+        if settings.follow { "ON " } else { "OFF" });
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+        settings.window_y+settings.rules_offset_y+9,
+        if settings.paint { "ON " } else { "OFF" });
+    draw_number(bg, vram,
+        settings.window_x+settings.rules_offset_x+7,
+        settings.window_y+settings.rules_offset_y+11,
+        3, settings.sample_interval as usize);
+    draw_number(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+        settings.window_y+settings.rules_offset_y+11,
+        2, settings.states as usize);
+    if settings.lifespan > 0 {
+        draw_number(bg, vram,
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x+10,
+            settings.window_y+settings.rules_offset_y+11,
+            2, settings.lifespan as usize);
+    } else {
+        draw_text(bg, vram,
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x+10,
+            settings.window_y+settings.rules_offset_y+11,
+            "  ");
+    }
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+5,
+        settings.window_y+settings.rules_offset_y+12,
+        match settings.automaton {
+            Automaton::Life if settings.runs_second_order() => "LIFE2",
+            Automaton::Immigration if settings.shows_territory() => "TERR ",
+            Automaton::Life => "LIFE ",
+            Automaton::Wireworld => "WIRE ",
+            Automaton::BriansBrain => "BRAIN",
+            Automaton::Ant => "ANT  ",
+            Automaton::Elementary => "1D   ",
+            Automaton::LargerThanLife => "LTL  ",
+            Automaton::Margolus => "BLOCK",
+            Automaton::Immigration => "IMMIG",
+            Automaton::Layers => "LAYER",
+            Automaton::LatticeGas => "GAS  ",
+            Automaton::ForestFire => "FIRE ",
+            Automaton::Anneal => "VOTE ",
+            Automaton::Species => "RPS  ",
+            Automaton::WaTor => "WATOR",
+            Automaton::Sandpile => "SAND ",
+            Automaton::Table => "TABLE",
+        });
+    // The block rule in place of the elementary one
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+        settings.window_y+settings.rules_offset_y+13,
+        "    ");
+    if settings.automaton == Automaton::Margolus {
+        draw_text(bg, vram,
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+            settings.window_y+settings.rules_offset_y+13,
+            BLOCK_RULES[settings.block_rule].0);
+    } else if settings.automaton == Automaton::ForestFire {
+        draw_text(bg, vram,
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+            settings.window_y+settings.rules_offset_y+13,
+            "FIRE");
+    } else if settings.automaton == Automaton::Anneal {
+        draw_text(bg, vram,
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+            settings.window_y+settings.rules_offset_y+13,
+            &format!("{}.{}", settings.temperature / 10, settings.temperature % 10));
+    } else if settings.automaton == Automaton::Species {
+        draw_text(bg, vram,
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+            settings.window_y+settings.rules_offset_y+13,
+            &format!("{}+", settings.threshold));
+    } else if settings.automaton == Automaton::WaTor {
+        draw_text(bg, vram,
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+            settings.window_y+settings.rules_offset_y+13,
+            "SEA");
+    } else if settings.automaton == Automaton::Table {
+        draw_text(bg, vram,
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+            settings.window_y+settings.rules_offset_y+13,
+            "TBL");
+    } else if settings.automaton == Automaton::Life {
+        draw_text(bg, vram,
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+            settings.window_y+settings.rules_offset_y+13,
+            if settings.weights == EVEN_WEIGHTS { "EVEN" } else { "WTD" });
+    } else {
+        draw_number(bg, vram,
+            settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+            settings.window_y+settings.rules_offset_y+13,
+            3, settings.wolfram as usize);
+    }
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x+8,
+        settings.window_y+settings.rules_offset_y+14,
+        if settings.highway_pause { "ON " } else { "OFF" });
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+5,
+        settings.window_y+settings.rules_offset_y+15,
+        if settings.ramp { "ON " } else { "OFF" });
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+9,
+        settings.window_y+settings.rules_offset_y+15,
+        if settings.pacing == Pacing::Frames { "VB" } else { "  " });
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x+7,
+        settings.window_y+settings.rules_offset_y+15,
+        if settings.kiosk { "ON " } else { "OFF" });
+    draw_text(bg, vram,
+        settings.window_x+settings.rules_offset_x+settings.column_offset_x+1,
+        settings.window_y+settings.rules_offset_y+1,
+        &format!("{:<10}", rulestring::PRESETS[settings.preset].0));
+}
 
-    window_x: u16,
-    window_y: u16,
-    window_width: u16,
-    window_height: u16,
-    rules_offset_x: u16,
-    rules_offset_y: u16,
+// The largest block the allocator can hand out, halving the gap between a
+// size that fits and one that doesn't
+fn largest_free_block() -> usize {
+    let (mut fits, mut too_big) = (0, EWRAM_SIZE + 1);
+    while too_big - fits > 1 {
+        let size = (fits + too_big) / 2;
+        if Vec::<u8>::new().try_reserve_exact(size).is_ok() {
+            fits = size;
+        } else {
+            too_big = size;
+        }
+    }
+    fits
 }
 
+// The heap left, as the largest blocks taken one after another until only
+// scraps are left. Each is held until the count is done so none is found
+// twice.
+fn free_heap() -> usize {
+    let mut taken: Vec<Vec<u8>> = Vec::with_capacity(FREE_PROBES);
+    let mut free = 0;
+    while taken.len() < FREE_PROBES {
+        let size = largest_free_block();
+        let mut block = Vec::new();
+        if size < FREE_SCRAP || block.try_reserve_exact(size).is_err() {
+            break;
+        }
+        free += size;
+        taken.push(block);
+    }
+    free
+}
+
+fn new_memory_report(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                     graph: &Graph, graph_settings: &Graph, history: &History,
+                     last: &Option<Context>) {
+    new_window(bg, vram,
+        settings.window_x, settings.window_y, settings.window_width, settings.window_height);
+
+    let x = settings.window_x + 1;
+    let y = settings.window_y + 1;
+    let number_x = x + 6;
+
+    // Heap allocations live in EWRAM. The free figures come from the
+    // allocator itself, and OTHER is whatever the three biggest users don't
+    // account for, the smaller ones, the statics and the allocator's own.
+    let world = graph.memory_usage();
+    let menu = graph_settings.memory_usage();
+    let hist = history.memory_usage();
+    let free = free_heap();
+    draw_text(bg, vram, x, y, "EWRAM");
+    draw_number(bg, vram, number_x, y, 6, EWRAM_SIZE);
+    draw_text(bg, vram, x+1, y+1, "WORLD");
+    draw_number(bg, vram, number_x, y+1, 6, world);
+    draw_text(bg, vram, x+1, y+2, "MENU");
+    draw_number(bg, vram, number_x, y+2, 6, menu);
+    draw_text(bg, vram, x+1, y+3, "HIST");
+    draw_number(bg, vram, number_x, y+3, 6, hist);
+    draw_text(bg, vram, x+1, y+4, "OTHER");
+    draw_number(bg, vram, number_x, y+4, 6, EWRAM_SIZE.saturating_sub(world + menu + hist + free));
+    draw_text(bg, vram, x+1, y+5, "FREE");
+    draw_number(bg, vram, number_x, y+5, 6, free);
+    draw_text(bg, vram, x+1, y+6, "BLOCK");
+    draw_number(bg, vram, number_x, y+6, 6, largest_free_block());
+    // Taken from the free heap by each generation and given back after
+    draw_text(bg, vram, x+1, y+7, "NBRS");
+    draw_number(bg, vram, number_x, y+7, 6, step_memory(graph));
+
+    // The stamp slots, the pattern clipboard, are on the stack
+    draw_text(bg, vram, x, y+9, "IWRAM");
+    draw_number(bg, vram, number_x, y+9, 6, IWRAM_SIZE);
+    draw_text(bg, vram, x+1, y+10, "STAMPS");
+    draw_number(bg, vram, number_x, y+10, 6, size_of::<[Option<Stamp>; STAMP_SLOTS]>());
+
+    // Where the previous session left off, in case it crashed
+    if let Some(last) = last {
+        draw_text(bg, vram, x, y+12, "LAST");
+        draw_text(bg, vram, x+1, y+13, &last.state);
+        draw_text(bg, vram, x+1, y+14, "GEN");
+        draw_number(bg, vram, number_x, y+14, 10, last.generation as usize);
+        draw_text(bg, vram, x+1, y+15, "SAVE");
+        draw_text(bg, vram, number_x, y+15, match last.save {
+            SaveStatus::None => "NONE",
+            SaveStatus::Saved => "OK",
+            SaveStatus::Failed => "FAILED",
+        });
+    }
+
+    draw_text(bg, vram, x, y+17, "INPUT L SAVE R REPLAY");
+}
+
+// Popup beside the cursor, flipped to the other side near the screen edges
+fn inspect_origin(graph: &Graph, node: NodeIndex) -> (u16, u16) {
+    let n = &graph.nodes[node];
+    let x = if n.x + 1 + INSPECT_WIDTH <= WIDTH { n.x + 1 } else { n.x - INSPECT_WIDTH };
+    let y = if n.y + INSPECT_HEIGHT <= HEIGHT { n.y } else { HEIGHT - INSPECT_HEIGHT };
+    (x, y)
+}
+
+fn new_cell_inspector(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                      graph: &Graph, node: NodeIndex) {
+    let (x, y) = inspect_origin(graph, node);
+    new_window(bg, vram, x, y, INSPECT_WIDTH, INSPECT_HEIGHT);
+
+    let state_name = |s: CellState| match (settings.automaton, s) {
+        (Automaton::Wireworld, LIVE) => "HEAD",
+        (Automaton::Wireworld, TAIL) => "TAIL",
+        (Automaton::Wireworld, WIRE) => "WIRE",
+        (Automaton::Wireworld, _) => "NONE",
+        (Automaton::BriansBrain, LIVE) => "FIRE",
+        (Automaton::BriansBrain, RESTING) => "REST",
+        (Automaton::BriansBrain, _) => "OFF",
+        (Automaton::Ant, DEAD) => "DEAD",
+        (Automaton::Ant, _) => "PATH",
+        (Automaton::Immigration, LIVE) => "BLUE",
+        (Automaton::Immigration, RED) => "RED",
+        (_, LIVE) => "LIVE",
+        (_, DEAD) => "DEAD",
+        _ => "FADE",
+    };
+    let n = &graph.nodes[node];
+    let state = match n.state { Cell(s) => s, _ => DEAD };
+    let neighbors = settings.neighbors(graph, node, WIDTH, settings.boundary);
+
+    draw_text(bg, vram, x+1, y+1, state_name(state));
+    draw_text(bg, vram, x+1, y+2, "AGE");
+    draw_number(bg, vram, x+5, y+2, 5, n.age as usize);
+    draw_text(bg, vram, x+1, y+3, "NBRS");
+    draw_number(bg, vram, x+5, y+3, 5, neighbors as usize);
+    draw_text(bg, vram, x+1, y+4, "NEXT");
+    if settings.uncertain(state, neighbors) {
+        draw_text(bg, vram, x+6, y+4, "RAND");
+    } else {
+        draw_text(bg, vram, x+6, y+4, state_name(settings.next_state(state, neighbors, &mut Prng::new(DEFAULT_SEED))));
+    }
+}
+
+fn clear_cell_inspector(bg : &mut RegularMap, vram : &mut VRamManager, graph: &Graph, node: NodeIndex) {
+    let (x, y) = inspect_origin(graph, node);
+    clear_rect(bg, vram, x, y, INSPECT_WIDTH, INSPECT_HEIGHT);
+}
+
+fn clear_rect(bg : &mut RegularMap, vram : &mut VRamManager, x: u16, y: u16, width: u16, height: u16) {
+    let tileset = background_tiles::tiles.tiles;
+    for i in x..x+width {
+    for j in y..y+height {
+        bg.set_tile(vram, (i, j), &tileset, background_tiles::tiles.tile_settings[0]);
+    }}
+}
+
+fn new_event_log(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings, log: &EventLog) {
+    new_window(bg, vram, 0, settings.window_y, WIDTH, settings.window_height);
+
+    let x = 1;
+    let y = settings.window_y + 1;
+    draw_text(bg, vram, x, y, "GEN");
+    draw_text(bg, vram, x+7, y, "EVENT");
+    for (i, (generation, entry)) in log.entries().iter().enumerate() {
+        let row = y + 2 + i as u16;
+        draw_number(bg, vram, x, row, 6, *generation as usize);
+        match entry {
+            LogEntry::Rule(rules, states) => draw_text(bg, vram, x+7, row, &rulestring::to_string(rules, *states)),
+            LogEntry::Velocity(v) => draw_text(bg, vram, x+7, row, &v.describe()),
+            LogEntry::Lifespan(lifespan, population) =>
+                draw_text(bg, vram, x+7, row, &format!("LIFE {} POP {}", lifespan, population)),
+            LogEntry::Highway(period) => draw_text(bg, vram, x+7, row, &format!("HIGHWAY PERIOD {}", period)),
+        }
+    }
+}
+
+fn new_marker_list(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                   graph: &Graph, markers: &[Option<NodeIndex>], selection: usize) {
+    new_window(bg, vram,
+        settings.window_x, settings.window_y, settings.window_width, settings.window_height);
+
+    let x = settings.window_x + 1;
+    let y = settings.window_y + 1;
+    draw_text(bg, vram, x+1, y, "MARKS");
+    for (i, marker) in markers.iter().enumerate() {
+        let row = y + 2 + i as u16;
+        draw_text(bg, vram, x, row, if i == selection { ">" } else { " " });
+        draw_number(bg, vram, x+1, row, 1, i + 1);
+        match marker {
+            Some(n) => {
+                draw_text(bg, vram, x+3, row, "X");
+                draw_number(bg, vram, x+4, row, 3, graph.nodes[*n].x as usize);
+                draw_text(bg, vram, x+8, row, "Y");
+                draw_number(bg, vram, x+9, row, 3, graph.nodes[*n].y as usize);
+            },
+            None => draw_text(bg, vram, x+3, row, "-"),
+        }
+    }
+}
+
+// Debug view of raw SRAM, one row of bytes per line after its address
+fn new_sram_view(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                 offset: usize, bytes: &[u8]) {
+    new_window(bg, vram, 0, settings.window_y, WIDTH, settings.window_height);
+
+    let x = 1;
+    let y = settings.window_y + 1;
+    draw_text(bg, vram, x, y, "SRAM");
+    for (i, row) in bytes.chunks(SRAM_ROW_BYTES).enumerate() {
+        let mut line = format!("{:04X}", offset + i * SRAM_ROW_BYTES);
+        for b in row {
+            line.push_str(&format!(" {:02X}", b));
+        }
+        draw_text(bg, vram, x, y + 2 + i as u16, &line);
+    }
+}
+
+fn read_sram(save: &mut SaveManager, offset: usize) -> Result<[u8; SRAM_ROW_BYTES * SRAM_PAGE_ROWS], Error> {
+    let mut access = save.access()?;
+    let mut bytes = [0u8; SRAM_ROW_BYTES * SRAM_PAGE_ROWS];
+    access.read(offset, &mut bytes)?;
+    Ok(bytes)
+}
+
+fn new_tools_page(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                  selection: usize) {
+    new_window(bg, vram,
+        settings.window_x, settings.window_y, settings.window_width, settings.window_height);
+
+    let x = settings.window_x + 1;
+    let y = settings.window_y + 1;
+    draw_text(bg, vram, x+1, y, "TOOLS");
+    for (i, name) in TOOLS.iter().enumerate() {
+        let row = y + 2 + i as u16;
+        draw_text(bg, vram, x, row, if i == selection { ">" } else { " " });
+        draw_text(bg, vram, x+1, row, name);
+    }
+}
+
+// ROM patterns followed by the stamp slots, or the circuit components when
+// drawing Wireworld
+fn new_pattern_browser(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                       stamps: &[Option<Stamp>], selection: usize) {
+    new_window(bg, vram,
+        settings.window_x, settings.window_y, settings.window_width, settings.window_height);
+
+    let x = settings.window_x + 1;
+    let y = settings.window_y + 1;
+    if settings.automaton == Automaton::Wireworld {
+        draw_text(bg, vram, x+1, y, "CIRCUITS");
+        for (i, (name, c)) in CIRCUITS.iter().enumerate() {
+            let row = y + 2 + i as u16;
+            draw_text(bg, vram, x, row, if i == selection { ">" } else { " " });
+            draw_text(bg, vram, x+1, row, name);
+            draw_number(bg, vram, x+9, row, 2, c.width as usize);
+            draw_text(bg, vram, x+11, row, "X");
+            draw_number(bg, vram, x+12, row, 2, c.height as usize);
+        }
+        return;
+    }
+
+    draw_text(bg, vram, x+1, y, "PATTERNS");
+    let sizes = PATTERNS.iter()
+        .map(|(_, p)| Some((p.width, p.height)))
+        .chain(stamps.iter().map(|s| s.map(|s| (s.width, s.height))));
+    for (i, size) in sizes.enumerate() {
+        let row = y + 2 + i as u16;
+        draw_text(bg, vram, x, row, if i == selection { ">" } else { " " });
+        if i < PATTERNS.len() {
+            draw_text(bg, vram, x+1, row, PATTERNS[i].0);
+        } else {
+            draw_text(bg, vram, x+1, row, "SLOT");
+            draw_number(bg, vram, x+6, row, 1, i - PATTERNS.len() + 1);
+        }
+        match size {
+            Some((w, h)) => {
+                draw_number(bg, vram, x+9, row, 2, w as usize);
+                draw_text(bg, vram, x+11, row, "X");
+                draw_number(bg, vram, x+12, row, 2, h as usize);
+            },
+            None => draw_text(bg, vram, x+9, row, "   -   "),
+        }
+    }
+}
+
+// The code of a pattern, editable one character at a time
+fn new_share_code(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                  code: &[u8], position: usize, status: &str) {
+    new_window(bg, vram,
+        settings.window_x, settings.window_y, settings.window_width, settings.window_height);
+
+    let x = settings.window_x + 2;
+    let y = settings.window_y + 1;
+    draw_text(bg, vram, x, y, "CODE");
+    for (i, digit) in code[..sharecode::length(code)].iter().enumerate() {
+        let column = x + (i % CODE_ROW_LENGTH) as u16;
+        let row = y + 2 + 2 * (i / CODE_ROW_LENGTH) as u16;
+        draw_text(bg, vram, column, row, &String::from(sharecode::to_char(*digit)));
+        if i == position {
+            draw_text(bg, vram, column, row+1, "-");
+        }
+    }
+    draw_text(bg, vram, x, y+9, status);
+}
+
+fn new_controls_page(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                     selection: usize) {
+    new_window(bg, vram,
+        settings.window_x, settings.window_y, settings.window_width, settings.window_height);
+
+    let x = settings.window_x + 2;
+    let y = settings.window_y + 1;
+    draw_text(bg, vram, x, y, "CONTROLS");
+    let values = [
+        settings.board_repeat.delay,
+        settings.board_repeat.interval,
+        settings.menu_repeat.delay,
+        settings.menu_repeat.interval,
+        settings.turbo_every,
+        settings.echo,
+        settings.see_through,
+        settings.adapt,
+        settings.noise_cells,
+        settings.noise_every,
+    ];
+    for (i, (name, value)) in CONTROL_ROWS.iter().zip(values).enumerate() {
+        let row = y + 2 + i as u16;
+        draw_text(bg, vram, x, row, name);
+        draw_spinner(bg, vram, x+12, row, 2, value as usize, i == selection);
+    }
+}
+
+// Bar chart of the most recent population samples
+fn new_stats_page(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                  graph: &Graph, samples: &[(u32, u16)], selection: usize) {
+    let tileset = background_tiles::tiles.tiles;
+    new_window(bg, vram,
+        settings.window_x, settings.window_y, settings.window_width, settings.window_height);
+
+    let x = settings.window_x + 2;
+    let y = settings.window_y + 1;
+    draw_text(bg, vram, x, y, "POPULATION");
+
+    let columns = (settings.window_width - 4) as usize;
+    let recent = &samples[samples.len().saturating_sub(columns)..];
+    let max = recent.iter().map(|(_, p)| *p).max().unwrap_or(0).max(1) as u32;
+    for (i, (_, population)) in recent.iter().enumerate() {
+        let height = (*population as u32 * STATS_ROWS as u32).div_ceil(max) as u16;
+        for j in 0..height {
+            bg.set_tile(
+                vram,
+                (x + i as u16, y + 2 + STATS_ROWS - 1 - j),
+                &tileset,
+                background_tiles::tiles.tile_settings[settings.tiles[LIVE.index()] as usize],
+            );
+        }
+    }
+
+    match recent.last() {
+        Some((generation, population)) => {
+            draw_text(bg, vram, x, y+3+STATS_ROWS, "GEN");
+            draw_number(bg, vram, x+4, y+3+STATS_ROWS, 10, *generation as usize);
+            draw_text(bg, vram, x, y+4+STATS_ROWS, "POP");
+            draw_number(bg, vram, x+4, y+4+STATS_ROWS, 10, *population as usize);
+        },
+        None => draw_text(bg, vram, x, y+3+STATS_ROWS, "NO SAMPLES"),
+    }
+    draw_text(bg, vram, x, y+5+STATS_ROWS, "HASH");
+    draw_text(bg, vram, x+6, y+5+STATS_ROWS,
+        &format!("{:08X}", board_hash(graph, &settings.rules)));
+    draw_alarms(bg, vram, settings, selection);
+}
+
+// Population alarms under the chart, 0 showing as off
+fn draw_alarms(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+               selection: usize) {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + 7 + STATS_ROWS;
+    let alarms = &settings.alarms;
+    for (i, name) in ["ABOVE", "BELOW", "AT ZERO", "ALARM"].iter().enumerate() {
+        let row = y + i as u16;
+        draw_text(bg, vram, x-1, row, if i == selection { ">" } else { " " });
+        draw_text(bg, vram, x, row, name);
+    }
+    for (i, limit) in [alarms.above, alarms.below].iter().enumerate() {
+        match limit {
+            0 => draw_text(bg, vram, x+8, y + i as u16, "OFF"),
+            _ => draw_number(bg, vram, x+8, y + i as u16, 3, *limit as usize),
+        }
+    }
+    draw_text(bg, vram, x+8, y+2, if alarms.zero { "ON " } else { "OFF" });
+    draw_text(bg, vram, x+8, y+3, alarms.action.name());
+}
+
+// Number with arrows either side when it is the field being edited
+fn draw_spinner(bg : &mut RegularMap, vram : &mut VRamManager, x: u16, y: u16,
+                width: u16, value: usize, selected: bool) {
+    draw_text(bg, vram, x, y, if selected { "<" } else { " " });
+    draw_number(bg, vram, x+1, y, width, value);
+    draw_text(bg, vram, x+1+width, y, if selected { ">" } else { " " });
+}
+
+fn new_wolfram_dialog(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings) {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + settings.rules_offset_y + 3;
+    new_window(bg, vram, x, y, 9, 4);
+    draw_text(bg, vram, x+1, y+1, "RULE");
+    draw_spinner(bg, vram, x+1, y+2, 3, settings.wolfram as usize, true);
+}
+
+// A chance as a decimal to four places
+fn draw_chance(bg : &mut RegularMap, vram : &mut VRamManager, x: u16, y: u16,
+               chance: Chance, selected: bool) {
+    let places = ((chance.to_raw() as u64 * 10000 + (1 << 15)) >> 16) as usize;
+    draw_text(bg, vram, x, y, if selected { "<" } else { " " });
+    draw_text(bg, vram, x+1, y, &format!("{}.{:04}", places / 10000, places % 10000));
+    draw_text(bg, vram, x+7, y, if selected { ">" } else { " " });
+}
+
+fn new_fire_dialog(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                   field: usize) {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + settings.rules_offset_y + 3;
+    new_window(bg, vram, x, y, 20, 4);
+    draw_text(bg, vram, x+1, y+1, "GROWTH");
+    draw_chance(bg, vram, x+11, y+1, settings.growth, field == 0);
+    draw_text(bg, vram, x+1, y+2, "LIGHTNING");
+    draw_chance(bg, vram, x+11, y+2, settings.lightning, field == 1);
+}
+
+fn new_wator_dialog(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                    field: usize) {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + settings.rules_offset_y + 3;
+    let rules = settings.wator;
+    new_window(bg, vram, x, y, 18, 5);
+    draw_text(bg, vram, x+1, y+1, "FISH BREED");
+    draw_spinner(bg, vram, x+13, y+1, 2, rules.fish_breed as usize, field == 0);
+    draw_text(bg, vram, x+1, y+2, "SHARK BREED");
+    draw_spinner(bg, vram, x+13, y+2, 2, rules.shark_breed as usize, field == 1);
+    draw_text(bg, vram, x+1, y+3, "STARVE");
+    draw_spinner(bg, vram, x+13, y+3, 2, rules.starve as usize, field == 2);
+}
+
+fn new_temperature_dialog(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings) {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + settings.rules_offset_y + 3;
+    new_window(bg, vram, x, y, 13, 5);
+    draw_text(bg, vram, x+1, y+1, "TEMPERATURE");
+    draw_text(bg, vram, x+1, y+2,
+        &format!("<{}.{}>", settings.temperature / 10, settings.temperature % 10));
+    draw_text(bg, vram, x+1, y+3, "TWIST");
+    draw_spinner(bg, vram, x+7, y+3, 1, settings.twist as usize, true);
+}
+
+// The weights in their places around the cell, the one being changed
+// between arrows
+fn new_weights_dialog(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                      place: usize) {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + settings.rules_offset_y + 3;
+    new_window(bg, vram, x, y, 11, 6);
+    draw_text(bg, vram, x+1, y+1, "WEIGHTS");
+    for (i, weight) in settings.weights.iter().enumerate() {
+        draw_spinner(bg, vram, x+1 + 3 * (i % 3) as u16, y+2 + (i / 3) as u16, 1,
+                     *weight as usize, i == place);
+    }
+}
+
+// A row for each state in use, and a column for each count of live neighbors
+fn new_table_dialog(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                    place: (usize, usize)) {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + settings.rules_offset_y + 3;
+    let states = settings.states as usize;
+    new_window(bg, vram, x, y, 22, states as u16 + 4);
+    draw_text(bg, vram, x+1, y+1, "TABLE");
+    for count in 0..9 {
+        draw_number(bg, vram, x+3 + 2 * count as u16, y+2, 1, count);
+    }
+    for state in 0..states {
+        draw_number(bg, vram, x+1, y+3 + state as u16, 1, state);
+        for count in 0..9 {
+            let next = table::next(&settings.table, settings.states, state, count);
+            draw_number(bg, vram, x+3 + 2 * count as u16, y+3 + state as u16, 1, next as usize);
+        }
+    }
+    let (state, count) = place;
+    draw_text(bg, vram, x+2 + 2 * count as u16, y+3 + state as u16, "<");
+    draw_text(bg, vram, x+4 + 2 * count as u16, y+3 + state as u16, ">");
+}
+
+fn new_range_dialog(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                    row: usize, field: usize) {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + settings.rules_offset_y + 3;
+    let [low, high] = settings.ranges[row];
+    new_window(bg, vram, x, y, 11, 4);
+    draw_text(bg, vram, x+1, y+1, if row == 0 { "BIRTH" } else { "SURVIVAL" });
+    draw_spinner(bg, vram, x+1, y+2, 2, low as usize, field == 0);
+    draw_text(bg, vram, x+5, y+2, "-");
+    draw_spinner(bg, vram, x+6, y+2, 2, high as usize, field == 1);
+}
+
+fn new_until_dialog(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                    goal: Goal, target: u32, every: u16, field: usize) {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + settings.rules_offset_y + 3;
+    new_window(bg, vram, x, y, 17, 6);
+    draw_text(bg, vram, x+1, y+1, "RUN UNTIL");
+    draw_text(bg, vram, x+1, y+2, "GOAL");
+    draw_text(bg, vram, x+8, y+2, if field == 0 { "<" } else { " " });
+    draw_text(bg, vram, x+9, y+2, goal.name());
+    draw_text(bg, vram, x+15, y+2, if field == 0 { ">" } else { " " });
+    draw_text(bg, vram, x+1, y+3, "TARGET");
+    if goal == Goal::Stable {
+        draw_text(bg, vram, x+8, y+3, "   -    ");
+    } else {
+        draw_spinner(bg, vram, x+8, y+3, 6, target as usize, field == 1);
+    }
+    draw_text(bg, vram, x+1, y+4, "DRAW 1 IN");
+    draw_spinner(bg, vram, x+11, y+4, 2, every as usize, field == 2);
+}
+
+fn new_resume_prompt(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                     generation: u32) {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + settings.rules_offset_y + 3;
+    new_window(bg, vram, x, y, 18, 5);
+    draw_text(bg, vram, x+1, y+1, "CONTINUE");
+    draw_text(bg, vram, x+1, y+2, &format!("FROM GEN {}", generation));
+    draw_text(bg, vram, x+1, y+3, "A YES  B NEW");
+}
+
+fn new_tribute_card(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings) {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + settings.rules_offset_y + 3;
+    new_window(bg, vram, x, y, 20, 5);
+    for (i, line) in TRIBUTE_CARD.iter().enumerate() {
+        draw_text(bg, vram, x + 1 + (18 - line.len() as u16) / 2, y + 1 + i as u16, line);
+    }
+}
+
+fn new_goto_dialog(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings,
+                   target: (u16, u16), field: usize) {
+    let x = settings.window_x + 2;
+    let y = settings.window_y + settings.rules_offset_y + 3;
+    new_window(bg, vram, x, y, 9, 5);
+    draw_text(bg, vram, x+1, y+1, "GOTO");
+    draw_text(bg, vram, x+1, y+2, "X");
+    draw_spinner(bg, vram, x+3, y+2, 3, target.0 as usize, field == 0);
+    draw_text(bg, vram, x+1, y+3, "Y");
+    draw_spinner(bg, vram, x+3, y+3, 3, target.1 as usize, field == 1);
+}
+
+// One line message along the bottom of the screen on the overlay
+fn show_toast(bg : &mut RegularMap, vram : &mut VRamManager, text: &str) {
+    let width = text.len() as u16 + 2;
+    let x = (WIDTH - width) / 2;
+    clear_rect(bg, vram, 0, HEIGHT-3, WIDTH, 3);
+    new_window(bg, vram, x, HEIGHT-3, width, 3);
+    draw_text(bg, vram, x+1, HEIGHT-2, text);
+}
+
+fn hide_toast(bg : &mut RegularMap, vram : &mut VRamManager, settings: &Settings) {
+    clear_rect(bg, vram, 0, HEIGHT-3, WIDTH, 3);
+    draw_world_edge(bg, vram, settings);
+}
+
+fn redraw_markers(objects: &mut [Object], markers: &[Option<NodeIndex>], graph: &Graph) {
+    for (object, marker) in objects.iter_mut().zip(markers) {
+        match marker {
+            Some(n) => {
+                object.set_x(graph.nodes[*n].x * TILE_SIZE)
+                      .set_y(graph.nodes[*n].y * TILE_SIZE)
+                      .show();
+            },
+            None => {
+                object.hide();
+            }
+        }
+    }
+}
+
+struct Settings {
+    rules: [[u16;9];2],
+    // Lowest and highest counts for birth and survival in Larger than Life
+    ranges: [[u8;2];2],
+    speed: u16,
+    automaton: Automaton,
+    // What the ants do in the ant mode
+    turmite: Turmite,
+    // Elementary rule number, bit n giving the next state for neighborhood n
+    wolfram: u8,
+    // Which of the block rules the Margolus mode runs
+    block_rule: usize,
+    // Chances of a tree growing on empty ground and of lightning striking one
+    growth: Chance,
+    lightning: Chance,
+    // How often voting cells go against the vote, in tenths
+    temperature: u16,
+    // Votes won by less than twice this go the other way, Vichniac's
+    // twisted majority being a twist of one
+    twist: u16,
+    // Neighbors of the species that beats a cell it takes to be taken over
+    threshold: u16,
+    wator: wator::Rules,
+    // How much a live cell counts for in each place around a Life cell
+    weights: [u8; 9],
+    table: table::Table,
+    // Rule preset shown beside the rules, written to them with A
+    preset: usize,
+    // Life with each next state flipped where the cell was live the
+    // generation before, which makes any rule reversible
+    second_order: bool,
+    // Dead cells drawn in the color that last held them, in two-color modes
+    territory: bool,
+    // Whether cells are updated all at once or one at a time, and in which order
+    update_order: UpdateOrder,
+    // Number of cell states in use and the tile drawn for each
+    states: u8,
+    tiles: [u16;MAX_STATES],
+    // Palettes the decaying states are cycled through, none when there are
+    // too few spare, and the phase they have reached
+    phases: u8,
+    rotation: u8,
+    history_depth: u16,
+    boundary: Boundary,
+    neighborhood: Neighborhood,
+    follow: bool,
+    // With dead edges, the board moved to center its live cells whenever
+    // they come near an edge, so growing patterns seem to be on an open plane
+    plane: bool,
+    // Holding A sets cells to the state the first one was given
+    paint: bool,
+    // Stop running once an ant builds a highway
+    highway_pause: bool,
+    // Runs started with B speed up gradually
+    ramp: bool,
+    // What generations are timed against
+    pacing: Pacing,
+    // Shows run by themselves with the menu and saving locked away
+    kiosk: bool,
+    // Generations between population samples kept in SRAM, 0 for none
+    sample_interval: u16,
+    lifespan: u16,
+    alarms: Alarms,
+    // Generations between boards drawn while R is held to run flat out
+    turbo_every: u16,
+    // Pixels down and right a dimmed copy of the board is drawn, 0 for none
+    echo: u16,
+    // Sixteenths of the board shown through the menu windows, 0 for none
+    see_through: u16,
+    // How much busy generations slow down and quiet ones speed up, none
+    // for the set speed all along
+    adapt: u16,
+    // Random cells brought to life every so many generations while running
+    noise_cells: u16,
+    noise_every: u16,
+
+    window_x: u16,
+    window_y: u16,
+    window_width: u16,
+    window_height: u16,
+    rules_offset_x: u16,
+    rules_offset_y: u16,
+    column_offset_x: u16,
+
+    board_repeat: RepeatProfile,
+    menu_repeat: RepeatProfile,
+}
+
+impl Settings {
+    fn new() -> Self {
+        Settings {
+            rules: [[0,0,0,1,0,0,0,0,0]
+                   ,[0,0,1,1,0,0,0,0,0]],
+            ranges: [[6,7],[5,8]],
+            speed: 5000,
+            automaton: Automaton::Life,
+            turmite: LANGTONS_ANT,
+            wolfram: 30,
+            block_rule: 0,
+            growth: Chance::from_raw(GROWTH),
+            lightning: Chance::from_raw(LIGHTNING),
+            temperature: 5,
+            twist: 0,
+            threshold: 3,
+            wator: wator::RULES,
+            weights: EVEN_WEIGHTS,
+            table: table::DEFAULT,
+            preset: 0,
+            second_order: false,
+            territory: false,
+            update_order: UpdateOrder::Synchronous,
+            states: 2,
+            tiles: [1,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2],
+            phases: 0,
+            rotation: 0,
+            history_depth: 32,
+            boundary: Boundary::Toroidal,
+            neighborhood: Neighborhood::Moore,
+            follow: false,
+            plane: false,
+            paint: false,
+            highway_pause: false,
+            ramp: false,
+            pacing: Pacing::Timer,
+            kiosk: false,
+            sample_interval: 0,
+            lifespan: 0,
+            alarms: Alarms::new(),
+            turbo_every: 8,
+            echo: 0,
+            see_through: 0,
+            adapt: 0,
+            noise_cells: 0,
+            noise_every: 10,
+
+            window_x: WIDTH/4-4,
+            window_y: 0,
+            window_width: WIDTH/2+12,
+            window_height: HEIGHT/2+10,
+            rules_offset_x: 3,
+            rules_offset_y: 3,
+            column_offset_x: 11,
+
+            board_repeat: RepeatProfile { delay: 12, interval: 4 },
+            menu_repeat: RepeatProfile { delay: 20, interval: 6 },
+        }
+    }
+
+    // Decaying states are spread over the shade tiles, oldest darkest
+    fn state_tiles(&mut self) {
+        // Hex cells have their own dead and live tiles
+        let (dead, live) = match self.neighborhood {
+            Neighborhood::Moore | Neighborhood::VonNeumann => (DEAD_TILE, LIVE_TILE),
+            Neighborhood::Hex => (HEX_DEAD_TILE, HEX_LIVE_TILE),
+        };
+        self.tiles[DEAD.index()] = dead;
+        self.tiles[LIVE.index()] = live;
+        self.phases = 0;
+        match self.automaton {
+            Automaton::Life => {
+                let decaying = self.states.saturating_sub(2) as usize;
+                for k in 0..decaying {
+                    self.tiles[2 + k] = DECAY_TILES[k * DECAY_TILES.len() / decaying];
+                }
+                let phases = cycle::phases();
+                if decaying > 0 && decaying <= phases {
+                    self.phases = phases as u8;
+                }
+            },
+            Automaton::Wireworld => {
+                self.tiles[TAIL.index()] = TAIL_TILE;
+                self.tiles[WIRE.index()] = WIRE_TILE;
+            },
+            Automaton::BriansBrain => self.tiles[RESTING.index()] = DECAY_TILES[0],
+            Automaton::Ant => self.tiles[2..=ANT_COUNT].copy_from_slice(&ANT_TILES),
+            Automaton::Immigration => self.tiles[RED.index()] = TAIL_TILE,
+            Automaton::ForestFire => {
+                self.tiles[LIVE.index()] = TREE_TILE;
+                self.tiles[BURNING.index()] = WIRE_TILE;
+            },
+            Automaton::WaTor => self.tiles[SHARK.index()] = TAIL_TILE,
+            // Shaded by state, with no palettes to cycle
+            Automaton::Table => {
+                let fading = self.states.saturating_sub(2) as usize;
+                for k in 0..fading {
+                    self.tiles[2 + k] = DECAY_TILES[k * DECAY_TILES.len() / fading];
+                }
+            },
+            // Brighter the more grains, and lit up when full
+            Automaton::Sandpile => {
+                for grains in 1..MAX_STATES {
+                    self.tiles[grains] = match grains {
+                        1..=3 => DECAY_TILES[3 - grains],
+                        _ => WIRE_TILE,
+                    };
+                }
+            },
+            Automaton::Species => {
+                self.tiles[PAPER.index()] = TAIL_TILE;
+                self.tiles[SCISSORS.index()] = TREE_TILE;
+            },
+            Automaton::Elementary | Automaton::LargerThanLife | Automaton::Margolus
+                | Automaton::Layers | Automaton::LatticeGas | Automaton::Anneal => (),
+        }
+    }
+
+    // Only with nothing but live and dead cells
+    fn runs_second_order(&self) -> bool {
+        self.second_order && self.automaton == Automaton::Life && self.states == 2
+    }
+
+    fn tile(&self, state: CellState) -> TileSetting {
+        match state.index() {
+            s if s > 0 && self.automaton == Automaton::LatticeGas => {
+                let (shape, hflip, vflip) = gas::shape(state.into());
+                background_tiles::tiles.tile_settings[GAS_TILES[shape] as usize].hflip(hflip).vflip(vflip)
+            },
+            s if s >= 2 && self.phases > 0 => background_tiles::tiles.tile_settings[DECAY_TILES[0] as usize]
+                .palette(cycle::palette(self.rotation, self.phases, s - 2)),
+            s => background_tiles::tiles.tile_settings[self.tiles[s] as usize],
+        }
+    }
+
+    // Dead ground is tinted by the color that last held it on a territory map
+    fn node_tile(&self, n: &NodeData) -> TileSetting {
+        match n.state {
+            Wall => return background_tiles::tiles.tile_settings[WALL_TILE as usize],
+            Emitter => return background_tiles::tiles.tile_settings[EMITTER_TILE as usize],
+            _ => (),
+        }
+        let state = match n.state { Cell(s) => s, _ => DEAD };
+        match cycle::TERRITORY_PALETTES.get((n.owner as usize).wrapping_sub(1)) {
+            Some(palette) if state == DEAD && self.shows_territory() =>
+                background_tiles::tiles.tile_settings[DECAY_TILES[0] as usize].palette(*palette),
+            _ => self.tile(state),
+        }
+    }
+
+    // Modes going by counts of live neighbors, which walls stay out of and
+    // emitters are always in
+    fn walls(&self) -> bool {
+        matches!(self.automaton, Automaton::Life | Automaton::BriansBrain | Automaton::LargerThanLife
+                 | Automaton::Layers | Automaton::Anneal | Automaton::Table)
+    }
+
+    fn shows_territory(&self) -> bool {
+        self.territory && self.automaton == Automaton::Immigration
+    }
+
+    // Decaying cells age a shade without being drawn again
+    fn cycle_palettes(&mut self, vram: &mut VRamManager) {
+        if self.phases > 0 {
+            self.rotation = (self.rotation + 1) % self.phases;
+            self.set_palettes(vram);
+        }
+    }
+
+    fn set_palettes(&self, vram: &mut VRamManager) {
+        if self.phases > 0 {
+            cycle::set_palettes(vram, self.rotation, self.phases, self.states as usize - 2);
+        }
+        if self.shows_territory() {
+            cycle::set_territory_palettes(vram);
+        }
+    }
+
+    fn set_automaton(&mut self, automaton: Automaton) {
+        self.automaton = automaton;
+        self.states = match automaton {
+            Automaton::Life => 2,
+            Automaton::Wireworld => 4,
+            Automaton::BriansBrain => 3,
+            // Dead and one paint for each ant
+            Automaton::Ant => ANT_COUNT as u8 + 1,
+            Automaton::Elementary => 2,
+            Automaton::LargerThanLife => 2,
+            Automaton::Margolus => 2,
+            // Dead and the two colors
+            Automaton::Immigration => 3,
+            Automaton::Layers => 2,
+            // Any of the four particles
+            Automaton::LatticeGas => MAX_STATES as u8,
+            // Empty, tree and burning
+            Automaton::ForestFire => 3,
+            Automaton::Anneal => 2,
+            // Empty and the three species
+            Automaton::Species => 4,
+            // Water, fish and sharks
+            Automaton::WaTor => 3,
+            Automaton::Sandpile => MAX_STATES as u8,
+            // Life with a state to fade through
+            Automaton::Table => 3,
+        };
+        self.state_tiles();
+    }
+
+    // The count the States item goes to next, round to two after the most
+    fn next_states(&self) -> u8 {
+        let most = if self.automaton == Automaton::Table { table::MAX } else { MAX_STATES };
+        if (self.states as usize) < most { self.states + 1 } else { 2 }
+    }
+
+    // Whether the next state of a Life-like or voting cell is left to chance
+    fn uncertain(&self, state: CellState, neighbors: u16) -> bool {
+        matches!(self.automaton, Automaton::Life)
+            && matches!(state, DEAD | LIVE)
+            && self.rules[state.index()][neighbors as usize] > 1
+    }
+
+    // Whether anything is left to chance, so a gravity well fits in
+    fn stochastic(&self) -> bool {
+        match self.automaton {
+            Automaton::ForestFire => true,
+            Automaton::Anneal => self.temperature > 0,
+            Automaton::Life | Automaton::Immigration | Automaton::Layers =>
+                self.rules.iter().flatten().any(|entry| *entry > 1),
+            _ => false,
+        }
+    }
+}
+
+impl Rule for Settings {
+    type Cell = CellState;
+
+    // The generator is only drawn from for counts left to chance
+    fn next_state(&self, state: CellState, neighbors: u16, rng: &mut Prng) -> CellState {
+        // Stepped a row, an ant or a block at a time elsewhere
+        if matches!(self.automaton, Automaton::Ant | Automaton::Elementary | Automaton::Margolus
+                    | Automaton::LatticeGas | Automaton::ForestFire | Automaton::Species
+                    | Automaton::WaTor | Automaton::Sandpile | Automaton::Table) {
+            return state;
+        }
+        if self.automaton == Automaton::Wireworld {
+            return CellState(Wireworld.next_state(WireCell::from_bits(state.0), neighbors, rng).into());
+        }
+        // The cell's own state counts as a vote, so there are no ties
+        if self.automaton == Automaton::Anneal {
+            let votes = neighbors + (state == LIVE) as u16;
+            let voters = self.neighborhood.size() + 1;
+            let margin = (2 * votes).abs_diff(voters);
+            let won = (2 * votes > voters) != (margin < 2 * self.twist);
+            let against = by_chance(anneal::against(margin, self.temperature), rng);
+            return if won != against { LIVE } else { DEAD };
+        }
+        if self.automaton == Automaton::LargerThanLife {
+            let [low, high] = self.ranges[if state == LIVE { 1 } else { 0 }];
+            return if (low as u16..=high as u16).contains(&neighbors) { LIVE } else { DEAD };
+        }
+        if self.automaton == Automaton::BriansBrain {
+            return match state {
+                DEAD if neighbors == 2 => LIVE,
+                LIVE => RESTING,
+                _ => DEAD,
+            };
+        }
+        match state {
+            DEAD | LIVE => {
+                let next = match self.rules[state.index()][neighbors as usize] {
+                    chance @ 2.. => (by_chance(chance, rng) as u16).into(),
+                    certain => certain.into(),
+                };
+                // With more than two states a live cell that does not
+                // survive starts to decay rather than dying outright
+                if state == LIVE && next == DEAD && self.states > 2
+                    && self.automaton == Automaton::Life { CellState(2) } else { next }
+            },
+            // Either color counts as live for the rules and keeps its color
+            RED if self.automaton == Automaton::Immigration =>
+                if self.next_state(LIVE, neighbors, rng) == LIVE { RED } else { DEAD },
+            // Decaying cells age whatever their neighbors
+            CellState(s) => if s + 1 < self.states { CellState(s + 1) } else { DEAD },
+        }
+    }
+
+    fn neighbors<M>(&self, graph: &automata::Graph<M>, node: NodeIndex, width: u16, boundary: Boundary) -> u16 {
+        match self.automaton {
+            Automaton::LargerThanLife => range_count(graph, node, width, boundary),
+            Automaton::Immigration => graph.living_neighbors_count_of(node)
+                + graph.neighbors_in_state(node, RED),
+            // Weights can add up past the rules, which then go by the highest count
+            Automaton::Life => {
+                let itself = graph.is_live(node) as u16 * self.weights[4] as u16;
+                (graph.weighted_neighbors_count_of(node) + itself).min(8)
+            },
+            _ => graph.living_neighbors_count_of(node),
+        }
+    }
+
+    fn newborn<M>(&self, graph: &automata::Graph<M>, node: NodeIndex) -> CellState {
+        match self.automaton {
+            Automaton::Immigration => majority_color(graph, node),
+            _ => LIVE,
+        }
+    }
+
+    // A live cell that has stayed so for its whole lifespan dies whatever its
+    // neighbors, decaying first where there are states to decay through
+    fn outlived(&self, state: CellState, next: CellState, age: u16) -> CellState {
+        let live = state == LIVE || (state == RED && self.automaton == Automaton::Immigration);
+        if self.lifespan == 0 || next != state || !live || age.saturating_add(1) < self.lifespan {
+            return next;
+        }
+        if self.automaton == Automaton::Life && self.states > 2 { CellState(2) } else { DEAD }
+    }
+
+    fn order(&self) -> UpdateOrder {
+        self.update_order
+    }
+
+    // Nothing is born far from the live cells unless it is from no
+    // neighbors, or flipped by the generation before
+    fn reach(&self) -> Option<i32> {
+        let born_from_nothing = match self.automaton {
+            Automaton::Life | Automaton::Immigration | Automaton::Layers => self.rules[0][0] != 0,
+            Automaton::LargerThanLife => self.ranges[0][0] == 0,
+            Automaton::Wireworld | Automaton::BriansBrain => false,
+            _ => true,
+        };
+        if born_from_nothing || self.runs_second_order() {
+            return None;
+        }
+        Some(if self.automaton == Automaton::LargerThanLife { 2 } else { 1 })
+    }
+}
+
+fn rule_chance(entry: u16) -> u16 {
+    match entry {
+        0 | 1 => entry * CHANCE_ONE,
+        _ => entry,
+    }
+}
+
+// Comes up `chance` times out of CHANCE_ONE
+fn by_chance(chance: u16, rng: &mut Prng) -> bool {
+    rng.below(CHANCE_ONE as u32) < chance as u32
+}
+
+fn rule_entry(chance: u16) -> u16 {
+    if chance >= CHANCE_ONE { 1 } else { chance }
+}
+
+// Birth or survival row and count of a rule in the config menu, unless
+// the rules there are ranges
+fn rule_under(settings: &Settings, n: &NodeData) -> Option<(usize, usize)> {
+    (matches!(n.state, Cell(_)) && settings.automaton != Automaton::LargerThanLife)
+        .then(|| ((n.y-settings.window_y-settings.rules_offset_y) as usize,
+                  (n.x-settings.window_x-settings.rules_offset_x) as usize))
+}
+
+#[derive(Copy,Clone,PartialEq)]
 enum GameState {
     Running,
     Paused,
-    Config
+    Config,
+    Memory,
+    EventLog,
+    Lab,
+    Patterns,
+    ShareCode,
+    Controls,
+    Stats,
+    Tools,
+    SramView,
+    Turmite,
+    Wolfram,
+    Fire,
+    Temperature,
+    Weights,
+    Table,
+    WaTor,
+    Range,
+    Until,
+    TimeLapse,
+    TryRule,
+    Markers,
+    Goto,
+    Resume,
+    Tribute
+}
+
+impl GameState {
+    fn name(&self) -> &'static str {
+        match self {
+            GameState::Running => "RUNNING",
+            GameState::Paused => "PAUSED",
+            GameState::Config => "CONFIG",
+            GameState::Memory => "MEMORY",
+            GameState::EventLog => "LOG",
+            GameState::Lab => "LAB",
+            GameState::Patterns => "PATTERNS",
+            GameState::ShareCode => "SHARECODE",
+            GameState::Controls => "CONTROLS",
+            GameState::Stats => "STATS",
+            GameState::Tools => "TOOLS",
+            GameState::SramView => "SRAM",
+            GameState::Turmite => "TURMITE",
+            GameState::Wolfram => "RULE",
+            GameState::Fire => "FIRE",
+            GameState::Temperature => "TEMP",
+            GameState::Weights => "WEIGHTS",
+            GameState::Table => "TABLE",
+            GameState::WaTor => "WATOR",
+            GameState::Range => "RANGE",
+            GameState::Until => "UNTIL",
+            GameState::TimeLapse => "LAPSE",
+            GameState::TryRule => "TRY",
+            GameState::Markers => "MARKERS",
+            GameState::Goto => "GOTO",
+            GameState::Resume => "RESUME",
+            GameState::Tribute => "TRIBUTE",
+        }
+    }
+
+    // The screen B or START goes back to
+    fn parent(&self) -> Option<GameState> {
+        match self {
+            GameState::Running | GameState::Paused | GameState::Resume | GameState::Tribute => None,
+            GameState::Config => Some(GameState::Paused),
+            GameState::SramView => Some(GameState::Memory),
+            GameState::ShareCode => Some(GameState::Patterns),
+            GameState::Until | GameState::TimeLapse | GameState::TryRule => Some(GameState::Tools),
+            GameState::Memory | GameState::EventLog | GameState::Lab | GameState::Patterns
+                | GameState::Controls | GameState::Stats | GameState::Tools | GameState::Turmite
+                | GameState::Wolfram | GameState::Fire | GameState::Temperature | GameState::Weights
+                | GameState::Table | GameState::WaTor | GameState::Range | GameState::Markers
+                | GameState::Goto
+                => Some(GameState::Config),
+        }
+    }
+
+    // The screens from the menu down to this one, for those more than a
+    // screen into it
+    fn breadcrumb(&self) -> Option<String> {
+        let mut path = vec![*self];
+        while let Some(parent) = path[path.len() - 1].parent() {
+            if parent == GameState::Paused {
+                break;
+            }
+            path.push(parent);
+        }
+        (path.len() > 1).then(|| path.iter().rev().map(|s| s.name()).collect::<Vec<_>>().join(">"))
+    }
+}
+
+// A letter each for dead and live cells, walls and emitters, other states
+// being below them all
+fn node_byte(state: NodeType<MenuType>) -> u8 {
+    match state {
+        Cell(LIVE) => b'L',
+        Cell(DEAD) => b'D',
+        Cell(s) => s.into(),
+        Wall => b'W',
+        Emitter => b'E',
+        Menu(_) => b'X'
+    }
+}
+
+fn byte_node(b: u8) -> NodeType<MenuType> {
+    match b {
+        b'L' => Cell(LIVE),
+        b'W' => Wall,
+        b'E' => Emitter,
+        s if (s as usize) < MAX_STATES => Cell(CellState(s)),
+        _ => Cell(DEAD)
+    }
 }
 
-fn load_world(save: &mut SaveManager, graph: &mut Graph, settings: &mut Settings) -> Result<(),Error> {
+fn load_world(save: &mut SaveManager, at: usize, graph: &mut Graph, settings: &mut Settings,
+              markers: &mut [Option<NodeIndex>], rng: &mut Prng) -> Result<(),Error> {
     let mut access = save.access()?;
 
     let mut is_save = 0;
-    access.read(0, core::slice::from_mut(&mut is_save))?;
+    access.read(at, core::slice::from_mut(&mut is_save))?;
 
     if is_save != 0 {
         let mut b: u8 = 0;
         let mut i = 0;
         while i < graph.nodes.len() {
-            access.read(i, core::slice::from_mut(&mut b))?;
-            graph.nodes[i].state = match b {
-                b'L' => Cell(Live),
-                _ => Cell(Dead)
-            };
+            access.read(at+i, core::slice::from_mut(&mut b))?;
+            graph.nodes[i].state = byte_node(b);
+            graph.nodes[i].age = 0;
             i+=1;
         }
         let mut j = 0;
         while j < settings.rules[0].len() {
-            access.read(i+j, core::slice::from_mut(&mut b))?;
+            access.read(at+i+j, core::slice::from_mut(&mut b))?;
             settings.rules[0][j] = b.into();
             j+=1;
         }
         let mut k = 0;
         while k < settings.rules[0].len() {
-            access.read(i+j+k, core::slice::from_mut(&mut b))?;
+            access.read(at+i+j+k, core::slice::from_mut(&mut b))?;
             settings.rules[1][k] = b.into();
             k+=1;
         }
+        // Marker node indices, u16::MAX when unused
+        let mut m = 0;
+        while m < markers.len() {
+            let mut bytes = [0u8; 2];
+            access.read(at+i+j+k+2*m, &mut bytes)?;
+            let node = u16::from_le_bytes(bytes) as usize;
+            markers[m] = if node < graph.nodes.len() { Some(node) } else { None };
+            m+=1;
+        }
+        // Random number generator state, so random features replay
+        let mut bytes = [0u8; 4];
+        access.read(at+i+j+k+2*m, &mut bytes)?;
+        *rng = Prng::new(u32::from_le_bytes(bytes));
+        // Number of states after the rules, two for saves from before
+        access.read(at+i+j+k+2*m+4, core::slice::from_mut(&mut b))?;
+        settings.states = if (2..=MAX_STATES as u8).contains(&b) { b } else { 2 };
+        access.read(at+i+j+k+2*m+5, core::slice::from_mut(&mut b))?;
+        settings.automaton = match b {
+            1 => Automaton::Wireworld,
+            2 => Automaton::BriansBrain,
+            3 => Automaton::Ant,
+            4 => Automaton::Elementary,
+            5 => Automaton::LargerThanLife,
+            6 => Automaton::Margolus,
+            7 => Automaton::Immigration,
+            8 => Automaton::Layers,
+            9 => Automaton::LatticeGas,
+            10 => Automaton::ForestFire,
+            11 => Automaton::Anneal,
+            12 => Automaton::Species,
+            13 => Automaton::WaTor,
+            14 => Automaton::Sandpile,
+            15 => Automaton::Table,
+            _ => Automaton::Life,
+        };
+        settings.state_tiles();
     };
     Ok(())
 
 }
 
-fn save_world(save: &mut SaveManager, graph: &Graph, settings: &Settings) -> Result<(), Error> {
+fn save_world(save: &mut SaveManager, at: usize, graph: &Graph, settings: &Settings,
+              markers: &[Option<NodeIndex>], rng: &Prng) -> Result<(), Error> {
     let mut access = save.access()?;
 
     let mut is_save = 0;
-    access.read(0, core::slice::from_mut(&mut is_save))?;
+    access.read(at, core::slice::from_mut(&mut is_save))?;
 
     if is_save != 0 {
         let mut i = 0;
         while i < graph.nodes.len() {
-            access.prepare_write(i..i+1)?
-                  .write(i, &[
-                        match graph.nodes[i].state {
-                            Cell(Live) => b'L',
-                            Cell(Dead) => b'D',
-                            _ => b'X'
-                        }]
-                  )?;
+            access.prepare_write(at+i..at+i+1)?
+                  .write(at+i, &[node_byte(graph.nodes[i].state)])?;
             i+=1;
         }
         let mut j = 0;
         while j < settings.rules[0].len() {
-            access.prepare_write(i+j..i+j+1)?
-                  .write(i+j, &[ settings.rules[0][j] as u8 ])?;
+            access.prepare_write(at+i+j..at+i+j+1)?
+                  .write(at+i+j, &[ settings.rules[0][j] as u8 ])?;
             j+=1;
         }
         let mut k = 0;
         while k < settings.rules[1].len() {
-            access.prepare_write(i+j+k..i+j+k+1)?
-                  .write(i+j+k, &[settings.rules[1][k] as u8 ])?;
+            access.prepare_write(at+i+j+k..at+i+j+k+1)?
+                  .write(at+i+j+k, &[settings.rules[1][k] as u8 ])?;
             k+=1;
         }
+        let mut m = 0;
+        while m < markers.len() {
+            let node = markers[m].map_or(u16::MAX, |n| n as u16);
+            access.prepare_write(at+i+j+k+2*m..at+i+j+k+2*m+2)?
+                  .write(at+i+j+k+2*m, &node.to_le_bytes())?;
+            m+=1;
+        }
+        access.prepare_write(at+i+j+k+2*m..at+i+j+k+2*m+4)?
+              .write(at+i+j+k+2*m, &rng.state().to_le_bytes())?;
+        access.prepare_write(at+i+j+k+2*m+4..at+i+j+k+2*m+5)?
+              .write(at+i+j+k+2*m+4, &[settings.states])?;
+        access.prepare_write(at+i+j+k+2*m+5..at+i+j+k+2*m+6)?
+              .write(at+i+j+k+2*m+5, &[settings.automaton as u8])?;
     };
     Ok(())
 }
 
-#[agb::entry]
-fn main(mut gba: agb::Gba) -> ! {
-
-    gba.save.init_sram();
+// Counts this boot as started, returning how many before it never finished
+fn boot_started(save: &mut SaveManager) -> Result<u8, Error> {
+    let mut access = save.access()?;
+    let mut failed = 0;
+    access.read(BOOT_OFFSET, core::slice::from_mut(&mut failed))?;
+    // Fresh SRAM reads as all ones
+    if failed == u8::MAX {
+        failed = 0;
+    }
+    access.prepare_write(BOOT_OFFSET..BOOT_OFFSET+1)?
+          .write(BOOT_OFFSET, &[failed.saturating_add(1).min(u8::MAX - 1)])?;
+    Ok(failed)
+}
 
-    // Settings for Conway's Game of Life
-    let mut settings = Settings {
-            rules: [[0,0,0,1,0,0,0,0,0]
-                   ,[0,0,1,1,0,0,0,0,0]],
-            speed: 5000,
-            tiles: [1,2],
+fn boot_finished(save: &mut SaveManager) -> Result<(), Error> {
+    let mut access = save.access()?;
+    access.prepare_write(BOOT_OFFSET..BOOT_OFFSET+1)?
+          .write(BOOT_OFFSET, &[0])?;
+    Ok(())
+}
 
-            window_x: WIDTH/4,
-            window_y: HEIGHT/4-3,
-            window_width: WIDTH/2,
-            window_height: HEIGHT/2+1,
-            rules_offset_x: 3,
-            rules_offset_y: 3,
+fn population(graph: &Graph) -> u16 {
+    graph.nodes.iter()
+        .filter(|n| matches!(n.state, Cell(LIVE)))
+        .count() as u16
+}
+
+// Describes the board for when no name is given, e.g. "B3S23-G214-P56"
+fn save_name(settings: &Settings, generation: u32, graph: &Graph) -> String {
+    let population = graph.nodes.iter()
+        .filter(|n| matches!(n.state, Cell(LIVE)))
+        .count();
+    let rules: String = rulestring::to_string(&settings.rules, settings.states)
+        .chars()
+        .filter(|c| *c != '/')
+        .collect();
+    // Cut short to fit SRAM and the toast with rules that have many counts
+    let mut name = format!("{}-G{}-P{}", rules, generation, population);
+    name.truncate(SAVE_NAME_LENGTH);
+    name
+}
+
+fn write_save_name(save: &mut SaveManager, name: &str) -> Result<(), Error> {
+    let mut access = save.access()?;
+    let mut bytes = [0u8; SAVE_NAME_LENGTH];
+    for (b, c) in bytes.iter_mut().zip(name.bytes()) {
+        *b = c;
+    }
+    access.prepare_write(SAVE_NAME_OFFSET..SAVE_NAME_OFFSET + SAVE_NAME_LENGTH)?
+          .write(SAVE_NAME_OFFSET, &bytes)?;
+    Ok(())
+}
+
+fn save_session(save: &mut SaveManager, graph: &Graph, settings: &Settings,
+                markers: &[Option<NodeIndex>], rng: &Prng,
+                node: NodeIndex, generation: u32) -> Result<(), Error> {
+    save_world(save, SESSION_OFFSET + SESSION_HEADER, graph, settings, markers, rng)?;
+    let mut header = [0u8; SESSION_HEADER];
+    header[0] = SESSION_MAGIC;
+    header[1..3].copy_from_slice(&(node as u16).to_le_bytes());
+    header[3..7].copy_from_slice(&generation.to_le_bytes());
+    let mut access = save.access()?;
+    access.prepare_write(SESSION_OFFSET..SESSION_OFFSET + SESSION_HEADER)?
+          .write(SESSION_OFFSET, &header)?;
+    Ok(())
+}
+
+// The board cursor and generation of the last session, if one was kept
+fn read_session(save: &mut SaveManager) -> Result<Option<(NodeIndex, u32)>, Error> {
+    let mut access = save.access()?;
+    let mut header = [0u8; SESSION_HEADER];
+    access.read(SESSION_OFFSET, &mut header)?;
+    let node = u16::from_le_bytes([header[1], header[2]]);
+    if header[0] != SESSION_MAGIC || node >= WIDTH * HEIGHT {
+        return Ok(None);
+    }
+    Ok(Some((node as NodeIndex, u32::from_le_bytes([header[3], header[4], header[5], header[6]]))))
+}
+
+// Empty when the bytes aren't a name, as in fresh SRAM
+fn read_save_name(save: &mut SaveManager) -> Result<String, Error> {
+    let mut access = save.access()?;
+    let mut bytes = [0u8; SAVE_NAME_LENGTH];
+    access.read(SAVE_NAME_OFFSET, &mut bytes)?;
+    let name: String = bytes.iter()
+        .take_while(|b| **b != 0)
+        .map(|b| *b as char)
+        .collect();
+    if name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-') {
+        Ok(name)
+    } else {
+        Ok(String::new())
+    }
+}
+
+// Slots whose size is out of range, as in fresh SRAM, are empty
+fn load_stamps(save: &mut SaveManager) -> Result<[Option<Stamp>; STAMP_SLOTS], Error> {
+    let mut access = save.access()?;
+    let mut stamps = [None; STAMP_SLOTS];
+    for (slot, stamp) in stamps.iter_mut().enumerate() {
+        let mut bytes = [0u8; STAMP_BYTES];
+        access.read(STAMP_OFFSET + slot * STAMP_BYTES, &mut bytes)?;
+        let (width, height) = (bytes[0] as u16, bytes[1] as u16);
+        if (1..=STAMP_SIZE).contains(&width) && (1..=STAMP_SIZE).contains(&height) {
+            let mut rows = [0; STAMP_SIZE as usize];
+            for (j, row) in rows.iter_mut().enumerate() {
+                *row = u16::from_le_bytes([bytes[2 + 2*j], bytes[3 + 2*j]]);
+            }
+            *stamp = Some(Stamp { width, height, rows });
+        }
+    }
+    Ok(stamps)
+}
+
+fn save_stamp(save: &mut SaveManager, slot: usize, stamp: &Stamp) -> Result<(), Error> {
+    let mut access = save.access()?;
+    let mut bytes = [0u8; STAMP_BYTES];
+    bytes[0] = stamp.width as u8;
+    bytes[1] = stamp.height as u8;
+    for (j, row) in stamp.rows.iter().enumerate() {
+        bytes[2 + 2*j..4 + 2*j].copy_from_slice(&row.to_le_bytes());
+    }
+    let offset = STAMP_OFFSET + slot * STAMP_BYTES;
+    access.prepare_write(offset..offset + STAMP_BYTES)?
+          .write(offset, &bytes)?;
+    Ok(())
+}
+
+#[agb::entry]
+fn main(mut gba: agb::Gba) -> ! {
+    loop {
+        run(&mut gba);
+    }
+}
+
+// Everything from boot on, returned from to soft reset
+fn run(gba: &mut agb::Gba) {
+
+    gba.save.init_sram();
+
+    // After repeated failed boots leave everything in SRAM alone, so a
+    // corrupted save can't stop the game from starting
+    let safe_mode = boot_started(&mut gba.save).unwrap_or(0) >= SAFE_MODE_BOOTS;
+    let last_context = context::read(&mut gba.save).unwrap_or(None);
+    let mut save_status = SaveStatus::None;
+    let mut context_state = GameState::Paused;
+    let mut crumb_state = GameState::Paused;
+    let mut context_generation = 0;
+    let mut stamps = if safe_mode {
+        [None; STAMP_SLOTS]
+    } else {
+        load_stamps(&mut gba.save).unwrap_or([None; STAMP_SLOTS])
     };
+    let mut pattern_selection = 0;
+    let mut code: Vec<u8> = vec![0; sharecode::MAX_LENGTH];
+    let mut code_position = 0;
+    let mut key_repeat = KeyRepeat::new();
+    let mut controls_selection = 0;
+    let mut alarm_selection = 0;
+    let mut paint_state = LIVE;
+    // A wall or emitter being painted in place of a cell state
+    let mut paint_fixed = None;
+    // Live cells of the generation before the board, for second order rules
+    let mut previous = vec![false; (WIDTH * HEIGHT) as usize];
+    // Live cells of the board over the board in the layers mode, and
+    // whether the cursor paints that one
+    let mut upper = vec![false; (WIDTH * HEIGHT) as usize];
+    let mut painting_upper = false;
+    // How long each fish and shark has gone without breeding or eating
+    let mut ocean = wator::Ocean::new((WIDTH * HEIGHT) as usize);
+    let mut painted: Option<NodeIndex> = None;
+
+    // Settings for Conway's Game of Life
+    let mut settings = Settings::new();
+
+    if !safe_mode {
+        preferences::load(&mut gba.save, &mut settings).expect("REASON");
+        settings.state_tiles();
+    }
+
+    // Only the alarms make any sound
+    gba.sound.enable();
 
-    let timer = gba.timers.timers();
-    let mut timer: agb::timer::Timer = timer.timer2;
+    let timers = gba.timers.timers();
+    let mut timer: agb::timer::Timer = timers.timer2;
     timer.set_divider(agb::timer::Divider::Divider1024);
     timer.set_enabled(false);
-    
-    // Settings Graph (Rules)
-    let mut graph_settings = Graph::new();
-    for j in 0..2 {
-    for i in 0..9 {
-        graph_settings.add_node(
-            settings.window_x+settings.rules_offset_x+i,
-            settings.window_y+settings.rules_offset_y+j,
-            Cell(settings.rules[j as usize][i as usize].into())
-        );
-    }}
-    for j in 0..2 {
-    for i in 0..9 {
-        if i < 8 {
-            graph_settings.add_edge(j*9+i, j*9+i+1, Some(Button::RIGHT));
-            graph_settings.add_edge(j*9+i+1, j*9+i, Some(Button::LEFT));
-        }
-        if j < 1 {
-            graph_settings.add_edge(j*9+i, (j+1)*9+i, Some(Button::DOWN));
-            graph_settings.add_edge((j+1)*9+i, j*9+i, Some(Button::UP));
-        }
-    }}
 
-    //Settings Graph (New/Save/Load)
-    let node_new = graph_settings.add_node(
-            settings.window_x+settings.rules_offset_x,
-            settings.window_y+settings.rules_offset_y+3,
-            Menu(New));
-    let node_save = graph_settings.add_node(
-            settings.window_x+settings.rules_offset_x,
-            settings.window_y+settings.rules_offset_y+4,
-            Menu(Save));
-    let node_load = graph_settings.add_node(
-            settings.window_x+settings.rules_offset_x,
-            settings.window_y+settings.rules_offset_y+5,
-            Menu(Load));
-    graph_settings.add_edge(node_new, 9, Some(Button::UP));
-    graph_settings.add_edge(node_new, node_save, Some(Button::DOWN));
-    graph_settings.add_edge(node_save, node_new, Some(Button::UP));
-    graph_settings.add_edge(node_save, node_load, Some(Button::DOWN));
-    graph_settings.add_edge(node_load, node_save, Some(Button::UP));
-    for n in 9..18 {
-        graph_settings.add_edge(n, node_new, Some(Button::DOWN));
-    }
-    
+    // Times each generation
+    let mut watchdog: agb::timer::Timer = timers.timer3;
+    watchdog.set_divider(agb::timer::Divider::Divider1024);
+    watchdog.set_enabled(false);
+    let mut strikes = 0;
+    // Timer ticks since a ramped run started, until it is up to speed
+    let mut ramp: Option<u32> = None;
+    // Frames since the last generation, for pacing by frames
+    let mut paced_frames: u16 = 0;
+    // Cells the last generation changed, for adaptive speed
+    let mut changed = 0;
+    // Paused, but with a generation going by every so often, and the one
+    // frame run for it
+    let mut steering = false;
+    let mut steer_step = false;
+    // A cart left in kiosk mode starts on the first show
+    let mut show = SHOWS.len() - 1;
+    let mut idle_frames = if settings.kiosk { KIOSK_IDLE_FRAMES } else { 0 };
     
+    let mut graph_settings = new_settings_graph(&settings);
+    let node_lab = graph_settings.nodes.iter()
+        .position(|n| matches!(n.state, Menu(Lab)))
+        .unwrap();
+    let node_turmite = graph_settings.nodes.iter()
+        .position(|n| matches!(n.state, Menu(TurmiteRules)))
+        .unwrap();
 
     // Game Graph
-    let mut graph = new_world(WIDTH.into(), HEIGHT.into());
+    let mut graph = new_board(&settings);
+    let mut history = History::new(settings.history_depth.into(), graph.nodes.len(), settings.states);
 
     let object = gba.display.object.get_managed();
     let mut cursor_world = Cursor::new(&graph, 0, &object);
     let mut cursor_config = Cursor::new(&graph_settings, 16, &object);
+    let mut lab = new_lab(settings.neighborhood);
+    let mut lab_running = false;
+    let mut cursor_lab = Cursor::new(&lab, 0, &object);
+    let turmite_graph = new_turmite_graph(&settings);
+    let mut cursor_turmite = Cursor::new(&turmite_graph, 0, &object);
     let mut cursor = &mut cursor_world;
     cursor.show();
+    let mut markers: [Option<NodeIndex>; MARKER_COUNT] = [None; MARKER_COUNT];
+    let mut marker_objects: Vec<Object> = (0..MARKER_COUNT).map(|i| {
+        let mut marker_object = object.object_sprite(MARKER_SPRITE.sprite(i));
+        marker_object.hide();
+        marker_object
+    }).collect();
+    let mut marker_selection = 0;
+    // Circuit material beside the board cursor while drawing Wireworld
+    let mut material = 0;
+    let mut material_objects: Vec<Object> = (0..MATERIALS.len()).map(|i| {
+        let mut material_object = object.object_sprite(MATERIAL_SPRITE.sprite(MATERIALS[i].index()));
+        material_object.hide();
+        material_object
+    }).collect();
+    let mut ants: Vec<Ant> = Vec::with_capacity(ANT_COUNT);
+    let mut ant_objects: Vec<Object> = (0..ANT_COUNT).map(|_| {
+        let mut ant_object = object.object_sprite(ANT_SPRITE.sprite(0));
+        ant_object.hide();
+        ant_object
+    }).collect();
+    let mut probe: Option<SignalProbe> = None;
+    let mut probe_object = object.object_sprite(PROBE_SPRITE.sprite(0));
+    probe_object.hide();
+    let mut world_node = 0;
+    let mut goto_target = (0, 0);
+    let mut goto_field = 0;
+    // Birth or survival and which end of it the range dialog is changing
+    let mut range_row = 0;
+    let mut range_field = 0;
+    let mut bookmarks: [Option<NodeIndex>; 4] = [None; 4];
+    let mut chord: Option<Button> = None;
+    let mut chord_used = false;
+    let mut tribute_code = 0;
     object.commit();
 
     let (gfx, mut vram) = gba.display.video.tiled0();
@@ -548,18 +2833,7 @@ fn main(mut gba: agb::Gba) -> ! {
         tileset.format(),
     );
 
-    for n in &graph.nodes {
-        bg.set_tile(
-            &mut vram,
-            (n.x, n.y),
-            &tileset,
-            background_tiles::tiles.tile_settings[
-                settings.tiles[
-                    match n.state { Cell(s) => s as usize, _ => 0 }
-                ] as usize
-            ],
-        );
-    }
+    redraw_world(&mut bg, &mut vram, &graph, &settings);
     bg.commit(&mut vram);
     bg.set_visible(true);
 
@@ -573,102 +2847,626 @@ fn main(mut gba: agb::Gba) -> ! {
     bg_settings.commit(&mut vram);
     bg_settings.set_visible(false);
 
+    //World Edge and Toast Background
+    let mut bg_overlay = gfx.background(
+        Priority::P0,
+        RegularBackgroundSize::Background32x32,
+        tileset.format(),
+    );
+    draw_world_edge(&mut bg_overlay, &mut vram, &settings);
+    bg_overlay.commit(&mut vram);
+    bg_overlay.set_visible(true);
+
+    //Echo Background, a copy of the board faded over it and shifted
+    //along by its own scroll
+    let mut bg_echo = gfx.background(
+        Priority::P0,
+        RegularBackgroundSize::Background32x32,
+        tileset.format(),
+    );
+    let mut blend = gba.display.blend.get();
+    blend.set_background_enable(Layer::Top, bg_echo.background(), true)
+         .set_background_enable(Layer::Bottom, bg.background(), true)
+         .set_background_enable(Layer::Bottom, bg_echo.background(), true)
+         .set_blend_weight(Layer::Top, Num::from_raw(ECHO_WEIGHT))
+         .set_blend_weight(Layer::Bottom, Num::from_raw(16 - ECHO_WEIGHT))
+         .set_blend_mode(BlendMode::Normal)
+         .commit();
+
 
-    let mut input = agb::input::ButtonController::new();
+    let mut input = Input::new();
 
     let mut game_state = GameState::Paused;
+    let mut inspected: Option<NodeIndex> = None;
+    let mut rng = Prng::new(DEFAULT_SEED);
+    let mut generation: u32 = 0;
+    let mut event_log = EventLog::new();
+    event_log.record(generation, LogEntry::Rule(settings.rules, settings.states));
+    let mut ship_tracker: Option<ShipTracker> = None;
+    let mut predecessor_search: Option<PredecessorSearch> = None;
+    let mut lifespan_tracker: Option<LifespanTracker> = None;
+    let mut run_until: Option<RunUntil> = None;
+    let mut until_goal = Goal::Generation;
+    let mut until_target: u32 = 1000;
+    let mut until_every: u16 = 10;
+    let mut until_field = 0;
+    let mut fire_field = 0;
+    let mut weight_place = 0;
+    let mut table_place = (0, 0);
+    let mut wator_field = 0;
+    let mut time_lapse: Option<TimeLapse> = None;
+    let mut lapse_snapshot = 0;
+    let mut lapse_frames = 0;
+    let mut edge_preview = EdgePreview::new(settings.boundary);
+    // Recent population in the corner, only while running
+    let mut sparkline = Sparkline::new();
+    let mut sparkline_shown = false;
+    let mut tools_selection = 0;
+    let mut sram_offset = 0;
+    let mut toast_frames = 0;
+    let mut config_rules = (settings.rules, settings.states);
+
+    if safe_mode {
+        show_toast(&mut bg_overlay, &mut vram, "SAFE MODE - DEFAULTS");
+        bg_overlay.commit(&mut vram);
+        toast_frames = TOAST_FRAMES;
+    }
+    boot_finished(&mut gba.save).expect("REASON");
 
+    // Visitors always start on a fresh board
+    let session = if safe_mode || settings.kiosk { None } else { read_session(&mut gba.save).unwrap_or(None) };
+    if let Some((_, session_generation)) = session {
+        game_state = GameState::Resume;
+        cursor.hide();
+        new_resume_prompt(&mut bg_settings, &mut vram, &settings, session_generation);
+        bg_settings.set_visible(true);
+    }
+    let mut session_frames = 0;
 
     timer.set_enabled(true);
+    context::write(&mut gba.save, GameState::Paused.name(), 0, save_status).ok();
+    let mut reset_armed = false;
     loop {
-        input.update();
+        let mut frame_due = true;
+        let replaying = input.replaying();
+        input.update(&rng);
+
+        // Only once they have been let go since the last reset, so holding
+        // them on doesn't reset over and over
+        let reset_held = SOFT_RESET.iter().all(|b| input.is_pressed(*b));
+        if reset_held && reset_armed {
+            return;
+        }
+        reset_armed |= !reset_held;
+        if replaying && !input.replaying() {
+            show_toast(&mut bg_overlay, &mut vram, "REPLAY DONE");
+            toast_frames = TOAST_FRAMES;
+        }
+
+        if game_state != context_state
+            || generation / CONTEXT_INTERVAL != context_generation / CONTEXT_INTERVAL {
+            context::write(&mut gba.save, game_state.name(), generation, save_status).ok();
+            context_state = game_state;
+            context_generation = generation;
+        }
+        // Not until the one there has been carried on or passed over
+        session_frames += 1;
+        if session_frames >= SESSION_FRAMES && game_state != GameState::Resume {
+            session_frames = 0;
+            let node = if matches!(game_state, GameState::Paused | GameState::Running) { cursor.node } else { world_node };
+            save_session(&mut gba.save, &graph, &settings, &markers, &rng, node, generation).ok();
+        }
+
+        if toast_frames > 0 {
+            toast_frames -= 1;
+            if toast_frames == 0 {
+                hide_toast(&mut bg_overlay, &mut vram, &settings);
+            }
+        }
+        // Where a screen is, unless something else is being shown
+        if sparkline_shown && game_state != GameState::Running && !steering {
+            hide_sparkline(&mut bg_overlay, &mut vram, &settings);
+            sparkline_shown = false;
+        }
+
+        if game_state != crumb_state {
+            crumb_state = game_state;
+            if let Some(crumbs) = game_state.breadcrumb().filter(|_| toast_frames == 0) {
+                show_toast(&mut bg_overlay, &mut vram, &crumbs);
+                toast_frames = TOAST_FRAMES;
+            }
+        }
+
+        // A slice of the predecessor search every frame whatever the state
+        if let Some(search) = &mut predecessor_search {
+            let message = match search.run(&settings.rules, PREDECESSOR_STEPS) {
+                Search::Searching => None,
+                Search::Found => Some("PREDECESSOR FOUND"),
+                Search::Impossible => Some("GARDEN OF EDEN"),
+                Search::OutOfBudget => Some("NONE FOUND WITHIN BUDGET"),
+            };
+            if let Some(message) = message {
+                show_toast(&mut bg_overlay, &mut vram, message);
+                toast_frames = TOAST_FRAMES;
+                predecessor_search = None;
+            }
+        }
+
+        // Holding L and R while pressing SELECT hands the cart back
+        if settings.kiosk && input.is_pressed(Button::L) && input.is_pressed(Button::R)
+            && input.is_just_pressed(Button::SELECT) {
+            settings.kiosk = false;
+            preferences::save(&mut gba.save, &settings).expect("REASON");
+            show_toast(&mut bg_overlay, &mut vram, "KIOSK OFF");
+            toast_frames = TOAST_FRAMES;
+        }
+        if settings.kiosk {
+            let touched = [Button::A, Button::B, Button::L, Button::R, Button::START, Button::SELECT,
+                           Button::UP, Button::DOWN, Button::LEFT, Button::RIGHT]
+                .iter().any(|b| input.is_pressed(*b));
+            idle_frames = if touched || game_state == GameState::Running { 0 } else { idle_frames + 1 };
+            if idle_frames >= KIOSK_IDLE_FRAMES
+                || (game_state == GameState::Running && generation >= KIOSK_SHOW_GENERATIONS) {
+                show = (show + 1) % SHOWS.len();
+                start_show(&mut graph, &mut settings, &SHOWS[show], &mut rng);
+                probe = None;
+                ants.clear();
+                settings.history_depth = history.set_states(settings.states) as u16;
+                generation = 0;
+                redraw_world(&mut bg, &mut vram, &graph, &settings);
+                show_toast(&mut bg_overlay, &mut vram, SHOWS[show].name);
+                toast_frames = TOAST_FRAMES;
+                game_state = GameState::Running;
+                cursor.hide();
+                idle_frames = 0;
+            }
+        }
 
         match game_state {
             GameState::Paused => {
+                // START and SELECT act on release so they can be held with a
+                // direction to store or recall a bookmark instead
+                for b in [Button::START, Button::SELECT] {
+                    if input.is_just_pressed(b) {
+                        chord = Some(b);
+                        chord_used = false;
+                    }
+                }
+                let start_tapped = chord == Some(Button::START) && !chord_used
+                    && input.is_just_released(Button::START);
+                let select_tapped = chord == Some(Button::SELECT) && !chord_used
+                    && input.is_just_released(Button::SELECT);
+                if let Some(b) = chord {
+                    if input.is_just_released(b) {
+                        chord = None;
+                    }
+                }
+
+                let leaving = input.is_just_pressed(Button::B) || start_tapped;
+                if let Some(node) = inspected {
+                    if leaving || !input.is_pressed(Button::R) {
+                        clear_cell_inspector(&mut bg_settings, &mut vram, &graph, node);
+                        new_config_menu(&mut bg_settings, &mut vram, &settings);
+                        bg_settings.set_visible(false);
+                        inspected = None;
+                    }
+                }
+
+                // SELECT and B steers, the board still there to edit between
+                // the generations
+                if chord == Some(Button::SELECT) && input.is_just_pressed(Button::B) {
+                    chord_used = true;
+                    steering = !steering;
+                    paced_frames = 0;
+                    show_toast(&mut bg_overlay, &mut vram, if steering { "STEERING" } else { "STEERING OFF" });
+                    toast_frames = TOAST_FRAMES;
+                    continue;
+                }
+
+                // B and A only count towards the code once the directions
+                // are all in, so neither is kept from its usual use otherwise
+                let pressed = [Button::UP, Button::DOWN, Button::LEFT, Button::RIGHT, Button::A,
+                               Button::B, Button::L, Button::R]
+                    .into_iter().find(|b| input.is_just_pressed(*b));
+                if let (Some(b), None) = (pressed, chord) {
+                    tribute_code = code_progress(tribute_code, b);
+                    if tribute_code == TRIBUTE_CODE.len() {
+                        tribute_code = 0;
+                        steering = false;
+                        start_show(&mut graph, &mut settings, &TRIBUTE, &mut rng);
+                        probe = None;
+                        ants.clear();
+                        settings.history_depth = history.set_states(settings.states) as u16;
+                        generation = 0;
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                        game_state = GameState::Tribute;
+                        cursor.hide();
+                        new_tribute_card(&mut bg_settings, &mut vram, &settings);
+                        bg_settings.set_visible(true);
+                        continue;
+                    }
+                    if tribute_code == TRIBUTE_CODE.len() - 1 {
+                        continue;
+                    }
+                }
+
                 if input.is_just_pressed(Button::B) {
                     game_state = GameState::Running;
                     cursor.hide();
+                    steering = false;
+                    steer_step = false;
+                    ramp = if settings.ramp { Some(0) } else { None };
+                    paced_frames = 0;
                     timer.set_enabled(false);
                     timer.set_enabled(true);
                     continue;
                 }
 
-                if input.is_just_pressed(Button::START) {
+                // No menu, and so no saving, for visitors
+                if start_tapped && settings.kiosk {
+                    show_toast(&mut bg_overlay, &mut vram, "B RUNS  A DRAWS");
+                    toast_frames = TOAST_FRAMES;
+                } else if start_tapped {
                     game_state = GameState::Config;
                     bg_settings.set_visible(true);
+                    world_node = cursor.node;
                     cursor.hide();
                     cursor = &mut cursor_config;
                     cursor.show();
                     cursor.set_position(&mut graph_settings, 18);
+                    config_rules = (settings.rules, settings.states);
                     continue;
                 }
 
-                match input.just_pressed_x_tri() {
-                    Tri::Negative => cursor.move_cursor(&mut graph, Button::LEFT),
-                    Tri::Positive => cursor.move_cursor(&mut graph, Button::RIGHT),
-                    _ => ()
+                // Circuits need SELECT for the material, so no markers there
+                if select_tapped && settings.automaton == Automaton::Wireworld {
+                    material = (material + 1) % MATERIALS.len();
+                } else if select_tapped {
+                    if let Some(m) = markers.iter().position(|m| *m == Some(cursor.node)) {
+                        markers[m] = None;
+                    } else if let Some(m) = markers.iter().position(|m| m.is_none()) {
+                        markers[m] = Some(cursor.node);
+                    }
+                    redraw_markers(&mut marker_objects, &markers, &graph);
                 }
-                match input.just_pressed_y_tri() {
-                    Tri::Negative => cursor.move_cursor(&mut graph, Button::UP),
-                    Tri::Positive => cursor.move_cursor(&mut graph, Button::DOWN),
-                    _ => ()
+
+                if input.is_just_pressed(Button::L) {
+                    let after: Vec<bool> = graph.nodes.iter().map(|n| matches!(n.state, Cell(LIVE))).collect();
+                    if let Some(g) = history.pop(&mut graph) {
+                        generation = g;
+                        if settings.runs_second_order() {
+                            previous = second_order_previous(&graph, &after, &settings, &mut rng);
+                        }
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    }
                 }
-                if input.is_just_pressed(Button::A) {
+
+                // START and A switches the layer the cursor paints
+                if chord == Some(Button::START) && input.is_just_pressed(Button::A)
+                    && settings.automaton == Automaton::Layers {
+                    chord_used = true;
+                    painting_upper = !painting_upper;
+                    show_toast(&mut bg_overlay, &mut vram, if painting_upper { "LAYER B" } else { "LAYER A" });
+                    toast_frames = TOAST_FRAMES;
+                }
+
+                // SELECT and A looks for the closest survivor
+                if chord == Some(Button::SELECT) && input.is_just_pressed(Button::A) {
+                    chord_used = true;
+                    match graph.nearest_live(cursor.node) {
+                        Some(node) => cursor.set_position(&graph, node),
+                        None => {
+                            show_toast(&mut bg_overlay, &mut vram, "NO LIVE CELLS");
+                            toast_frames = TOAST_FRAMES;
+                        },
+                    }
+                }
+
+                if let Some(b) = chord {
+                    let direction = [Button::UP, Button::RIGHT, Button::DOWN, Button::LEFT]
+                        .iter()
+                        .position(|d| input.is_just_pressed(*d));
+                    if let Some(d) = direction {
+                        chord_used = true;
+                        if b == Button::START {
+                            bookmarks[d] = Some(cursor.node);
+                        } else if let Some(node) = bookmarks[d] {
+                            cursor.set_position(&graph, node);
+                        }
+                    }
+                } else if let Some(b) = key_repeat.update(&input, &settings.board_repeat) {
+                    cursor.move_cursor(&graph, b);
+                }
+                let painting = settings.paint && input.is_pressed(Button::A)
+                    && painted != Some(cursor.node);
+                // The upper layer is drawn at the end of the frame and has
+                // no undo
+                if (input.is_just_pressed(Button::A) || painting) && chord.is_none()
+                    && painting_upper && settings.automaton == Automaton::Layers {
+                    if input.is_just_pressed(Button::A) {
+                        paint_state = if upper[cursor.node] { DEAD } else { LIVE };
+                    }
+                    upper[cursor.node] = paint_state == LIVE;
+                    painted = Some(cursor.node);
+                } else if (input.is_just_pressed(Button::A) || painting) && chord.is_none() {
+                    // One undo step per stroke
+                    if input.is_just_pressed(Button::A) {
+                        history.push(&graph, generation);
+                        paint_fixed = None;
+                    }
                     let n = &mut (graph.nodes)[cursor.node];
                     match &n.state {
-                        Cell(s) => n.state = Cell(!*s),
+                        // A cell already of the material is cleared
+                        Cell(s) if input.is_just_pressed(Button::A)
+                            && settings.automaton == Automaton::Wireworld => {
+                            paint_state = if *s == MATERIALS[material] { DEAD } else { MATERIALS[material] };
+                            n.state = Cell(paint_state);
+                        },
+                        // Dead, red, blue and round again
+                        Cell(s) if input.is_just_pressed(Button::A)
+                            && settings.automaton == Automaton::Immigration => {
+                            paint_state = match *s { DEAD => RED, RED => LIVE, _ => DEAD };
+                            n.state = Cell(paint_state);
+                        },
+                        // Empty, tree, burning and round again
+                        Cell(s) if input.is_just_pressed(Button::A)
+                            && settings.automaton == Automaton::ForestFire => {
+                            paint_state = match *s { DEAD => LIVE, LIVE => BURNING, _ => DEAD };
+                            n.state = Cell(paint_state);
+                        },
+                        // Empty, rock, paper, scissors and round again, or
+                        // water, fish and sharks
+                        Cell(s) if input.is_just_pressed(Button::A)
+                            && matches!(settings.automaton, Automaton::Species | Automaton::WaTor) => {
+                            paint_state = CellState::from((s.index() as u16 + 1) % settings.states as u16);
+                            n.state = Cell(paint_state);
+                        },
+                        // Each press drops another grain on the pile
+                        Cell(s) if input.is_just_pressed(Button::A)
+                            && settings.automaton == Automaton::Sandpile => {
+                            paint_state = CellState::from((s.index() as u16 + 1).min(MAX_STATES as u16 - 1));
+                            n.state = Cell(paint_state);
+                        },
+                        // Each press is the next mix of particles
+                        Cell(s) if input.is_just_pressed(Button::A)
+                            && settings.automaton == Automaton::LatticeGas => {
+                            paint_state = CellState::from((s.index() as u16 + 1) % MAX_STATES as u16);
+                            n.state = Cell(paint_state);
+                        },
+                        // Dead, live, wall, emitter and round again
+                        Cell(LIVE) if input.is_just_pressed(Button::A) && settings.walls() => {
+                            paint_fixed = Some(Wall);
+                            n.state = Wall;
+                        },
+                        Wall if input.is_just_pressed(Button::A) => {
+                            paint_fixed = Some(Emitter);
+                            n.state = Emitter;
+                        },
+                        Emitter if input.is_just_pressed(Button::A) => {
+                            paint_state = DEAD;
+                            n.state = Cell(paint_state);
+                        },
+                        Cell(s) if input.is_just_pressed(Button::A) => {
+                            paint_state = !*s;
+                            n.state = Cell(paint_state);
+                        },
+                        Cell(_) | Wall | Emitter => n.state = paint_fixed.unwrap_or(Cell(paint_state)),
                         _ => (),
                     }
+                    n.age = 0;
+                    painted = Some(cursor.node);
 
-                    let tile_id = match n.state {
-                        Cell(s) => settings.tiles[s as usize],
-                        _ => 0,
-                    };
                     bg.set_tile(
                          &mut vram,
                          (n.x, n.y),
                          &tileset,
-                         background_tiles::tiles.tile_settings[tile_id as usize],
+                         settings.node_tile(n),
                          );
                 }
+
+                if input.is_pressed(Button::R)
+                    && (inspected != Some(cursor.node) || input.is_just_pressed(Button::A)) {
+                    if let Some(node) = inspected {
+                        clear_cell_inspector(&mut bg_settings, &mut vram, &graph, node);
+                    }
+                    new_cell_inspector(&mut bg_settings, &mut vram, &settings, &graph, cursor.node);
+                    bg_settings.set_visible(true);
+                    inspected = Some(cursor.node);
+                }
+
+                // The edits so far go into the next generation, run in a
+                // frame of its own
+                if steering && paced_frames >= STEER_FRAMES && game_state == GameState::Paused {
+                    game_state = GameState::Running;
+                    steer_step = true;
+                }
             },
             GameState::Running => {
                 if input.is_just_pressed(Button::B) {
                     game_state = GameState::Paused;
                     cursor.show();
+                    if run_until.take().is_some() || input.is_pressed(Button::R) {
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    }
                     continue;
                 }
 
-                if timer.value() < settings.speed {
+                // Holding A where chance plays a part opens a gravity well
+                // under the cursor, which the D-pad moves about
+                let well = input.is_pressed(Button::A) && settings.stochastic();
+                if well {
+                    cursor.show();
+                    if let Some(b) = key_repeat.update(&input, &settings.board_repeat) {
+                        cursor.move_cursor(&graph, b);
+                    }
+                } else if input.is_just_released(Button::A) && !steering {
+                    cursor.hide();
+                }
+
+                // Holding R runs flat out, as does running until a goal, with
+                // the whole board drawn only every so many generations
+                let turbo = input.is_pressed(Button::R);
+                let every = match &run_until {
+                    Some(until) => Some(until.every()),
+                    None if turbo => Some(settings.turbo_every as u32),
+                    None => None,
+                };
+                if input.is_just_released(Button::R) && run_until.is_none() {
+                    redraw_world(&mut bg, &mut vram, &graph, &settings);
+                }
+
+                let interval = match ramp {
+                    Some(elapsed) => ramp_interval(settings.speed, elapsed),
+                    None => settings.speed,
+                };
+                let interval = adaptive_interval(interval, changed, settings.adapt);
+                let (due, waited) = match settings.pacing {
+                    Pacing::Timer => (timer.value() >= interval, timer.value() as u32),
+                    Pacing::Frames => (paced_frames >= interval_frames(interval),
+                                       paced_frames as u32 * FRAME_TICKS),
+                };
+                if !due && every.is_none() && !steer_step {
                     vblank.wait_for_vblank();
+                    paced_frames = paced_frames.saturating_add(1);
                     bg.commit(&mut vram);
+                    bg_overlay.commit(&mut vram);
                     object.commit();
                     continue;
                 } else {
+                    ramp = ramp.map(|elapsed| elapsed + waited)
+                        .filter(|elapsed| *elapsed < RAMP_TICKS);
+                    paced_frames = 0;
                     timer.set_enabled(false);
                     timer.set_enabled(true);
                 }
 
                 // Update State
-                let mut neighbors = [0 ; (HEIGHT * WIDTH) as usize];
-                for i in 0..graph.nodes.len() {
-                    neighbors[i] = graph.living_neighbors_count_of(i);
+                watchdog.set_enabled(false);
+                watchdog.set_enabled(true);
+                let before = if settings.alarms.armed() { population(&graph) } else { 0 };
+                history.push(&graph, generation);
+                generation = generation.wrapping_add(1);
+                // Births measured from the screen center
+                let mut births = 0;
+                let mut birth_x = 0;
+                let mut birth_y = 0;
+                let drawn = every.is_none();
+                let second_order = settings.runs_second_order();
+                if settings.automaton == Automaton::Elementary {
+                    step_elementary(&mut graph, settings.wolfram, WIDTH, settings.boundary);
+                    if drawn {
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    }
+                } else if settings.automaton == Automaton::ForestFire {
+                    fire::step(&mut graph, settings.growth, settings.lightning, &mut rng);
+                    if drawn {
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    }
+                } else if settings.automaton == Automaton::Sandpile {
+                    sandpile::step(&mut graph);
+                    if drawn {
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    }
+                } else if settings.automaton == Automaton::Table {
+                    table::step(&mut graph, &settings.table, settings.states);
+                    if drawn {
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    }
+                } else if settings.automaton == Automaton::WaTor {
+                    wator::step(&mut graph, &mut ocean, &settings.wator, &mut rng);
+                    if drawn {
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    }
+                } else if settings.automaton == Automaton::Species {
+                    species::step(&mut graph, settings.threshold);
+                    if drawn {
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    }
+                } else if settings.automaton == Automaton::LatticeGas {
+                    gas::step(&mut graph, WIDTH, settings.boundary);
+                    if drawn {
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    }
+                } else if settings.automaton == Automaton::Margolus {
+                    // Undo takes the generation back too, so the blocks
+                    // stay in step
+                    margolus::step(&mut graph, BLOCK_RULES[settings.block_rule].1, WIDTH,
+                                   settings.boundary, (generation % 2) as u16);
+                    if drawn {
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    }
+                } else {
+                    settings.cycle_palettes(&mut vram);
+                    // From the lower board as it was
+                    if settings.automaton == Automaton::Layers {
+                        layers::step(&graph, &mut upper, &settings, &mut rng);
+                    }
+                    step_graph_with(&mut graph, &settings, WIDTH, settings.boundary, &mut rng, |i, n, was| {
+                        if second_order {
+                            if let Cell(next) = n.state {
+                                n.state = Cell(second_order_flip(next, previous[i]));
+                            }
+                            previous[i] = was == LIVE;
+                        }
+                        if was == DEAD && !matches!(n.state, Cell(DEAD)) {
+                            births += 1;
+                            birth_x += n.x as i32 - (WIDTH/2) as i32;
+                            birth_y += n.y as i32 - (HEIGHT/2) as i32;
+                        }
+                        if !drawn {
+                            return;
+                        }
+                        // Still decaying, and shaded by its palette
+                        let state = match n.state { Cell(s) => s, _ => DEAD };
+                        if settings.phases > 0 && state.index() > 2 {
+                            return;
+                        }
+
+                        bg.set_tile(
+                             &mut vram,
+                             (n.x, n.y),
+                             &tileset,
+                             settings.node_tile(n),
+                         );
+                    });
                 }
 
-                for i in 0..graph.nodes.len() {
-                    let n = &mut graph.nodes[i];
-                    match n.state {
-                        Cell(s) => {
-                            n.state = Cell(settings.rules[s as usize][neighbors[i] as usize].into())
-                        },
-                        _ => (),
+                if settings.noise_cells > 0 && generation.is_multiple_of(settings.noise_every as u32) {
+                    for i in inject_noise(&mut graph, settings.noise_cells, &mut rng) {
+                        if drawn {
+                            let n = &graph.nodes[i];
+                            bg.set_tile(&mut vram, (n.x, n.y), &tileset, settings.tile(LIVE));
+                        }
+                    }
+                }
+
+                if well {
+                    for i in gravity_well(&mut graph, cursor.node, &mut rng) {
+                        if drawn {
+                            let n = &graph.nodes[i];
+                            bg.set_tile(&mut vram, (n.x, n.y), &tileset, settings.tile(LIVE));
+                        }
+                    }
+                }
+
+                for ant in &mut ants {
+                    let node = ant.node;
+                    ant.step(&mut graph, &settings.turmite);
+                    if let Some(highway) = ant.highway.step(ant.heading, ant.node, generation) {
+                        event_log.record(highway.start, LogEntry::Highway(highway.period));
+                        show_toast(&mut bg_overlay, &mut vram,
+                            &format!("HIGHWAY FROM {} P{}", highway.start, highway.period));
+                        toast_frames = TOAST_FRAMES;
+                        if settings.highway_pause {
+                            game_state = GameState::Paused;
+                            cursor.show();
+                        }
                     }
-                    
-                    let tile = settings.tiles[
-                        match n.state {
-                            Cell(s) => s as usize,
-                            _ => 0,
-                        } as usize];
+                    if !drawn {
+                        continue;
+                    }
+                    let n = &graph.nodes[node];
+                    let tile = settings.tiles[match n.state { Cell(s) => s.index(), _ => 0 }];
                     bg.set_tile(
                          &mut vram,
                          (n.x, n.y),
@@ -676,94 +3474,618 @@ fn main(mut gba: agb::Gba) -> ! {
                          background_tiles::tiles.tile_settings[tile as usize],
                      );
                 }
-            },
-            GameState::Config => {
-                for n in &mut graph_settings.nodes {
-                    match n.state {
-                        Cell(s) => {
 
-                            let r = &mut settings.rules
-                                [(n.y-settings.window_y-settings.rules_offset_y) as usize]
-                                [(n.x-settings.window_x-settings.rules_offset_x) as usize];
-                            n.state = Cell((*r).into());
+                // The generation keeps counting between the boards drawn
+                if let Some(every) = every {
+                    show_toast(&mut bg_overlay, &mut vram, &format!("GEN {}", generation));
+                    toast_frames = TOAST_FRAMES;
+                    frame_due = generation.is_multiple_of(every);
+                    if frame_due {
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    }
+                }
 
-                            let tile = settings.tiles[s as usize];
-                            bg_settings.set_tile(
-                                &mut vram,
-                                (n.x, n.y),
-                                &tileset,
-                                background_tiles::tiles.tile_settings[tile as usize]
-                            );
+                if let Some(lapse) = &mut time_lapse {
+                    lapse.step(&graph, generation);
+                }
+
+                if settings.adapt > 0 {
+                    changed = activity(&graph);
+                }
+
+                sparkline.push(population(&graph));
+                if drawn || frame_due {
+                    sparkline.draw(&mut bg_overlay, &mut vram, WIDTH - sparkline::TILES, 0);
+                    sparkline_shown = true;
+                }
+
+                if let Some(until) = &mut run_until {
+                    if let Some(report) = until.step(&graph, &settings.rules, generation) {
+                        show_toast(&mut bg_overlay, &mut vram, &report);
+                        toast_frames = TOAST_FRAMES;
+                        game_state = GameState::Paused;
+                        cursor.show();
+                    }
+                }
+
+                if settings.sample_interval > 0 && generation.is_multiple_of(settings.sample_interval as u32) {
+                    samples::record(&mut gba.save, generation, population(&graph)).expect("REASON");
+                }
+
+                if settings.alarms.armed() {
+                    if let Some(alarm) = settings.alarms.check(before, population(&graph)) {
+                        show_toast(&mut bg_overlay, &mut vram, &alarm.describe());
+                        toast_frames = TOAST_FRAMES;
+                        if settings.alarms.action.beeps() {
+                            alarm::beep(&gba.sound);
+                        }
+                        if settings.alarms.action.pauses() {
+                            game_state = GameState::Paused;
+                            cursor.show();
                         }
-                        _ => (),
                     }
                 }
 
-                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                // There is only the one engine to run, so stop rather than
+                // leave the game unresponsive when generations run long
+                strikes = if watchdog.value() > GENERATION_BUDGET { strikes + 1 } else { 0 };
+                if strikes >= WATCHDOG_STRIKES {
+                    strikes = 0;
                     game_state = GameState::Paused;
-                    bg_settings.set_visible(false);
-                    cursor.hide();
-                    cursor = &mut cursor_world;
                     cursor.show();
+                    show_toast(&mut bg_overlay, &mut vram, "TOO SLOW - PAUSED");
+                    toast_frames = TOAST_FRAMES;
+                }
+
+                // Wait for the tracked board to repeat, possibly translated
+                if let Some(tracker) = &mut ship_tracker {
+                    if let Some(velocity) = tracker.step(&graph) {
+                        event_log.record(generation, LogEntry::Velocity(velocity));
+                        show_toast(&mut bg_overlay, &mut vram, &velocity.describe());
+                        toast_frames = TOAST_FRAMES;
+                        ship_tracker = None;
+                    } else if tracker.expired() {
+                        show_toast(&mut bg_overlay, &mut vram, "NO PERIOD FOUND");
+                        toast_frames = TOAST_FRAMES;
+                        ship_tracker = None;
+                    }
+                }
+
+                // Run the pattern until the board settles
+                if let Some(tracker) = &mut lifespan_tracker {
+                    match tracker.step(&graph, &settings.rules, generation) {
+                        Lifespan::Running => (),
+                        Lifespan::Stable { lifespan, population } => {
+                            event_log.record(generation, LogEntry::Lifespan(lifespan, population));
+                            let message = match tracker.methuselah {
+                                // Measured against the record on an unbounded plane
+                                Some(m) => format!("{} {}/{} POP {}/{}",
+                                    m.name, lifespan, m.lifespan, population, m.population),
+                                None => format!("LIFE {} POP {}", lifespan, population),
+                            };
+                            show_toast(&mut bg_overlay, &mut vram, &message);
+                            toast_frames = TOAST_FRAMES;
+                            lifespan_tracker = None;
+                        },
+                        Lifespan::GaveUp => {
+                            show_toast(&mut bg_overlay, &mut vram, "DID NOT SETTLE");
+                            toast_frames = TOAST_FRAMES;
+                            lifespan_tracker = None;
+                        },
+                    }
+                }
+
+                // Report every head through the probe
+                if let Some(p) = &mut probe {
+                    if p.step(&graph, generation) {
+                        show_toast(&mut bg_overlay, &mut vram, &p.describe());
+                        toast_frames = TOAST_FRAMES;
+                    }
+                }
+
+                // On a torus moving every cell is the same as moving the view,
+                // so step the board one cell toward centering the births. On
+                // the plane the board only moves when it has to, but all the
+                // way to the middle.
+                let (dx, dy) = if settings.follow && settings.boundary == Boundary::Toroidal && births > 0 {
+                    let dx = -(birth_x / births).signum();
+                    // Whole pairs of rows keep the hex rows leaning the same way
+                    let dy = -(birth_y / births).signum() * match settings.neighborhood {
+                        Neighborhood::Moore | Neighborhood::VonNeumann => 1,
+                        Neighborhood::Hex => 2,
+                    };
+                    (dx, dy)
+                } else if settings.plane && settings.boundary == Boundary::DeadEdge {
+                    plane_shift(&graph, settings.neighborhood)
+                } else {
+                    (0, 0)
+                };
+                if dx != 0 || dy != 0 {
+                    shift_world(&mut graph, dx, dy);
+                    for m in markers.iter_mut().chain(bookmarks.iter_mut()).flatten()
+                        .chain(probe.iter_mut().map(|p| &mut p.node))
+                        .chain(ants.iter_mut().map(|a| &mut a.node)) {
+                        *m = shifted_node(*m, dx, dy);
+                    }
+                    redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    redraw_markers(&mut marker_objects, &markers, &graph);
+                }
+
+                // Back to editing, unless something else stopped the run
+                if steer_step {
+                    steer_step = false;
+                    steering &= game_state == GameState::Running;
+                    game_state = GameState::Paused;
+                }
+
+                // Whatever stopped the run, the board is shown as it was left
+                if game_state != GameState::Running && every.is_some() {
+                    run_until = None;
+                    frame_due = true;
+                    redraw_world(&mut bg, &mut vram, &graph, &settings);
+                }
+            },
+            GameState::Config => {
+                for n in &mut graph_settings.nodes {
+                    match n.state {
+                        Cell(s) => {
+
+                            let count = n.x-settings.window_x-settings.rules_offset_x;
+                            let r = &mut settings.rules
+                                [(n.y-settings.window_y-settings.rules_offset_y) as usize]
+                                [count as usize];
+                            n.state = Cell(((*r != 0) as u16).into());
+
+                            // Ranges are drawn as numbers over the counts
+                            if settings.automaton == Automaton::LargerThanLife {
+                                continue;
+                            }
+                            // Counts left to chance are shaded
+                            let tile = match *r {
+                                _ if count > settings.neighborhood.size() => 1,
+                                2.. => DECAY_TILES[0],
+                                _ => settings.tiles[s.index()],
+                            };
+                            bg_settings.set_tile(
+                                &mut vram,
+                                (n.x, n.y),
+                                &tileset,
+                                background_tiles::tiles.tile_settings[tile as usize]
+                            );
+                        }
+                        _ => (),
+                    }
+                }
+
+                if input.is_just_pressed(Button::SELECT) {
+                    game_state = GameState::Memory;
+                    cursor.hide();
+                    new_memory_report(&mut bg_settings, &mut vram, &settings, &graph, &graph_settings, &history, &last_context);
+                    continue;
+                }
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                    if (settings.rules, settings.states) != config_rules {
+                        event_log.record(generation, LogEntry::Rule(settings.rules, settings.states));
+                    }
+                    game_state = GameState::Paused;
+                    bg_settings.set_visible(false);
+                    cursor.hide();
+                    cursor = &mut cursor_world;
+                    cursor.show();
+                    paced_frames = 0;
                     timer.set_enabled(false);
                     timer.set_enabled(true);
                     continue;
                 }
-                match input.just_pressed_x_tri() {
-                    Tri::Negative => cursor.move_cursor(&mut graph_settings, Button::LEFT),
-                    Tri::Positive => cursor.move_cursor(&mut graph_settings, Button::RIGHT),
-                    _ => ()
+                let n = &graph_settings.nodes[cursor.node];
+                let rule = rule_under(&settings, n);
+                // On the rules UP and DOWN give the count under the cursor a
+                // live or dead cell, or with A held make it more or less
+                // likely to, and L and R jump to the birth or survival row
+                match (rule, key_repeat.update(&input, &settings.menu_repeat)) {
+                    (Some((j, i)), Some(b)) if b == Button::UP || b == Button::DOWN => {
+                        let r = &mut settings.rules[j][i];
+                        *r = match (input.is_pressed(Button::A), b == Button::UP) {
+                            (true, true) => rule_entry((rule_chance(*r) + CHANCE_STEP).min(CHANCE_ONE)),
+                            (true, false) => rule_entry(rule_chance(*r).saturating_sub(CHANCE_STEP)),
+                            (false, up) => up as u16,
+                        };
+                    },
+                    (_, Some(b)) => cursor.move_cursor(&graph_settings, b),
+                    _ => (),
                 }
-                match input.just_pressed_y_tri() {
-                    Tri::Negative => cursor.move_cursor(&mut graph_settings, Button::UP),
-                    Tri::Positive => cursor.move_cursor(&mut graph_settings, Button::DOWN),
-                    _ => ()
+                if let Some((_, i)) = rule {
+                    if input.is_just_pressed(Button::L) {
+                        cursor.set_position(&graph_settings, i);
+                    }
+                    if input.is_just_pressed(Button::R) {
+                        cursor.set_position(&graph_settings, 9 + i);
+                    }
+                }
+                let n = &graph_settings.nodes[cursor.node];
+                let rule = rule_under(&settings, n);
+                // L and R on the mode turn second order Life on and off
+                if matches!(n.state, Menu(Mode)) && settings.automaton == Automaton::Life
+                    && (input.is_just_pressed(Button::L) || input.is_just_pressed(Button::R)) {
+                    if settings.states == 2 {
+                        settings.second_order = !settings.second_order;
+                        previous.fill(false);
+                        draw_config_options(&mut bg_settings, &mut vram, &settings);
+                    } else {
+                        show_toast(&mut bg_overlay, &mut vram, "TWO STATES ONLY");
+                        toast_frames = TOAST_FRAMES;
+                    }
+                }
+                // and a territory map on and off in immigration
+                if matches!(n.state, Menu(Mode)) && settings.automaton == Automaton::Immigration
+                    && (input.is_just_pressed(Button::L) || input.is_just_pressed(Button::R)) {
+                    if cycle::phases() >= cycle::TERRITORY_PALETTES.len() {
+                        settings.territory = !settings.territory;
+                        draw_config_options(&mut bg_settings, &mut vram, &settings);
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    } else {
+                        show_toast(&mut bg_overlay, &mut vram, "NO SPARE PALETTES");
+                        toast_frames = TOAST_FRAMES;
+                    }
                 }
+                // and on the edge make dead edges an open plane
+                if matches!(n.state, Menu(Edge))
+                    && (input.is_just_pressed(Button::L) || input.is_just_pressed(Button::R)) {
+                    if settings.boundary == Boundary::DeadEdge {
+                        settings.plane = !settings.plane;
+                        draw_config_options(&mut bg_settings, &mut vram, &settings);
+                        preferences::save(&mut gba.save, &settings).expect("REASON");
+                    } else {
+                        show_toast(&mut bg_overlay, &mut vram, "DEAD EDGES ONLY");
+                        toast_frames = TOAST_FRAMES;
+                    }
+                }
+                // and on the grid step through the update orders
+                if matches!(n.state, Menu(Grid))
+                    && (input.is_just_pressed(Button::L) || input.is_just_pressed(Button::R)) {
+                    settings.update_order = settings.update_order.next();
+                    draw_config_options(&mut bg_settings, &mut vram, &settings);
+                }
+                // and on the states step through the lifespans
+                if matches!(n.state, Menu(States))
+                    && (input.is_just_pressed(Button::L) || input.is_just_pressed(Button::R)) {
+                    let i = LIFESPANS.iter().position(|n| *n == settings.lifespan).unwrap_or(0);
+                    let step = if input.is_just_pressed(Button::R) { 1 } else { LIFESPANS.len() - 1 };
+                    settings.lifespan = LIFESPANS[(i + step) % LIFESPANS.len()];
+                    draw_config_options(&mut bg_settings, &mut vram, &settings);
+                }
+                // and on the ramp switch between pacing by the timer and by frames
+                if matches!(n.state, Menu(Ramp))
+                    && (input.is_just_pressed(Button::L) || input.is_just_pressed(Button::R)) {
+                    settings.pacing = settings.pacing.next();
+                    draw_config_options(&mut bg_settings, &mut vram, &settings);
+                    preferences::save(&mut gba.save, &settings).expect("REASON");
+                }
+                // and scroll through the presets
+                if matches!(n.state, Menu(Preset)) {
+                    if input.is_just_pressed(Button::L) {
+                        settings.preset = (settings.preset + rulestring::PRESETS.len() - 1) % rulestring::PRESETS.len();
+                        draw_config_options(&mut bg_settings, &mut vram, &settings);
+                    }
+                    if input.is_just_pressed(Button::R) {
+                        settings.preset = (settings.preset + 1) % rulestring::PRESETS.len();
+                        draw_config_options(&mut bg_settings, &mut vram, &settings);
+                    }
+                }
+                draw_rule_chance(&mut bg_settings, &mut vram, &settings, rule.map(|(j, i)| settings.rules[j][i]));
+                // What the edge does, beside the rules while it is chosen
+                let preview_x = settings.window_x+settings.rules_offset_x+9;
+                let preview_y = settings.window_y+settings.rules_offset_y+1;
+                if matches!(n.state, Menu(Edge)) {
+                    edge_preview.update(settings.boundary);
+                    edge_preview.draw(&mut bg_settings, &mut vram, preview_x, preview_y);
+                } else {
+                    for row in 0..PREVIEW_TILES {
+                        draw_text(&mut bg_settings, &mut vram, preview_x, preview_y+row, "  ");
+                    }
+                }
+                draw_rulestring(&mut bg_settings, &mut vram, &settings);
                 if input.is_just_pressed(Button::A) {
                     let mut n = &mut (graph_settings.nodes)[cursor.node];
                     match &n.state {
                         Menu(m) => match m {
-                            New => for cell in &mut graph.nodes {
-                                cell.state = Cell(Dead);
-                                let tile = settings.tiles[match cell.state {
-                                    Cell(s) => s as usize,
-                                    _ => 0 as usize
-                                }] as usize;
-                                bg.set_tile(
-                                    &mut vram,
-                                    (cell.x, cell.y),
-                                    &tileset,
-                                    background_tiles::tiles.tile_settings[tile]
-                                );
-                                // Default to Conway's Game of Life rules
-                                for i in 0..settings.rules.len() {
-                                for j in 0..settings.rules[0].len() {
-                                    settings.rules[i][j] = 0;
-                                }}
-                                settings.rules[0][3] = 1;
-                                settings.rules[1][2] = 1;
-                                settings.rules[1][3] = 1;
-                            },
-                            Save => save_world(&mut gba.save, &graph, &settings).expect("REASON"),
-                            Load => {
-                                load_world(&mut gba.save, &mut graph, &mut settings).expect("REASON");
-                                for n in &graph.nodes {
+                            New => {
+                                generation = 0;
+                                previous.fill(false);
+                                upper.fill(false);
+                                sparkline.clear();
+                                for cell in &mut graph.nodes {
+                                    cell.state = Cell(DEAD);
+                                    cell.age = 0;
+                                    let tile = settings.tiles[match cell.state {
+                                        Cell(s) => s.index(),
+                                        _ => 0
+                                    }] as usize;
                                     bg.set_tile(
                                         &mut vram,
-                                        (n.x, n.y),
+                                        (cell.x, cell.y),
                                         &tileset,
-                                        background_tiles::tiles.tile_settings[
-                                            settings.tiles[
-                                                match n.state { Cell(s) => s as usize, _ => 0 }
-                                            ] as usize
-                                        ],
+                                        background_tiles::tiles.tile_settings[tile]
                                     );
-                                };
+                                    // Default to Conway's Game of Life rules
+                                    settings.rules = rulestring::from_str("B3/S23").unwrap().0;
+                                }
+                            },
+                            Save => {
+                                let name = save_name(&settings, generation, &graph);
+                                let saved = save_world(&mut gba.save, WORLD_OFFSET, &graph, &settings, &markers, &rng)
+                                    .and_then(|_| write_save_name(&mut gba.save, &name));
+                                save_status = if saved.is_ok() { SaveStatus::Saved } else { SaveStatus::Failed };
+                                context::write(&mut gba.save, game_state.name(), generation, save_status).ok();
+                                show_toast(&mut bg_overlay, &mut vram,
+                                    if saved.is_ok() { &name } else { "SAVE FAILED" });
+                                toast_frames = TOAST_FRAMES;
+                            },
+                            Load => {
+                                load_world(&mut gba.save, WORLD_OFFSET, &mut graph, &mut settings, &mut markers, &mut rng).expect("REASON");
+                                let name = read_save_name(&mut gba.save).expect("REASON");
+                                if !name.is_empty() {
+                                    show_toast(&mut bg_overlay, &mut vram, &name);
+                                    toast_frames = TOAST_FRAMES;
+                                }
+                                redraw_markers(&mut marker_objects, &markers, &graph);
+                                probe = None;
+                                ants.clear();
+                                settings.history_depth = history.set_states(settings.states) as u16;
+                                generation = 0;
+                                redraw_world(&mut bg, &mut vram, &graph, &settings);
+                                graph_settings = new_settings_graph(&settings);
+                                new_config_menu(&mut bg_settings, &mut vram, &settings);
                                 for n in &graph_settings.nodes {
                                     
                                 };
+                            },
+                            HistoryDepth => {
+                                let i = HISTORY_DEPTHS.iter()
+                                    .position(|d| *d == settings.history_depth)
+                                    .unwrap_or(0);
+                                let depth = HISTORY_DEPTHS[(i + 1) % HISTORY_DEPTHS.len()];
+                                settings.history_depth = history.resize(depth.into()) as u16;
+                                draw_config_options(&mut bg_settings, &mut vram, &settings);
+                                preferences::save(&mut gba.save, &settings).expect("REASON");
+                            },
+                            Edge => {
+                                settings.boundary = settings.boundary.next();
+                                let mut world = new_board(&settings);
+                                for (cell, old) in world.nodes.iter_mut().zip(&graph.nodes) {
+                                    if let Cell(s) = old.state {
+                                        cell.state = Cell(s);
+                                        cell.age = old.age;
+                                    }
+                                }
+                                graph = world;
+                                draw_world_edge(&mut bg_overlay, &mut vram, &settings);
+                                draw_config_options(&mut bg_settings, &mut vram, &settings);
+                                preferences::save(&mut gba.save, &settings).expect("REASON");
+                            },
+                            Grid => {
+                                settings.neighborhood = settings.neighborhood.next();
+                                let mut world = new_board(&settings);
+                                for (cell, old) in world.nodes.iter_mut().zip(&graph.nodes) {
+                                    if let Cell(s) = old.state {
+                                        cell.state = Cell(s);
+                                        cell.age = old.age;
+                                    }
+                                }
+                                graph = world;
+                                lab = new_lab(settings.neighborhood);
+                                settings.state_tiles();
+                                redraw_world(&mut bg, &mut vram, &graph, &settings);
+                                // Only the counts the neighborhood can reach are shown
+                                graph_settings = new_settings_graph(&settings);
+                                new_config_menu(&mut bg_settings, &mut vram, &settings);
+                                preferences::save(&mut gba.save, &settings).expect("REASON");
+                            },
+                            Gliders => {
+                                history.push(&graph, generation);
+                                for _ in 0..GLIDER_BATCH {
+                                    let orientation = rng.below(8) as u8;
+                                    let (w, h) = GLIDER.size(orientation);
+                                    let x = rng.below((WIDTH - w + 1).into()) as u16;
+                                    let y = rng.below((HEIGHT - h + 1).into()) as u16;
+                                    stamp(&mut graph, WIDTH, &GLIDER, x, y, orientation);
+                                }
+                                redraw_world(&mut bg, &mut vram, &graph, &settings);
+                            },
+                            Log => {
+                                game_state = GameState::EventLog;
+                                cursor.hide();
+                                new_event_log(&mut bg_settings, &mut vram, &settings, &event_log);
+                            },
+                            Marks => {
+                                game_state = GameState::Markers;
+                                cursor.hide();
+                                new_marker_list(&mut bg_settings, &mut vram, &settings,
+                                                &graph, &markers, marker_selection);
+                            },
+                            Goto => {
+                                game_state = GameState::Goto;
+                                cursor.hide();
+                                goto_target = (graph.nodes[world_node].x, graph.nodes[world_node].y);
+                                goto_field = 0;
+                                new_goto_dialog(&mut bg_settings, &mut vram, &settings, goto_target, goto_field);
+                            },
+                            Follow => {
+                                settings.follow = !settings.follow;
+                                draw_config_options(&mut bg_settings, &mut vram, &settings);
+                                preferences::save(&mut gba.save, &settings).expect("REASON");
+                            },
+                            // Only Life and the table have a choice of states
+                            States if !matches!(settings.automaton, Automaton::Life | Automaton::Table) => (),
+                            States => {
+                                settings.states = settings.next_states();
+                                settings.state_tiles();
+                                settings.history_depth = history.set_states(settings.states) as u16;
+                                draw_config_options(&mut bg_settings, &mut vram, &settings);
+                                redraw_world(&mut bg, &mut vram, &graph, &settings);
+                            },
+                            Mode => {
+                                settings.set_automaton(settings.automaton.next());
+                                probe = None;
+                                ants.clear();
+                                settings.history_depth = history.set_states(settings.states) as u16;
+                                redraw_world(&mut bg, &mut vram, &graph, &settings);
+                                // Larger than Life has ranges in place of the counts
+                                graph_settings = new_settings_graph(&settings);
+                                new_config_menu(&mut bg_settings, &mut vram, &settings);
+                            },
+                            Ants if settings.automaton != Automaton::Ant => {
+                                show_toast(&mut bg_overlay, &mut vram, "ANT MODE ONLY");
+                                toast_frames = TOAST_FRAMES;
+                            },
+                            // Placed under the board cursor, or taken away if
+                            // one is already there
+                            Ants => {
+                                if let Some(i) = ants.iter().position(|a| a.node == world_node) {
+                                    ants.remove(i);
+                                } else if let Some(paint) = (1..=ANT_COUNT as u16).map(CellState::from)
+                                    .find(|c| ants.iter().all(|a| a.paint != *c)) {
+                                    ants.push(Ant::new(world_node, paint));
+                                } else {
+                                    show_toast(&mut bg_overlay, &mut vram, "NO MORE ANTS");
+                                    toast_frames = TOAST_FRAMES;
+                                }
+                            },
+                            Sample => {
+                                let i = SAMPLE_INTERVALS.iter()
+                                    .position(|n| *n == settings.sample_interval)
+                                    .unwrap_or(0);
+                                settings.sample_interval = SAMPLE_INTERVALS[(i + 1) % SAMPLE_INTERVALS.len()];
+                                draw_config_options(&mut bg_settings, &mut vram, &settings);
+                                preferences::save(&mut gba.save, &settings).expect("REASON");
+                            },
+                            Stats => {
+                                game_state = GameState::Stats;
+                                cursor.hide();
+                                let samples = samples::read_all(&mut gba.save).unwrap_or_default();
+                                new_stats_page(&mut bg_settings, &mut vram, &settings, &graph, &samples, alarm_selection);
+                            },
+                            Paint => {
+                                settings.paint = !settings.paint;
+                                draw_config_options(&mut bg_settings, &mut vram, &settings);
+                                preferences::save(&mut gba.save, &settings).expect("REASON");
+                            },
+                            Highway => {
+                                settings.highway_pause = !settings.highway_pause;
+                                draw_config_options(&mut bg_settings, &mut vram, &settings);
+                                preferences::save(&mut gba.save, &settings).expect("REASON");
+                            },
+                            Ramp => {
+                                settings.ramp = !settings.ramp;
+                                draw_config_options(&mut bg_settings, &mut vram, &settings);
+                                preferences::save(&mut gba.save, &settings).expect("REASON");
+                            },
+                            // Leaves the menu for good, the first show
+                            // starting straight away
+                            Kiosk => {
+                                settings.kiosk = true;
+                                preferences::save(&mut gba.save, &settings).expect("REASON");
+                                game_state = GameState::Paused;
+                                bg_settings.set_visible(false);
+                                cursor.hide();
+                                cursor = &mut cursor_world;
+                                show = SHOWS.len() - 1;
+                                idle_frames = KIOSK_IDLE_FRAMES;
+                                continue;
+                            },
+                            // The rule cells pick the new rules up next frame
+                            Preset => {
+                                (settings.rules, settings.states) = rulestring::from_str(rulestring::PRESETS[settings.preset].1).unwrap();
+                                settings.state_tiles();
+                                settings.history_depth = history.set_states(settings.states) as u16;
+                                draw_config_options(&mut bg_settings, &mut vram, &settings);
+                                redraw_world(&mut bg, &mut vram, &graph, &settings);
+                            },
+                            // Measured from the next generation on
+                            Tools => {
+                                game_state = GameState::Tools;
+                                cursor.hide();
+                                new_tools_page(&mut bg_settings, &mut vram, &settings, tools_selection);
+                            },
+                            Lab => {
+                                game_state = GameState::Lab;
+                                lab_running = false;
+                                clear_rect(&mut bg_settings, &mut vram,
+                                    settings.window_x, settings.window_y, settings.window_width, settings.window_height);
+                                new_lab_window(&mut bg_settings, &mut vram, &lab, &settings);
+                                cursor.hide();
+                                cursor = &mut cursor_lab;
+                                cursor.show();
+                            },
+                            Wolfram if settings.automaton == Automaton::Margolus => {
+                                settings.block_rule = (settings.block_rule + 1) % BLOCK_RULES.len();
+                                draw_config_options(&mut bg_settings, &mut vram, &settings);
+                            },
+                            Wolfram if settings.automaton == Automaton::ForestFire => {
+                                game_state = GameState::Fire;
+                                fire_field = 0;
+                                cursor.hide();
+                                new_fire_dialog(&mut bg_settings, &mut vram, &settings, fire_field);
+                            },
+                            Wolfram if settings.automaton == Automaton::WaTor => {
+                                game_state = GameState::WaTor;
+                                wator_field = 0;
+                                cursor.hide();
+                                new_wator_dialog(&mut bg_settings, &mut vram, &settings, wator_field);
+                            },
+                            Wolfram if settings.automaton == Automaton::Species => {
+                                settings.threshold = settings.threshold % settings.neighborhood.size() + 1;
+                                draw_config_options(&mut bg_settings, &mut vram, &settings);
+                            },
+                            Wolfram if settings.automaton == Automaton::Life => {
+                                game_state = GameState::Weights;
+                                cursor.hide();
+                                new_weights_dialog(&mut bg_settings, &mut vram, &settings, weight_place);
+                            },
+                            Wolfram if settings.automaton == Automaton::Table => {
+                                game_state = GameState::Table;
+                                cursor.hide();
+                                new_table_dialog(&mut bg_settings, &mut vram, &settings, table_place);
+                            },
+                            Wolfram if settings.automaton == Automaton::Anneal => {
+                                game_state = GameState::Temperature;
+                                cursor.hide();
+                                new_temperature_dialog(&mut bg_settings, &mut vram, &settings);
+                            },
+                            Wolfram => {
+                                game_state = GameState::Wolfram;
+                                cursor.hide();
+                                new_wolfram_dialog(&mut bg_settings, &mut vram, &settings);
+                            },
+                            TurmiteRules => {
+                                game_state = GameState::Turmite;
+                                new_turmite_editor(&mut bg_settings, &mut vram, &settings);
+                                cursor.hide();
+                                cursor = &mut cursor_turmite;
+                                cursor.show();
+                            },
+                            Stamps => {
+                                game_state = GameState::Patterns;
+                                cursor.hide();
+                                if settings.automaton == Automaton::Wireworld {
+                                    pattern_selection %= CIRCUITS.len();
+                                }
+                                new_pattern_browser(&mut bg_settings, &mut vram, &settings,
+                                                    &stamps, pattern_selection);
+                            },
+                            Controls => {
+                                game_state = GameState::Controls;
+                                cursor.hide();
+                                new_controls_page(&mut bg_settings, &mut vram, &settings, controls_selection);
                             }
                         },
+                        Cell(_) if settings.automaton == Automaton::LargerThanLife => {
+                            game_state = GameState::Range;
+                            range_row = (n.y-settings.window_y-settings.rules_offset_y) as usize;
+                            range_field = 0;
+                            cursor.hide();
+                            new_range_dialog(&mut bg_settings, &mut vram, &settings, range_row, range_field);
+                        },
                         Cell(s) => {
                             n.state = Cell(!*s);
                             let r = &mut settings.rules
@@ -771,14 +4093,1509 @@ fn main(mut gba: agb::Gba) -> ! {
                                 [(n.x-settings.window_x-settings.rules_offset_x) as usize];
                             *r = !(*r != 0) as u16;
                         }
+                        // Only on the board
+                        Wall | Emitter => (),
                     }
                 }
-            }
-        }
+            },
+            GameState::Memory => {
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::SELECT) {
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                }
+                if input.is_just_pressed(Button::A) {
+                    game_state = GameState::SramView;
+                    let bytes = read_sram(&mut gba.save, sram_offset).expect("REASON");
+                    new_sram_view(&mut bg_settings, &mut vram, &settings, sram_offset, &bytes);
+                }
+                if input.is_just_pressed(Button::L) {
+                    let saved = recording::save(&mut gba.save, &input.recording());
+                    show_toast(&mut bg_overlay, &mut vram,
+                        if saved.is_ok() { "INPUT SAVED" } else { "SAVE FAILED" });
+                    toast_frames = TOAST_FRAMES;
+                }
+                // Played back from the board, paused, with the generator as
+                // it was when the recording began
+                if input.is_just_pressed(Button::R) {
+                    match recording::load(&mut gba.save).expect("REASON") {
+                        Some(r) => {
+                            rng = Prng::new(r.seed);
+                            input.play(r);
+                            game_state = GameState::Paused;
+                            bg_settings.set_visible(false);
+                            cursor.hide();
+                            cursor = &mut cursor_world;
+                            cursor.show();
+                            show_toast(&mut bg_overlay, &mut vram, "REPLAYING");
+                        },
+                        None => show_toast(&mut bg_overlay, &mut vram, "NO INPUT SAVED"),
+                    }
+                    toast_frames = TOAST_FRAMES;
+                }
+            },
+            GameState::SramView => {
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::A) {
+                    game_state = GameState::Memory;
+                    clear_rect(&mut bg_settings, &mut vram, 0, settings.window_y, WIDTH, settings.window_height);
+                    new_memory_report(&mut bg_settings, &mut vram, &settings, &graph, &graph_settings, &history, &last_context);
+                    continue;
+                }
+                // Up and down move a row, left and right a page
+                let page = SRAM_ROW_BYTES * SRAM_PAGE_ROWS;
+                let last = SRAM_SIZE - page;
+                match input.just_pressed_y_tri() {
+                    Tri::Negative => sram_offset = sram_offset.saturating_sub(SRAM_ROW_BYTES),
+                    Tri::Positive => sram_offset = (sram_offset + SRAM_ROW_BYTES).min(last),
+                    _ => ()
+                }
+                match input.just_pressed_x_tri() {
+                    Tri::Negative => sram_offset = sram_offset.saturating_sub(page),
+                    Tri::Positive => sram_offset = (sram_offset + page).min(last),
+                    _ => ()
+                }
+                if input.just_pressed_x_tri() != Tri::Zero || input.just_pressed_y_tri() != Tri::Zero {
+                    let bytes = read_sram(&mut gba.save, sram_offset).expect("REASON");
+                    new_sram_view(&mut bg_settings, &mut vram, &settings, sram_offset, &bytes);
+                }
+            },
+            GameState::Markers => {
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                match input.just_pressed_y_tri() {
+                    Tri::Negative => marker_selection = (marker_selection + MARKER_COUNT - 1) % MARKER_COUNT,
+                    Tri::Positive => marker_selection = (marker_selection + 1) % MARKER_COUNT,
+                    _ => ()
+                }
+                if input.just_pressed_y_tri() != Tri::Zero {
+                    new_marker_list(&mut bg_settings, &mut vram, &settings,
+                                    &graph, &markers, marker_selection);
+                }
+                // Jump the board cursor to the marker and go back to editing
+                if input.is_just_pressed(Button::A) {
+                    if let Some(node) = markers[marker_selection] {
+                        if (settings.rules, settings.states) != config_rules {
+                            event_log.record(generation, LogEntry::Rule(settings.rules, settings.states));
+                        }
+                        game_state = GameState::Paused;
+                        new_config_menu(&mut bg_settings, &mut vram, &settings);
+                        bg_settings.set_visible(false);
+                        cursor = &mut cursor_world;
+                        cursor.set_position(&graph, node);
+                        cursor.show();
+                    }
+                }
+            },
+            GameState::Resume => {
+                if input.is_just_pressed(Button::A) {
+                    if let Some((node, session_generation)) = session {
+                        load_world(&mut gba.save, SESSION_OFFSET + SESSION_HEADER,
+                                   &mut graph, &mut settings, &mut markers, &mut rng).expect("REASON");
+                        redraw_markers(&mut marker_objects, &markers, &graph);
+                        settings.history_depth = history.set_states(settings.states) as u16;
+                        generation = session_generation;
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                        graph_settings = new_settings_graph(&settings);
+                        cursor.set_position(&graph, node);
+                    }
+                }
+                if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::B) {
+                    game_state = GameState::Paused;
+                    bg_settings.set_visible(false);
+                    cursor.show();
+                    session_frames = 0;
+                }
+            },
+            // A or B lets the salute fly
+            GameState::Tribute => {
+                if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::B) {
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    bg_settings.set_visible(false);
+                    game_state = GameState::Running;
+                }
+            },
+            GameState::Goto => {
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                if input.just_pressed_x_tri() != Tri::Zero {
+                    goto_field = 1 - goto_field;
+                }
+                let (value, size) = if goto_field == 0 {
+                    (&mut goto_target.0, WIDTH)
+                } else {
+                    (&mut goto_target.1, HEIGHT)
+                };
+                match input.just_pressed_y_tri() {
+                    Tri::Negative => *value = (*value + 1) % size,
+                    Tri::Positive => *value = (*value + size - 1) % size,
+                    _ => ()
+                }
+                if input.is_just_pressed(Button::R) {
+                    *value = (*value + 10) % size;
+                }
+                if input.is_just_pressed(Button::L) {
+                    *value = (*value + size - 10 % size) % size;
+                }
+                if input.just_pressed_x_tri() != Tri::Zero
+                    || input.just_pressed_y_tri() != Tri::Zero
+                    || input.is_just_pressed(Button::L)
+                    || input.is_just_pressed(Button::R) {
+                    new_goto_dialog(&mut bg_settings, &mut vram, &settings, goto_target, goto_field);
+                }
+                if input.is_just_pressed(Button::A) {
+                    if (settings.rules, settings.states) != config_rules {
+                        event_log.record(generation, LogEntry::Rule(settings.rules, settings.states));
+                    }
+                    game_state = GameState::Paused;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    bg_settings.set_visible(false);
+                    cursor = &mut cursor_world;
+                    cursor.set_position(&graph, (goto_target.1 * WIDTH + goto_target.0).into());
+                    cursor.show();
+                }
+            },
+            GameState::EventLog => {
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    clear_rect(&mut bg_settings, &mut vram, 0, settings.window_y, WIDTH, settings.window_height);
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                }
+            },
+            GameState::Patterns => {
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                let circuits = settings.automaton == Automaton::Wireworld;
+                let count = if circuits { CIRCUITS.len() } else { PATTERNS.len() + STAMP_SLOTS };
+                match input.just_pressed_y_tri() {
+                    Tri::Negative => pattern_selection = (pattern_selection + count - 1) % count,
+                    Tri::Positive => pattern_selection = (pattern_selection + 1) % count,
+                    _ => ()
+                }
 
-        vblank.wait_for_vblank();
-        bg.commit(&mut vram);
-        bg_settings.commit(&mut vram);
-        object.commit();
+                // Components go straight onto the board, there is no lab
+                // bench or code for more than two states
+                if circuits {
+                    if input.just_pressed_y_tri() != Tri::Zero {
+                        new_pattern_browser(&mut bg_settings, &mut vram, &settings,
+                                            &stamps, pattern_selection);
+                    }
+                    if input.is_just_pressed(Button::A) {
+                        history.push(&graph, generation);
+                        let origin = &graph.nodes[world_node];
+                        let (x, y) = (origin.x, origin.y);
+                        place_circuit(&mut graph, WIDTH, CIRCUITS[pattern_selection].1, x, y);
+                        redraw_world(&mut bg, &mut vram, &graph, &settings);
+                        game_state = GameState::Paused;
+                        new_config_menu(&mut bg_settings, &mut vram, &settings);
+                        bg_settings.set_visible(false);
+                        cursor = &mut cursor_world;
+                        cursor.show();
+                    }
+                    continue;
+                }
+                let selected = if pattern_selection < PATTERNS.len() {
+                    Some(Stamp::from(PATTERNS[pattern_selection].1))
+                } else {
+                    stamps[pattern_selection - PATTERNS.len()]
+                };
+
+                // Keep the lab bench in the selected slot
+                if input.is_just_pressed(Button::SELECT) && pattern_selection >= PATTERNS.len() {
+                    if let Some(stamp) = Stamp::from_graph(&lab, LAB_X, LAB_Y) {
+                        let slot = pattern_selection - PATTERNS.len();
+                        save_stamp(&mut gba.save, slot, &stamp).expect("REASON");
+                        stamps[slot] = Some(stamp);
+                    }
+                }
+                if input.just_pressed_y_tri() != Tri::Zero || input.is_just_pressed(Button::SELECT) {
+                    new_pattern_browser(&mut bg_settings, &mut vram, &settings,
+                                        &stamps, pattern_selection);
+                }
+
+                // Edit a copy of the pattern on the lab bench
+                if let (true, Some(stamp)) = (input.is_just_pressed(Button::R), selected) {
+                    for n in &mut lab.nodes {
+                        n.state = Cell(DEAD);
+                        n.age = 0;
+                    }
+                    for j in 0..stamp.height {
+                    for i in 0..stamp.width {
+                        if stamp.is_live(i, j) {
+                            lab.nodes[(j * LAB_SIZE + i) as usize].state = Cell(LIVE);
+                        }
+                    }}
+                    game_state = GameState::Lab;
+                    lab_running = false;
+                    clear_rect(&mut bg_settings, &mut vram,
+                        settings.window_x, settings.window_y, settings.window_width, settings.window_height);
+                    new_lab_window(&mut bg_settings, &mut vram, &lab, &settings);
+                    cursor = &mut cursor_lab;
+                    cursor.show();
+                    continue;
+                }
+
+                // Show the pattern's code, which can then be edited and
+                // loaded into the slot
+                if input.is_just_pressed(Button::L) {
+                    code = vec![0; sharecode::MAX_LENGTH];
+                    if let Some(stamp) = selected {
+                        let encoded = sharecode::encode(&stamp, false);
+                        code[..encoded.len()].copy_from_slice(&encoded);
+                    }
+                    code_position = 0;
+                    game_state = GameState::ShareCode;
+                    new_share_code(&mut bg_settings, &mut vram, &settings, &code, code_position, "");
+                    continue;
+                }
+
+                // Place the pattern at the board cursor and go back to editing
+                if let (true, Some(stamp)) = (input.is_just_pressed(Button::A), selected) {
+                    history.push(&graph, generation);
+                    let origin = &graph.nodes[world_node];
+                    let (x, y) = (origin.x, origin.y);
+                    place(&mut graph, WIDTH, &stamp, x, y);
+                    redraw_world(&mut bg, &mut vram, &graph, &settings);
+
+                    if (settings.rules, settings.states) != config_rules {
+                        event_log.record(generation, LogEntry::Rule(settings.rules, settings.states));
+                    }
+                    game_state = GameState::Paused;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    bg_settings.set_visible(false);
+                    cursor = &mut cursor_world;
+                    cursor.show();
+                }
+            },
+            GameState::ShareCode => {
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Patterns;
+                    new_pattern_browser(&mut bg_settings, &mut vram, &settings,
+                                        &stamps, pattern_selection);
+                    continue;
+                }
+                let length = sharecode::length(&code);
+                match input.just_pressed_x_tri() {
+                    Tri::Negative => code_position = (code_position + length - 1) % length,
+                    Tri::Positive => code_position = (code_position + 1) % length,
+                    _ => ()
+                }
+                match input.just_pressed_y_tri() {
+                    Tri::Negative => code[code_position] = (code[code_position] + 1) % 32,
+                    Tri::Positive => code[code_position] = (code[code_position] + 31) % 32,
+                    _ => ()
+                }
+                // The size may have changed along with the first two characters
+                code_position = code_position.min(sharecode::length(&code) - 1);
+
+                let mut status = "";
+                // SELECT scrambles the code or puts it back as it was
+                if input.is_just_pressed(Button::SELECT) {
+                    let scrambled = !sharecode::is_scrambled(&code);
+                    status = match sharecode::decode(&code) {
+                        Some(stamp) => {
+                            code = vec![0; sharecode::MAX_LENGTH];
+                            let encoded = sharecode::encode(&stamp, scrambled);
+                            code[..encoded.len()].copy_from_slice(&encoded);
+                            if scrambled { "SCRAMBLED" } else { "PLAIN" }
+                        },
+                        None => "BAD CODE",
+                    };
+                }
+                if input.is_just_pressed(Button::A) {
+                    status = match sharecode::decode(&code) {
+                        _ if pattern_selection < PATTERNS.len() => "ROM PATTERN",
+                        Some(stamp) => {
+                            let slot = pattern_selection - PATTERNS.len();
+                            save_stamp(&mut gba.save, slot, &stamp).expect("REASON");
+                            stamps[slot] = Some(stamp);
+                            "SAVED"
+                        },
+                        None => "BAD CODE",
+                    };
+                }
+                if input.just_pressed_x_tri() != Tri::Zero
+                    || input.just_pressed_y_tri() != Tri::Zero
+                    || input.is_just_pressed(Button::A)
+                    || input.is_just_pressed(Button::SELECT) {
+                    new_share_code(&mut bg_settings, &mut vram, &settings, &code, code_position, status);
+                }
+            },
+            GameState::Controls => {
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                    preferences::save(&mut gba.save, &settings).expect("REASON");
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                match input.just_pressed_y_tri() {
+                    Tri::Negative => controls_selection = (controls_selection + CONTROL_ROWS.len() - 1) % CONTROL_ROWS.len(),
+                    Tri::Positive => controls_selection = (controls_selection + 1) % CONTROL_ROWS.len(),
+                    _ => ()
+                }
+                let (value, min, max) = match controls_selection {
+                    0 => (&mut settings.board_repeat.delay, 1, REPEAT_MAX),
+                    1 => (&mut settings.board_repeat.interval, 1, REPEAT_MAX),
+                    2 => (&mut settings.menu_repeat.delay, 1, REPEAT_MAX),
+                    3 => (&mut settings.menu_repeat.interval, 1, REPEAT_MAX),
+                    4 => (&mut settings.turbo_every, 1, REPEAT_MAX),
+                    5 => (&mut settings.echo, 0, ECHO_MAX),
+                    6 => (&mut settings.see_through, 0, SEE_THROUGH_MAX),
+                    7 => (&mut settings.adapt, 0, ADAPT_MAX),
+                    8 => (&mut settings.noise_cells, 0, NOISE_MAX),
+                    _ => (&mut settings.noise_every, 1, NOISE_MAX),
+                };
+                match input.just_pressed_x_tri() {
+                    Tri::Negative => *value = value.saturating_sub(1).max(min),
+                    Tri::Positive => *value = (*value + 1).min(max),
+                    _ => ()
+                }
+                if input.just_pressed_x_tri() != Tri::Zero || input.just_pressed_y_tri() != Tri::Zero {
+                    new_controls_page(&mut bg_settings, &mut vram, &settings, controls_selection);
+                }
+            },
+            GameState::Tools => {
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                match input.just_pressed_y_tri() {
+                    Tri::Negative => tools_selection = (tools_selection + TOOLS.len() - 1) % TOOLS.len(),
+                    Tri::Positive => tools_selection = (tools_selection + 1) % TOOLS.len(),
+                    _ => ()
+                }
+                if input.just_pressed_y_tri() != Tri::Zero {
+                    new_tools_page(&mut bg_settings, &mut vram, &settings, tools_selection);
+                }
+                // Start the tool and go back to the menu, it reports when done
+                if input.is_just_pressed(Button::A) {
+                    match tools_selection {
+                        0 => ship_tracker = ShipTracker::new(&graph),
+                        // The region centered on the board cursor
+                        1 => {
+                            let n = &graph.nodes[world_node];
+                            predecessor_search = Some(PredecessorSearch::new(&graph,
+                                (n.x + WIDTH - PREDECESSOR_SIZE/2) % WIDTH,
+                                (n.y + HEIGHT - PREDECESSOR_SIZE/2) % HEIGHT,
+                                PREDECESSOR_SIZE, PREDECESSOR_SIZE, PREDECESSOR_BUDGET));
+                        },
+                        2 => lifespan_tracker = Some(LifespanTracker::new(&graph, &settings.rules, generation)),
+                        3 => {
+                            game_state = GameState::Until;
+                            until_field = 0;
+                            new_until_dialog(&mut bg_settings, &mut vram, &settings,
+                                             until_goal, until_target, until_every, until_field);
+                            continue;
+                        },
+                        // Started the first time, then played back
+                        4 => match &time_lapse {
+                            Some(lapse) if !lapse.is_empty() => {
+                                game_state = GameState::TimeLapse;
+                                lapse_snapshot = 0;
+                                lapse_frames = 0;
+                                bg_settings.set_visible(false);
+                                draw_snapshot(&mut bg, &mut vram, &settings, lapse, lapse_snapshot);
+                                continue;
+                            },
+                            Some(_) => {
+                                show_toast(&mut bg_overlay, &mut vram, "NOTHING CAPTURED YET");
+                                toast_frames = TOAST_FRAMES;
+                            },
+                            None => {
+                                time_lapse = Some(TimeLapse::new());
+                                show_toast(&mut bg_overlay, &mut vram, "TIME LAPSE STARTED");
+                                toast_frames = TOAST_FRAMES;
+                            },
+                        },
+                        // Shown in place of the board until kept or put back
+                        5 if matches!(settings.automaton, Automaton::Life | Automaton::Immigration | Automaton::Layers) => {
+                            game_state = GameState::TryRule;
+                            bg_settings.set_visible(false);
+                            redraw_world(&mut bg, &mut vram, &try_rule(&graph, &settings, rng), &settings);
+                            show_toast(&mut bg_overlay, &mut vram, &format!("AFTER {} GENERATIONS", TRY_GENERATIONS));
+                            toast_frames = TOAST_FRAMES;
+                            continue;
+                        },
+                        5 => {
+                            show_toast(&mut bg_overlay, &mut vram, "NO RULES TO TRY");
+                            toast_frames = TOAST_FRAMES;
+                        },
+                        // Kept straight away, the grid showing it once back
+                        // in the menu
+                        6 if matches!(settings.automaton, Automaton::Life | Automaton::Immigration | Automaton::Layers) => {
+                            settings.rules = random_rules(&mut rng);
+                            show_toast(&mut bg_overlay, &mut vram, &rulestring::to_string(&settings.rules, settings.states));
+                            toast_frames = TOAST_FRAMES;
+                        },
+                        6 => {
+                            show_toast(&mut bg_overlay, &mut vram, "NO RULES TO SET");
+                            toast_frames = TOAST_FRAMES;
+                        },
+                        _ if settings.automaton != Automaton::Wireworld => {
+                            show_toast(&mut bg_overlay, &mut vram, "WIREWORLD ONLY");
+                            toast_frames = TOAST_FRAMES;
+                        },
+                        // Heads and tails go back to wire, so a circuit can
+                        // be restarted without redrawing it
+                        7 => {
+                            history.push(&graph, generation);
+                            for n in &mut graph.nodes {
+                                if let Cell(LIVE | TAIL) = n.state {
+                                    n.state = Cell(WIRE);
+                                    n.age = 0;
+                                }
+                            }
+                            redraw_world(&mut bg, &mut vram, &graph, &settings);
+                        },
+                        // Placed under the board cursor, or taken away if
+                        // it is already there
+                        _ => {
+                            probe = match probe {
+                                Some(p) if p.node == world_node => None,
+                                _ => Some(SignalProbe::new(world_node)),
+                            };
+                        },
+                    }
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                }
+            },
+            GameState::TimeLapse => {
+                // SELECT throws the capture away and starts a new one
+                let clear = input.is_just_pressed(Button::SELECT);
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) || clear {
+                    if clear {
+                        time_lapse = Some(TimeLapse::new());
+                        show_toast(&mut bg_overlay, &mut vram, "TIME LAPSE STARTED");
+                        toast_frames = TOAST_FRAMES;
+                    }
+                    redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    game_state = GameState::Tools;
+                    bg_settings.set_visible(true);
+                    new_tools_page(&mut bg_settings, &mut vram, &settings, tools_selection);
+                    continue;
+                }
+                // Round and round until left
+                if let Some(lapse) = &time_lapse {
+                    lapse_frames += 1;
+                    if lapse_frames == LAPSE_FRAME_TIME {
+                        lapse_frames = 0;
+                        lapse_snapshot = (lapse_snapshot + 1) % lapse.len();
+                        draw_snapshot(&mut bg, &mut vram, &settings, lapse, lapse_snapshot);
+                        show_toast(&mut bg_overlay, &mut vram,
+                            &format!("GEN {}", lapse.generation(lapse_snapshot)));
+                        toast_frames = TOAST_FRAMES;
+                    }
+                }
+            },
+            // A keeps the rules as edited, B puts back the ones the menu
+            // was opened with
+            GameState::TryRule => {
+                let keep = input.is_just_pressed(Button::A);
+                if keep || input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                    if !keep {
+                        settings.rules = config_rules.0;
+                    }
+                    redraw_world(&mut bg, &mut vram, &graph, &settings);
+                    game_state = GameState::Tools;
+                    bg_settings.set_visible(true);
+                    new_tools_page(&mut bg_settings, &mut vram, &settings, tools_selection);
+                    show_toast(&mut bg_overlay, &mut vram, if keep { "RULE KEPT" } else { "RULE PUT BACK" });
+                    toast_frames = TOAST_FRAMES;
+                }
+            },
+            GameState::Until => {
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Tools;
+                    new_tools_page(&mut bg_settings, &mut vram, &settings, tools_selection);
+                    continue;
+                }
+                // Straight to running, as leaving the menu would
+                if input.is_just_pressed(Button::A) {
+                    if (settings.rules, settings.states) != config_rules {
+                        event_log.record(generation, LogEntry::Rule(settings.rules, settings.states));
+                    }
+                    run_until = Some(RunUntil::new(until_goal, until_target, until_every,
+                                                   &graph, &settings.rules, generation));
+                    game_state = GameState::Running;
+                    bg_settings.set_visible(false);
+                    cursor = &mut cursor_world;
+                    ramp = None;
+                    continue;
+                }
+                match input.just_pressed_y_tri() {
+                    Tri::Negative => until_field = (until_field + 2) % 3,
+                    Tri::Positive => until_field = (until_field + 1) % 3,
+                    _ => ()
+                }
+                // L and R take bigger steps
+                let step: i32 = match input.just_pressed_x_tri() {
+                    Tri::Zero if input.is_just_pressed(Button::L) => -10,
+                    Tri::Zero if input.is_just_pressed(Button::R) => 10,
+                    Tri::Negative => -1,
+                    Tri::Positive => 1,
+                    Tri::Zero => 0,
+                };
+                match until_field {
+                    0 if step != 0 => until_goal = until_goal.next(),
+                    1 => {
+                        let unit = if until_goal == Goal::Generation { 100 } else { 1 };
+                        until_target = until_target.saturating_add_signed(step * unit);
+                    },
+                    2 => until_every = (until_every as i32 + step).clamp(1, UNTIL_EVERY_MAX as i32) as u16,
+                    _ => (),
+                }
+                if step != 0 || input.just_pressed_y_tri() != Tri::Zero {
+                    new_until_dialog(&mut bg_settings, &mut vram, &settings,
+                                     until_goal, until_target, until_every, until_field);
+                }
+            },
+            GameState::Stats => {
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                    preferences::save(&mut gba.save, &settings).expect("REASON");
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                if input.is_just_pressed(Button::SELECT) {
+                    samples::clear(&mut gba.save).expect("REASON");
+                    new_stats_page(&mut bg_settings, &mut vram, &settings, &graph, &[], alarm_selection);
+                }
+                match input.just_pressed_y_tri() {
+                    Tri::Negative => alarm_selection = (alarm_selection + 3) % 4,
+                    Tri::Positive => alarm_selection = (alarm_selection + 1) % 4,
+                    _ => ()
+                }
+                // L and R step the limits by ten
+                let step = match input.just_pressed_x_tri() {
+                    Tri::Zero if input.is_just_pressed(Button::L) => -10,
+                    Tri::Zero if input.is_just_pressed(Button::R) => 10,
+                    Tri::Negative => -1,
+                    Tri::Positive => 1,
+                    Tri::Zero => 0,
+                };
+                let alarms = &mut settings.alarms;
+                match alarm_selection {
+                    0 => alarms.above = alarms.above.saturating_add_signed(step).min(WIDTH * HEIGHT),
+                    1 => alarms.below = alarms.below.saturating_add_signed(step).min(WIDTH * HEIGHT),
+                    2 if step != 0 => alarms.zero = !alarms.zero,
+                    3 if step != 0 => alarms.action = alarms.action.next(),
+                    _ => (),
+                }
+                if step != 0 || input.just_pressed_y_tri() != Tri::Zero {
+                    draw_alarms(&mut bg_settings, &mut vram, &settings, alarm_selection);
+                }
+            },
+            GameState::Range => {
+                if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::B)
+                    || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                if input.just_pressed_x_tri() != Tri::Zero {
+                    range_field = 1 - range_field;
+                }
+                let value = &mut settings.ranges[range_row][range_field];
+                match input.just_pressed_y_tri() {
+                    Tri::Negative => *value = (*value + 1).min(RANGE_NEIGHBORS),
+                    Tri::Positive => *value = value.saturating_sub(1),
+                    _ => ()
+                }
+                if input.just_pressed_x_tri() != Tri::Zero || input.just_pressed_y_tri() != Tri::Zero {
+                    new_range_dialog(&mut bg_settings, &mut vram, &settings, range_row, range_field);
+                }
+            },
+            GameState::Wolfram => {
+                if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::B)
+                    || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                match input.just_pressed_y_tri() {
+                    Tri::Negative => settings.wolfram = settings.wolfram.wrapping_add(1),
+                    Tri::Positive => settings.wolfram = settings.wolfram.wrapping_sub(1),
+                    _ => ()
+                }
+                if input.is_just_pressed(Button::R) {
+                    settings.wolfram = settings.wolfram.wrapping_add(10);
+                }
+                if input.is_just_pressed(Button::L) {
+                    settings.wolfram = settings.wolfram.wrapping_sub(10);
+                }
+                if input.just_pressed_y_tri() != Tri::Zero
+                    || input.is_just_pressed(Button::L)
+                    || input.is_just_pressed(Button::R) {
+                    new_wolfram_dialog(&mut bg_settings, &mut vram, &settings);
+                }
+            },
+            // Tenths with UP and DOWN, whole degrees with L and R
+            GameState::Temperature => {
+                if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::B)
+                    || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                let step: i16 = match input.just_pressed_y_tri() {
+                    Tri::Zero if input.is_just_pressed(Button::L) => -10,
+                    Tri::Zero if input.is_just_pressed(Button::R) => 10,
+                    Tri::Negative => 1,
+                    Tri::Positive => -1,
+                    Tri::Zero => 0,
+                };
+                // LEFT and RIGHT twist the vote
+                let twist = match input.just_pressed_x_tri() {
+                    Tri::Negative => settings.twist.saturating_sub(1),
+                    Tri::Positive => (settings.twist + 1).min(TWIST_MAX),
+                    Tri::Zero => settings.twist,
+                };
+                if step != 0 || twist != settings.twist {
+                    settings.temperature = settings.temperature.saturating_add_signed(step).min(TEMPERATURE_MAX);
+                    settings.twist = twist;
+                    new_temperature_dialog(&mut bg_settings, &mut vram, &settings);
+                }
+            },
+            // The D-pad picks a place, L and R lower and raise its weight and
+            // SELECT sets them all back to even
+            GameState::Weights => {
+                if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::B)
+                    || input.is_just_pressed(Button::START) {
+                    weigh_edges(&mut graph, WIDTH, settings.boundary, settings.neighborhood, &settings.weights);
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                let (column, row) = (weight_place % 3, weight_place / 3);
+                let column = match input.just_pressed_x_tri() {
+                    Tri::Negative => column.saturating_sub(1),
+                    Tri::Positive => (column + 1).min(2),
+                    Tri::Zero => column,
+                };
+                let row = match input.just_pressed_y_tri() {
+                    Tri::Negative => row.saturating_sub(1),
+                    Tri::Positive => (row + 1).min(2),
+                    Tri::Zero => row,
+                };
+                let place = row * 3 + column;
+                let weight = &mut settings.weights[place];
+                let before = *weight;
+                if input.is_just_pressed(Button::L) {
+                    *weight = weight.saturating_sub(1);
+                }
+                if input.is_just_pressed(Button::R) {
+                    *weight = (*weight + 1).min(WEIGHT_MAX);
+                }
+                let changed = *weight != before;
+                if input.is_just_pressed(Button::SELECT) {
+                    settings.weights = EVEN_WEIGHTS;
+                }
+                if place != weight_place || changed || input.is_just_pressed(Button::SELECT) {
+                    weight_place = place;
+                    new_weights_dialog(&mut bg_settings, &mut vram, &settings, weight_place);
+                }
+            },
+            // The D-pad moves about the table, L and R change the state a
+            // place goes to
+            GameState::Table => {
+                if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::B)
+                    || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                let (state, count) = table_place;
+                let state = state.min(settings.states as usize - 1);
+                let count = match input.just_pressed_x_tri() {
+                    Tri::Negative => count.saturating_sub(1),
+                    Tri::Positive => (count + 1).min(8),
+                    Tri::Zero => count,
+                };
+                let state = match input.just_pressed_y_tri() {
+                    Tri::Negative => state.saturating_sub(1),
+                    Tri::Positive => (state + 1).min(settings.states as usize - 1),
+                    Tri::Zero => state,
+                };
+                let next = table::next(&settings.table, settings.states, state, count);
+                let step = match (input.is_just_pressed(Button::L), input.is_just_pressed(Button::R)) {
+                    (true, false) => settings.states - 1,
+                    (false, true) => 1,
+                    _ => 0,
+                };
+                if step != 0 {
+                    settings.table[state][count] = (next + step) % settings.states;
+                }
+                if input.is_just_pressed(Button::SELECT) {
+                    settings.table = table::DEFAULT;
+                }
+                if (state, count) != table_place || step != 0 || input.is_just_pressed(Button::SELECT) {
+                    table_place = (state, count);
+                    new_table_dialog(&mut bg_settings, &mut vram, &settings, table_place);
+                }
+            },
+            // UP and DOWN pick a field, LEFT and RIGHT change it
+            GameState::WaTor => {
+                if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::B)
+                    || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                match input.just_pressed_y_tri() {
+                    Tri::Negative => wator_field = (wator_field + 2) % 3,
+                    Tri::Positive => wator_field = (wator_field + 1) % 3,
+                    Tri::Zero => (),
+                }
+                let rules = &mut settings.wator;
+                let value = match wator_field {
+                    0 => &mut rules.fish_breed,
+                    1 => &mut rules.shark_breed,
+                    _ => &mut rules.starve,
+                };
+                let before = *value;
+                match key_repeat.update(&input, &settings.menu_repeat) {
+                    Some(b) if b == Button::LEFT => *value = (*value - 1).max(1),
+                    Some(b) if b == Button::RIGHT => *value = (*value + 1).min(wator::LIMIT),
+                    _ => (),
+                }
+                if *value != before || input.just_pressed_y_tri() != Tri::Zero {
+                    new_wator_dialog(&mut bg_settings, &mut vram, &settings, wator_field);
+                }
+            },
+            // LEFT and RIGHT slide the chance, L and R halve and double it
+            GameState::Fire => {
+                if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::B)
+                    || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.show();
+                    continue;
+                }
+                if input.just_pressed_y_tri() != Tri::Zero {
+                    fire_field = 1 - fire_field;
+                }
+                let chance = if fire_field == 0 { &mut settings.growth } else { &mut settings.lightning };
+                let raw = chance.to_raw();
+                let next = match key_repeat.update(&input, &settings.menu_repeat) {
+                    Some(b) if b == Button::LEFT => raw.saturating_sub(FIRE_STEP),
+                    Some(b) if b == Button::RIGHT => raw + FIRE_STEP,
+                    _ if input.is_just_pressed(Button::L) => raw / 2,
+                    _ if input.is_just_pressed(Button::R) => (raw * 2).max(1),
+                    _ => raw,
+                }.min(1 << 16);
+                *chance = Chance::from_raw(next);
+                if next != raw || input.just_pressed_y_tri() != Tri::Zero {
+                    new_fire_dialog(&mut bg_settings, &mut vram, &settings, fire_field);
+                }
+            },
+            GameState::Turmite => {
+                if input.is_just_pressed(Button::B) || input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.hide();
+                    cursor = &mut cursor_config;
+                    cursor.set_position(&graph_settings, node_turmite);
+                    cursor.show();
+                    continue;
+                }
+                if let Some(b) = key_repeat.update(&input, &settings.menu_repeat) {
+                    cursor.move_cursor(&turmite_graph, b);
+                }
+                // Each field steps through its values
+                if input.is_just_pressed(Button::A) {
+                    let i = cursor.node;
+                    let t = &mut settings.turmite[i / 6][i / 3 % 2];
+                    match i % 3 {
+                        0 => t.color ^= 1,
+                        1 => t.turn = t.turn.next(),
+                        _ => t.state ^= 1,
+                    }
+                    new_turmite_editor(&mut bg_settings, &mut vram, &settings);
+                }
+                if input.is_just_pressed(Button::SELECT) {
+                    settings.turmite = LANGTONS_ANT;
+                    new_turmite_editor(&mut bg_settings, &mut vram, &settings);
+                }
+            },
+            GameState::Lab => {
+                if input.is_just_pressed(Button::START) {
+                    game_state = GameState::Config;
+                    clear_rect(&mut bg_settings, &mut vram, LAB_X-1, LAB_Y-1, LAB_SIZE+2, LAB_SIZE+2);
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    cursor.hide();
+                    cursor = &mut cursor_config;
+                    cursor.set_position(&graph_settings, node_lab);
+                    cursor.show();
+                    continue;
+                }
+                // Copy the whole bench onto the world at the board cursor
+                if input.is_just_pressed(Button::R) {
+                    history.push(&graph, generation);
+                    let origin = &graph.nodes[world_node];
+                    let (x, y) = (origin.x, origin.y);
+                    for n in &lab.nodes {
+                        let i = x + n.x - LAB_X;
+                        let j = y + n.y - LAB_Y;
+                        let cell = &mut graph.nodes[((j % HEIGHT) * WIDTH + i % WIDTH) as usize];
+                        cell.state = match n.state { Cell(s) => Cell(s), _ => Cell(DEAD) };
+                        cell.age = 0;
+                    }
+                    redraw_world(&mut bg, &mut vram, &graph, &settings);
+
+                    if (settings.rules, settings.states) != config_rules {
+                        event_log.record(generation, LogEntry::Rule(settings.rules, settings.states));
+                    }
+                    game_state = GameState::Paused;
+                    clear_rect(&mut bg_settings, &mut vram, LAB_X-1, LAB_Y-1, LAB_SIZE+2, LAB_SIZE+2);
+                    new_config_menu(&mut bg_settings, &mut vram, &settings);
+                    bg_settings.set_visible(false);
+                    cursor.hide();
+                    cursor = &mut cursor_world;
+                    cursor.show();
+                    continue;
+                }
+                if input.is_just_pressed(Button::B) {
+                    lab_running = !lab_running;
+                    timer.set_enabled(false);
+                    timer.set_enabled(true);
+                }
+                if input.is_just_pressed(Button::SELECT) {
+                    for n in &mut lab.nodes {
+                        n.state = Cell(DEAD);
+                        n.age = 0;
+                    }
+                    redraw_world(&mut bg_settings, &mut vram, &lab, &settings);
+                }
+
+                let tick = lab_running && timer.value() >= settings.speed;
+                if tick {
+                    timer.set_enabled(false);
+                    timer.set_enabled(true);
+                }
+                if tick || (!lab_running && input.is_just_pressed(Button::L)) {
+                    step_graph(&mut lab, &settings, LAB_SIZE, Boundary::DeadEdge, &mut rng);
+                    redraw_world(&mut bg_settings, &mut vram, &lab, &settings);
+                }
+
+                if let Some(b) = key_repeat.update(&input, &settings.board_repeat) {
+                    cursor.move_cursor(&lab, b);
+                }
+                if input.is_just_pressed(Button::A) {
+                    let n = &mut lab.nodes[cursor.node];
+                    if let Cell(s) = n.state {
+                        n.state = Cell(!s);
+                        n.age = 0;
+                    }
+                    redraw_world(&mut bg_settings, &mut vram, &lab, &settings);
+                }
+            }
+        }
+
+        // Just right of the board cursor, hidden once it leaves the board
+        let drawing = game_state == GameState::Paused && settings.automaton == Automaton::Wireworld;
+        for (i, material_object) in material_objects.iter_mut().enumerate() {
+            if drawing && i == material {
+                let n = &graph.nodes[cursor.node];
+                material_object.set_x(((n.x + 1) % WIDTH) * TILE_SIZE)
+                               .set_y(n.y * TILE_SIZE)
+                               .show();
+            } else {
+                material_object.hide();
+            }
+        }
+
+        for (i, ant_object) in ant_objects.iter_mut().enumerate() {
+            match ants.get(i) {
+                Some(ant) => {
+                    let n = &graph.nodes[ant.node];
+                    ant_object.set_sprite(object.sprite(ANT_SPRITE.sprite(ant.heading)))
+                              .set_x(n.x * TILE_SIZE)
+                              .set_y(n.y * TILE_SIZE)
+                              .show();
+                },
+                None => {
+                    ant_object.hide();
+                }
+            }
+        }
+
+        match &probe {
+            Some(p) => {
+                let n = &graph.nodes[p.node];
+                probe_object.set_x(n.x * TILE_SIZE)
+                            .set_y(n.y * TILE_SIZE)
+                            .show();
+            },
+            None => {
+                probe_object.hide();
+            }
+        }
+
+        // Generations between the drawn ones run without waiting for a frame
+        if !frame_due {
+            continue;
+        }
+
+        // Time lapses draw their snapshots over the board
+        let layered = settings.automaton == Automaton::Layers && game_state != GameState::TimeLapse;
+        let echo = settings.echo > 0 && game_state != GameState::TimeLapse && !layered;
+        if layered {
+            draw_upper(&mut bg_echo, &mut vram, &graph, &upper);
+            bg_echo.set_scroll_pos((0, 0));
+        } else if echo {
+            redraw_world(&mut bg_echo, &mut vram, &graph, &settings);
+            bg_echo.set_scroll_pos((-(settings.echo as i16), -(settings.echo as i16)));
+        }
+        bg_echo.set_visible(echo || layered);
+        // The menu shares the echo's weights while it is up
+        let see_through = settings.see_through > 0 && bg_settings.is_visible();
+        let weight = match (see_through, layered) {
+            (true, _) => 16 - settings.see_through as u8,
+            (false, true) => LAYER_WEIGHT,
+            (false, false) => ECHO_WEIGHT,
+        };
+        blend.set_background_enable(Layer::Top, bg_settings.background(), see_through)
+             .set_blend_weight(Layer::Top, Num::from_raw(weight))
+             .set_blend_weight(Layer::Bottom, Num::from_raw(16 - weight))
+             .commit();
+
+        vblank.wait_for_vblank();
+        paced_frames = paced_frames.saturating_add(1);
+        bg.commit(&mut vram);
+        bg_settings.commit(&mut vram);
+        bg_overlay.commit(&mut vram);
+        bg_echo.commit(&mut vram);
+        object.commit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use automata::active_region;
+
+    fn opposite(d: Direction) -> Direction {
+        match d {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    fn edges(graph: &Graph, node: NodeIndex) -> Vec<(Direction, NodeIndex)> {
+        let mut edges = Vec::new();
+        let mut maybe_edge = graph.nodes[node].first_outgoing_edge;
+        while let Some(e) = maybe_edge {
+            if let Some(b) = graph.edges[e].direction {
+                edges.push((b, graph.edges[e].target));
+            }
+            maybe_edge = graph.edges[e].next_outgoing_edge;
+        }
+        edges
+    }
+
+    #[test_case]
+    fn every_menu_node_is_reachable(_gba: &mut agb::Gba) {
+        let graph = new_settings_graph(&Settings::new());
+        let mut seen = vec![false; graph.nodes.len()];
+        // The cursor starts on NEW
+        let mut stack = vec![18];
+        while let Some(node) = stack.pop() {
+            if !seen[node] {
+                seen[node] = true;
+                stack.extend(edges(&graph, node).into_iter().map(|(_, n)| n));
+            }
+        }
+        assert!(seen.iter().all(|s| *s));
+    }
+
+    #[test_case]
+    fn one_edge_per_direction(_gba: &mut agb::Gba) {
+        let graph = new_settings_graph(&Settings::new());
+        for node in 0..graph.nodes.len() {
+            let directions: Vec<Direction> = edges(&graph, node).into_iter().map(|(d, _)| d).collect();
+            for (i, d) in directions.iter().enumerate() {
+                assert!(!directions[i+1..].contains(d));
+            }
+        }
+    }
+
+    // The rules grid drops every column onto NEW, so from there only the
+    // way back has to exist. Between menu entries moves undo exactly.
+    #[test_case]
+    fn every_move_can_be_undone(_gba: &mut agb::Gba) {
+        let graph = new_settings_graph(&Settings::new());
+        for node in 0..graph.nodes.len() {
+            for (b, target) in edges(&graph, node) {
+                let back = edges(&graph, target);
+                assert!(back.iter().any(|(d, _)| *d == opposite(b)));
+                if let (Menu(_), Menu(_)) = (&graph.nodes[node].state, &graph.nodes[target].state) {
+                    assert!(back.contains(&(opposite(b), node)));
+                }
+            }
+        }
+    }
+
+    // A live cell counts for the weight of the place it is in around each
+    // neighbor, stand-ins and the cell itself included
+    #[test_case]
+    fn neighbors_count_for_their_weight(_gba: &mut agb::Gba) {
+        let mut settings = Settings::new();
+        settings.weights = [0, 0, 0, 0, 1, 0, 0, 0, 3];
+        let mut graph = new_board(&settings);
+        let at = |x: u16, y: u16| (y * WIDTH + x) as NodeIndex;
+        graph.nodes[at(4, 4)].state = Cell(LIVE);
+        assert_eq!(settings.neighbors(&graph, at(3, 3), WIDTH, settings.boundary), 3);
+        assert_eq!(settings.neighbors(&graph, at(5, 5), WIDTH, settings.boundary), 0);
+        assert_eq!(settings.neighbors(&graph, at(4, 4), WIDTH, settings.boundary), 1);
+
+        settings.boundary = Boundary::Reflective;
+        settings.weights = [2, 0, 0, 0, 0, 0, 0, 0, 0];
+        let mut graph = new_board(&settings);
+        graph.nodes[0].state = Cell(LIVE);
+        assert_eq!(settings.neighbors(&graph, 0, WIDTH, settings.boundary), 2);
+        assert_eq!(settings.neighbors(&graph, at(1, 1), WIDTH, settings.boundary), 2);
+        assert_eq!(settings.neighbors(&graph, at(1, 0), WIDTH, settings.boundary), 2);
+    }
+
+    // Every kiosk show starts with something on the board
+    #[test_case]
+    fn shows_start_populated(_gba: &mut agb::Gba) {
+        let mut settings = Settings::new();
+        let mut graph = new_world(WIDTH, HEIGHT, Boundary::Toroidal, Neighborhood::Moore);
+        let mut rng = Prng::new(DEFAULT_SEED);
+        for show in &SHOWS {
+            start_show(&mut graph, &mut settings, show, &mut rng);
+            assert!(graph.nodes.iter().any(|n| matches!(n.state, Cell(LIVE))));
+            assert_eq!(settings.automaton, Automaton::Life);
+        }
+    }
+
+    // From a generation a second to full speed, always getting faster
+    #[test_case]
+    fn ramp_reaches_full_speed(_gba: &mut agb::Gba) {
+        let speed = Settings::new().speed;
+        assert_eq!(ramp_interval(speed, 0), TIMER_SECOND as u16);
+        assert_eq!(ramp_interval(speed, RAMP_TICKS), speed);
+        assert_eq!(ramp_interval(speed, 2 * RAMP_TICKS), speed);
+        for elapsed in (TIMER_SECOND..RAMP_TICKS).step_by(TIMER_SECOND as usize) {
+            assert!(ramp_interval(speed, elapsed) < ramp_interval(speed, elapsed - TIMER_SECOND));
+        }
+    }
+
+    // Only screens opened from another in the menu have a trail
+    #[test_case]
+    fn breadcrumbs_lead_back_to_the_menu(_gba: &mut agb::Gba) {
+        assert_eq!(GameState::TimeLapse.breadcrumb().as_deref(), Some("CONFIG>TOOLS>LAPSE"));
+        assert_eq!(GameState::SramView.breadcrumb().as_deref(), Some("CONFIG>MEMORY>SRAM"));
+        assert_eq!(GameState::Fire.breadcrumb().as_deref(), Some("CONFIG>FIRE"));
+        assert_eq!(GameState::Config.breadcrumb(), None);
+        assert_eq!(GameState::Running.breadcrumb(), None);
+    }
+
+    // Frozen votes are the majority presets, twisted or not
+    #[test_case]
+    fn votes_match_the_majority_presets(_gba: &mut agb::Gba) {
+        let mut rng = Prng::new(DEFAULT_SEED);
+        for (twist, preset) in [(0, "B5678/S45678"), (1, "B4678/S35678")] {
+            let mut vote = Settings::new();
+            vote.set_automaton(Automaton::Anneal);
+            vote.temperature = 0;
+            vote.twist = twist;
+            let mut life = Settings::new();
+            life.rules = rulestring::from_str(preset).unwrap().0;
+            for state in [DEAD, LIVE] {
+                for neighbors in 0..=8 {
+                    assert_eq!(vote.next_state(state, neighbors, &mut rng),
+                               life.next_state(state, neighbors, &mut rng));
+                }
+            }
+        }
+    }
+
+    // A cell past the live ones, the whole way across where that reaches
+    // round a torus, and everything when cells are born from nothing
+    #[test_case]
+    fn active_region_reaches_past_live_cells(_gba: &mut agb::Gba) {
+        let mut settings = Settings::new();
+        let mut graph: Graph = new_world(WIDTH, HEIGHT, Boundary::Toroidal, Neighborhood::Moore);
+        assert_eq!(active_region(&graph, &settings, WIDTH, Boundary::Toroidal), Some((0, 0, -1, -1)));
+        graph.nodes[(5 * WIDTH + 5) as usize].state = Cell(LIVE);
+        graph.nodes[(7 * WIDTH) as usize].state = Cell(LIVE);
+        assert_eq!(active_region(&graph, &settings, WIDTH, Boundary::Toroidal),
+                   Some((0, 4, WIDTH as i32 - 1, 8)));
+        assert_eq!(active_region(&graph, &settings, WIDTH, Boundary::DeadEdge), Some((-1, 4, 6, 8)));
+        settings.rules[0][0] = 1;
+        assert_eq!(active_region(&graph, &settings, WIDTH, Boundary::DeadEdge), None);
+    }
+
+    // Moved only once a cell comes near an edge, then to the middle
+    #[test_case]
+    fn plane_centers_cells_near_an_edge(_gba: &mut agb::Gba) {
+        let mut graph = new_world(WIDTH, HEIGHT, Boundary::DeadEdge, Neighborhood::Moore);
+        assert_eq!(plane_shift(&graph, Neighborhood::Moore), (0, 0));
+        graph.nodes[(5 * WIDTH + 5) as usize].state = Cell(LIVE);
+        assert_eq!(plane_shift(&graph, Neighborhood::Moore), (0, 0));
+        graph.nodes[(6 * WIDTH + 1) as usize].state = Cell(LIVE);
+        let (dx, dy) = plane_shift(&graph, Neighborhood::Moore);
+        assert_eq!((dx, dy), ((WIDTH as i32 - 1 - 6) / 2, (HEIGHT as i32 - 1 - 11) / 2));
+        assert_eq!(plane_shift(&graph, Neighborhood::Hex).1 % 2, 0);
+    }
+
+    // Only cells within reach of the cursor are seeded, and only dead ones
+    #[test_case]
+    fn gravity_wells_seed_near_the_cursor(_gba: &mut agb::Gba) {
+        let mut graph = new_world(WIDTH, HEIGHT, Boundary::DeadEdge, Neighborhood::Moore);
+        let mut rng = Prng::new(DEFAULT_SEED);
+        let center = (10 * WIDTH + 15) as usize;
+        let mut seeded = Vec::new();
+        for _ in 0..50 {
+            seeded.extend(gravity_well(&mut graph, center, &mut rng));
+        }
+        assert!(!seeded.is_empty());
+        for i in seeded {
+            let (dx, dy) = (graph.nodes[i].x as i32 - 15, graph.nodes[i].y as i32 - 10);
+            assert!(dx * dx + dy * dy <= WELL_RADIUS * WELL_RADIUS);
+        }
+        let live = graph.nodes.iter().filter(|n| matches!(n.state, Cell(LIVE))).count();
+        assert!(live <= 29);
+    }
+
+    // Quiet boards fast forward and busy ones slow down, the more so the
+    // more sensitive
+    #[test_case]
+    fn busy_generations_take_longer(_gba: &mut agb::Gba) {
+        assert_eq!(adaptive_interval(4000, 500, 0), 4000);
+        assert_eq!(adaptive_interval(4000, 0, 1), 1000);
+        assert_eq!(adaptive_interval(4000, ADAPT_CELLS as u16 * 3, 1), 4000);
+        assert!(adaptive_interval(4000, 40, 4) > adaptive_interval(4000, 40, 1));
+        assert_eq!(adaptive_interval(4000, 600, ADAPT_MAX), 16000);
+    }
+
+    // Only dead cells are brought to life, walls and all else left alone
+    #[test_case]
+    fn noise_brings_dead_cells_to_life(_gba: &mut agb::Gba) {
+        let mut graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        let mut rng = Prng::new(DEFAULT_SEED);
+        for n in graph.nodes.iter_mut().take(32) {
+            n.state = Wall;
+        }
+        let seeded = inject_noise(&mut graph, 20, &mut rng);
+        assert!(!seeded.is_empty() && seeded.len() <= 20);
+        assert!(seeded.iter().all(|i| *i >= 32));
+        assert_eq!(population(&graph) as usize, seeded.len());
+    }
+
+    // Rules picked at random are all ones and zeros, never born from nothing
+    #[test_case]
+    fn random_rules_never_birth_on_no_neighbors(_gba: &mut agb::Gba) {
+        let mut rng = Prng::new(DEFAULT_SEED);
+        for _ in 0..20 {
+            let rules = random_rules(&mut rng);
+            assert_eq!(rules[0][0], 0);
+            assert!(rules.iter().flatten().all(|r| *r <= 1));
+        }
+    }
+
+    // A wrong button starts the code over, counting as its start if it can
+    #[test_case]
+    fn the_tribute_code_takes_every_button_in_order(_gba: &mut agb::Gba) {
+        let mut progress = 0;
+        for b in [Button::UP, Button::UP, Button::DOWN, Button::UP] {
+            progress = code_progress(progress, b);
+        }
+        assert_eq!(progress, 1);
+        for b in &TRIBUTE_CODE[1..] {
+            progress = code_progress(progress, *b);
+        }
+        assert_eq!(progress, TRIBUTE_CODE.len());
+    }
+
+    // The heap measured free takes in at least its largest block
+    #[test_case]
+    fn free_heap_holds_the_largest_block(_gba: &mut agb::Gba) {
+        let largest = largest_free_block();
+        assert!(largest > 0 && largest <= EWRAM_SIZE);
+        assert!(free_heap() >= largest);
+    }
+
+    // About sixty frames to a second, and never none at all
+    #[test_case]
+    fn frames_stand_in_for_timer_ticks(_gba: &mut agb::Gba) {
+        assert_eq!(interval_frames(TIMER_SECOND as u16), 60);
+        assert_eq!(interval_frames(FRAME_TICKS as u16), 1);
+        assert_eq!(interval_frames(1), 1);
+        assert_eq!(interval_frames(0), 1);
+    }
+
+    #[test_case]
+    fn turmite_moves_can_be_undone(_gba: &mut agb::Gba) {
+        let graph = new_turmite_graph(&Settings::new());
+        for node in 0..graph.nodes.len() {
+            for (b, target) in edges(&graph, node) {
+                assert!(edges(&graph, target).contains(&(opposite(b), node)));
+            }
+        }
+    }
+
+    // Board hashes after 64 generations from a board filled by the default
+    // seed, first wrapping and then with dead edges. Life is the rule a new
+    // cart starts with, the others reach the birth and survival counts it
+    // leaves unused.
+    const GOLDEN_GENERATIONS: u32 = 64;
+    const GOLDEN: [(&str, [[u16; 9]; 2], u32, u32); 4] = [
+        ("LIFE",      [[0,0,0,1,0,0,0,0,0], [0,0,1,1,0,0,0,0,0]], 0x0052_43A4, 0xB8CA_F0E9),
+        ("HIGHLIFE",  [[0,0,0,1,0,0,1,0,0], [0,0,1,1,0,0,0,0,0]], 0xDA90_B4A1, 0x9E14_6ABC),
+        ("SEEDS",     [[0,0,1,0,0,0,0,0,0], [0,0,0,0,0,0,0,0,0]], 0x0F51_EF8F, 0xB4EA_42D7),
+        ("DAY&NIGHT", [[0,0,0,1,0,0,1,1,1], [0,0,0,1,1,0,1,1,1]], 0x1F40_B1DD, 0x19DB_8D55),
+    ];
+
+    fn golden_hash(rules: [[u16; 9]; 2], boundary: Boundary) -> u32 {
+        let mut settings = Settings::new();
+        settings.rules = rules;
+        settings.boundary = boundary;
+        let mut graph = new_world(WIDTH, HEIGHT, boundary, Neighborhood::Moore);
+        let mut rng = Prng::new(DEFAULT_SEED);
+        for n in graph.nodes.iter_mut() {
+            n.state = Cell(if rng.below(2) == 1 { LIVE } else { DEAD });
+        }
+        for _ in 0..GOLDEN_GENERATIONS {
+            step_graph(&mut graph, &settings, WIDTH, boundary, &mut rng);
+        }
+        board_hash(&graph, &settings.rules)
+    }
+
+    #[test_case]
+    fn default_rules_are_life(_gba: &mut agb::Gba) {
+        assert_eq!(Settings::new().rules, GOLDEN[0].1);
+    }
+
+    #[test_case]
+    fn golden_boards(_gba: &mut agb::Gba) {
+        for (name, rules, toroidal, dead_edge) in GOLDEN {
+            assert_eq!(golden_hash(rules, Boundary::Toroidal), toroidal, "{}", name);
+            assert_eq!(golden_hash(rules, Boundary::DeadEdge), dead_edge, "{}", name);
+        }
+    }
+
+    // An even chance of birth with no neighbors fills about half a blank
+    // board, the same half for the same seed
+    #[test_case]
+    fn chance_rules_follow_the_generator(_gba: &mut agb::Gba) {
+        let mut settings = Settings::new();
+        settings.rules[0][0] = CHANCE_ONE / 2;
+        let board = |seed| {
+            let mut graph: Graph = new_world(WIDTH, HEIGHT, Boundary::Toroidal, Neighborhood::Moore);
+            step_graph(&mut graph, &settings, WIDTH, Boundary::Toroidal, &mut Prng::new(seed));
+            graph.nodes.iter().map(|n| matches!(n.state, Cell(LIVE))).collect::<Vec<bool>>()
+        };
+        let live = board(1).iter().filter(|l| **l).count();
+        assert!((200..400).contains(&live));
+        assert_eq!(board(1), board(1));
+        assert_ne!(board(1), board(2));
+    }
+
+    // Working back from each pair of generations lands on the soup the run
+    // started from
+    #[test_case]
+    fn second_order_runs_backwards(_gba: &mut agb::Gba) {
+        let mut settings = Settings::new();
+        settings.second_order = true;
+        let mut rng = Prng::new(DEFAULT_SEED);
+        let mut graph = new_world(WIDTH, HEIGHT, Boundary::Toroidal, Neighborhood::Moore);
+        for n in &mut graph.nodes {
+            n.state = Cell(if rng.below(2) == 0 { LIVE } else { DEAD });
+        }
+        let live = |graph: &Graph| graph.nodes.iter().map(|n| matches!(n.state, Cell(LIVE))).collect::<Vec<bool>>();
+        let start = live(&graph);
+        let mut previous = vec![false; start.len()];
+        for _ in 0..20 {
+            let before = live(&graph);
+            step_graph(&mut graph, &settings, WIDTH, Boundary::Toroidal, &mut rng);
+            for (n, was_live) in graph.nodes.iter_mut().zip(&previous) {
+                if let Cell(s) = n.state {
+                    n.state = Cell(second_order_flip(s, *was_live));
+                }
+            }
+            previous = before;
+        }
+        for _ in 0..20 {
+            let after = live(&graph);
+            for (n, was_live) in graph.nodes.iter_mut().zip(&previous) {
+                n.state = Cell(if *was_live { LIVE } else { DEAD });
+            }
+            previous = second_order_previous(&graph, &after, &settings, &mut rng);
+        }
+        assert_eq!(live(&graph), start);
+    }
+
+    // Trying a rule leaves the board as it was and lands where running it
+    // would
+    #[test_case]
+    fn tried_rules_leave_the_board(_gba: &mut agb::Gba) {
+        let settings = Settings::new();
+        let mut graph = new_world(WIDTH, HEIGHT, Boundary::Toroidal, Neighborhood::Moore);
+        for i in [1, WIDTH + 2, 2 * WIDTH, 2 * WIDTH + 1, 2 * WIDTH + 2] {
+            graph.nodes[i as usize].state = Cell(LIVE);
+        }
+        let live = |graph: &Graph| graph.nodes.iter().map(|n| matches!(n.state, Cell(LIVE))).collect::<Vec<bool>>();
+        let start = live(&graph);
+        let tried = try_rule(&graph, &settings, Prng::new(DEFAULT_SEED));
+        assert_eq!(live(&graph), start);
+        for _ in 0..TRY_GENERATIONS {
+            step_graph(&mut graph, &settings, WIDTH, Boundary::Toroidal, &mut Prng::new(DEFAULT_SEED));
+        }
+        assert_eq!(live(&tried), live(&graph));
+        assert_ne!(live(&tried), start);
+    }
+
+    // Survivors keep their color and births take the majority's
+    #[test_case]
+    fn immigrants_take_the_majority_color(_gba: &mut agb::Gba) {
+        let mut settings = Settings::new();
+        settings.set_automaton(Automaton::Immigration);
+        let mut graph: Graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        graph.nodes[1].state = Cell(RED);
+        graph.nodes[8].state = Cell(RED);
+        graph.nodes[10].state = Cell(LIVE);
+        step_graph(&mut graph, &settings, 8, Boundary::Toroidal, &mut Prng::new(DEFAULT_SEED));
+        assert!(matches!(graph.nodes[1].state, Cell(RED)));
+        assert!(matches!(graph.nodes[9].state, Cell(RED)));
+    }
+
+    // The table's state count goes round every row it has
+    #[test_case]
+    fn table_states_go_up_to_its_rows(_gba: &mut agb::Gba) {
+        let mut settings = Settings::new();
+        settings.set_automaton(Automaton::Table);
+        let mut seen = Vec::new();
+        for _ in 0..table::MAX - 1 {
+            settings.states = settings.next_states();
+            seen.push(settings.states);
+        }
+        seen.sort();
+        assert_eq!(seen, (2..=table::MAX as u8).collect::<Vec<_>>());
+    }
+
+    // Ground a color has left is still marked as its own
+    #[test_case]
+    fn territory_remembers_the_last_color(_gba: &mut agb::Gba) {
+        let mut settings = Settings::new();
+        settings.set_automaton(Automaton::Immigration);
+        let mut graph: Graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        for i in [9, 10, 11] {
+            graph.nodes[i].state = Cell(RED);
+        }
+        step_graph(&mut graph, &settings, 8, Boundary::Toroidal, &mut Prng::new(DEFAULT_SEED));
+        assert!(matches!(graph.nodes[9].state, Cell(DEAD)));
+        assert_eq!(graph.nodes[9].owner, RED.0);
+        assert_eq!(graph.nodes[2].owner, RED.0);
+        assert_eq!(graph.nodes[0].owner, 0);
+    }
+
+    // A wall stays put and counts for nothing, so the corner beside it sees
+    // only two live neighbors
+    #[test_case]
+    fn walls_never_change(_gba: &mut agb::Gba) {
+        let settings = Settings::new();
+        let mut graph: Graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        graph.nodes[9].state = Wall;
+        for i in [1, 8] {
+            graph.nodes[i].state = Cell(LIVE);
+        }
+        assert_eq!(graph.living_neighbors_count_of(0), 2);
+        step_graph(&mut graph, &settings, 8, Boundary::Toroidal, &mut Prng::new(DEFAULT_SEED));
+        assert!(matches!(graph.nodes[9].state, Wall));
+        assert!(matches!(graph.nodes[0].state, Cell(DEAD)));
+    }
+
+    // Walls and emitters come back from a save as themselves rather than
+    // dead cells
+    #[test_case]
+    fn fixed_cells_are_saved(_gba: &mut agb::Gba) {
+        for state in [Cell(DEAD), Cell(LIVE), Cell(CellState(5)), Wall, Emitter] {
+            assert_eq!(byte_node(node_byte(state)), state);
+        }
+    }
+
+    // An emitter lives on alone, and counts toward births beside it
+    #[test_case]
+    fn emitters_stay_alive(_gba: &mut agb::Gba) {
+        let settings = Settings::new();
+        let mut graph: Graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        graph.nodes[9].state = Emitter;
+        for i in [1, 8] {
+            graph.nodes[i].state = Cell(LIVE);
+        }
+        step_graph(&mut graph, &settings, 8, Boundary::Toroidal, &mut Prng::new(DEFAULT_SEED));
+        assert!(matches!(graph.nodes[9].state, Emitter));
+        assert!(matches!(graph.nodes[0].state, Cell(LIVE)));
+        step_graph(&mut graph, &settings, 8, Boundary::Toroidal, &mut Prng::new(DEFAULT_SEED));
+        assert!(matches!(graph.nodes[9].state, Emitter));
+    }
+
+    // A block would last forever, but not past its lifespan
+    #[test_case]
+    fn cells_die_of_old_age(_gba: &mut agb::Gba) {
+        let mut settings = Settings::new();
+        settings.lifespan = 3;
+        let mut graph = new_world(8, 8, Boundary::Toroidal, Neighborhood::Moore);
+        for i in [9, 10, 17, 18] {
+            graph.nodes[i].state = Cell(LIVE);
+        }
+        let mut rng = Prng::new(DEFAULT_SEED);
+        for _ in 1..settings.lifespan {
+            step_graph(&mut graph, &settings, 8, Boundary::Toroidal, &mut rng);
+        }
+        assert_eq!(population(&graph), 4);
+        step_graph(&mut graph, &settings, 8, Boundary::Toroidal, &mut rng);
+        assert_eq!(population(&graph), 0);
+    }
+
+    #[test_case]
+    fn runs_until_the_goal(_gba: &mut agb::Gba) {
+        let settings = Settings::new();
+        let mut rng = Prng::new(DEFAULT_SEED);
+        let mut graph = new_world(WIDTH, HEIGHT, Boundary::Toroidal, Neighborhood::Moore);
+        for x in 4..7 {
+            graph.nodes[(4 * WIDTH + x) as usize].state = Cell(LIVE);
+        }
+        let mut until = RunUntil::new(Goal::Stable, 0, 1, &graph, &settings.rules, 0);
+        let mut reports = Vec::new();
+        for generation in 1..=2 {
+            step_graph(&mut graph, &settings, WIDTH, Boundary::Toroidal, &mut rng);
+            reports.push(until.step(&graph, &settings.rules, generation));
+        }
+        assert_eq!(reports, [None, Some(String::from("SETTLED AFTER 0 POP 3"))]);
+
+        let mut until = RunUntil::new(Goal::Below, 3, 1, &graph, &settings.rules, 2);
+        graph.nodes[(4 * WIDTH + 5) as usize].state = Cell(DEAD);
+        step_graph(&mut graph, &settings, WIDTH, Boundary::Toroidal, &mut rng);
+        assert_eq!(until.step(&graph, &settings.rules, 3), Some(String::from("POP 0 AT GEN 3")));
     }
 }
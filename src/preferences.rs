@@ -0,0 +1,215 @@
+use::alloc::vec;
+use::alloc::vec::Vec;
+
+use agb::save::{SaveManager, Error};
+
+use crate::{Settings, Boundary, Neighborhood, Pacing};
+use crate::alarm::AlarmAction;
+
+// Options are kept in SRAM after the population samples. A chunk holds a
+// magic byte, its version and then that version's fields, so older chunks
+// can be brought up to date rather than thrown away.
+const PREFERENCES_OFFSET: usize = 4096;
+const PREFERENCES_MAGIC: u8 = b'C';
+const HEADER_BYTES: usize = 2;
+
+pub const VERSION: u8 = 14;
+
+// Field bytes of each version, starting with version 1
+const SIZES: [usize; VERSION as usize] = [4, 11, 12, 13, 14, 15, 21, 22, 23, 24, 25, 26, 27, 29];
+
+// Each adds the fields of the next version using the defaults, so new
+// options start out as they would on a fresh cart
+const MIGRATIONS: [fn(&mut Vec<u8>, &Settings); VERSION as usize - 1] = [
+    migrate_1_to_2,
+    migrate_2_to_3,
+    migrate_3_to_4,
+    migrate_4_to_5,
+    migrate_5_to_6,
+    migrate_6_to_7,
+    migrate_7_to_8,
+    migrate_8_to_9,
+    migrate_9_to_10,
+    migrate_10_to_11,
+    migrate_11_to_12,
+    migrate_12_to_13,
+    migrate_13_to_14,
+];
+
+// Version 1 had the history depth, edge and follow options
+fn migrate_1_to_2(fields: &mut Vec<u8>, defaults: &Settings) {
+    fields.push(defaults.paint as u8);
+    fields.extend_from_slice(&defaults.sample_interval.to_le_bytes());
+    fields.push(defaults.board_repeat.delay as u8);
+    fields.push(defaults.board_repeat.interval as u8);
+    fields.push(defaults.menu_repeat.delay as u8);
+    fields.push(defaults.menu_repeat.interval as u8);
+}
+
+fn migrate_2_to_3(fields: &mut Vec<u8>, defaults: &Settings) {
+    fields.push(match defaults.neighborhood {
+        Neighborhood::Moore => 0,
+        Neighborhood::Hex => 1,
+        Neighborhood::VonNeumann => 2,
+    });
+}
+
+fn migrate_3_to_4(fields: &mut Vec<u8>, defaults: &Settings) {
+    fields.push(defaults.highway_pause as u8);
+}
+
+fn migrate_4_to_5(fields: &mut Vec<u8>, defaults: &Settings) {
+    fields.push(defaults.ramp as u8);
+}
+
+fn migrate_5_to_6(fields: &mut Vec<u8>, defaults: &Settings) {
+    fields.push(defaults.kiosk as u8);
+}
+
+fn migrate_6_to_7(fields: &mut Vec<u8>, defaults: &Settings) {
+    encode_alarms(fields, defaults);
+}
+
+fn migrate_7_to_8(fields: &mut Vec<u8>, defaults: &Settings) {
+    fields.push(defaults.turbo_every as u8);
+}
+
+fn migrate_8_to_9(fields: &mut Vec<u8>, defaults: &Settings) {
+    fields.push(defaults.echo as u8);
+}
+
+fn migrate_9_to_10(fields: &mut Vec<u8>, defaults: &Settings) {
+    fields.push(defaults.see_through as u8);
+}
+
+fn migrate_10_to_11(fields: &mut Vec<u8>, defaults: &Settings) {
+    fields.push((defaults.pacing == Pacing::Frames) as u8);
+}
+
+fn migrate_11_to_12(fields: &mut Vec<u8>, defaults: &Settings) {
+    fields.push(defaults.plane as u8);
+}
+
+fn migrate_12_to_13(fields: &mut Vec<u8>, defaults: &Settings) {
+    fields.push(defaults.adapt as u8);
+}
+
+fn migrate_13_to_14(fields: &mut Vec<u8>, defaults: &Settings) {
+    fields.push(defaults.noise_cells as u8);
+    fields.push(defaults.noise_every as u8);
+}
+
+fn encode_alarms(fields: &mut Vec<u8>, settings: &Settings) {
+    fields.extend_from_slice(&settings.alarms.above.to_le_bytes());
+    fields.extend_from_slice(&settings.alarms.below.to_le_bytes());
+    fields.push(settings.alarms.zero as u8);
+    fields.push(match settings.alarms.action {
+        AlarmAction::Pause => 0,
+        AlarmAction::Beep => 1,
+        AlarmAction::Both => 2,
+    });
+}
+
+fn encode(settings: &Settings) -> Vec<u8> {
+    let mut fields = Vec::with_capacity(SIZES[VERSION as usize - 1]);
+    fields.extend_from_slice(&settings.history_depth.to_le_bytes());
+    fields.push(match settings.boundary {
+        Boundary::Toroidal => 0,
+        Boundary::DeadEdge => 1,
+        Boundary::Reflective => 2,
+    });
+    fields.push(settings.follow as u8);
+    fields.push(settings.paint as u8);
+    fields.extend_from_slice(&settings.sample_interval.to_le_bytes());
+    fields.push(settings.board_repeat.delay as u8);
+    fields.push(settings.board_repeat.interval as u8);
+    fields.push(settings.menu_repeat.delay as u8);
+    fields.push(settings.menu_repeat.interval as u8);
+    fields.push(match settings.neighborhood {
+        Neighborhood::Moore => 0,
+        Neighborhood::Hex => 1,
+        Neighborhood::VonNeumann => 2,
+    });
+    fields.push(settings.highway_pause as u8);
+    fields.push(settings.ramp as u8);
+    fields.push(settings.kiosk as u8);
+    encode_alarms(&mut fields, settings);
+    fields.push(settings.turbo_every as u8);
+    fields.push(settings.echo as u8);
+    fields.push(settings.see_through as u8);
+    fields.push((settings.pacing == Pacing::Frames) as u8);
+    fields.push(settings.plane as u8);
+    fields.push(settings.adapt as u8);
+    fields.push(settings.noise_cells as u8);
+    fields.push(settings.noise_every as u8);
+    fields
+}
+
+fn decode(fields: &[u8], settings: &mut Settings) {
+    settings.history_depth = u16::from_le_bytes([fields[0], fields[1]]);
+    settings.boundary = match fields[2] {
+        1 => Boundary::DeadEdge,
+        2 => Boundary::Reflective,
+        _ => Boundary::Toroidal,
+    };
+    settings.follow = fields[3] != 0;
+    settings.paint = fields[4] != 0;
+    settings.sample_interval = u16::from_le_bytes([fields[5], fields[6]]);
+    settings.board_repeat.delay = fields[7].into();
+    settings.board_repeat.interval = fields[8].into();
+    settings.menu_repeat.delay = fields[9].into();
+    settings.menu_repeat.interval = fields[10].into();
+    settings.neighborhood = match fields[11] {
+        1 => Neighborhood::Hex,
+        2 => Neighborhood::VonNeumann,
+        _ => Neighborhood::Moore,
+    };
+    settings.highway_pause = fields[12] != 0;
+    settings.ramp = fields[13] != 0;
+    settings.kiosk = fields[14] != 0;
+    settings.alarms.above = u16::from_le_bytes([fields[15], fields[16]]);
+    settings.alarms.below = u16::from_le_bytes([fields[17], fields[18]]);
+    settings.alarms.zero = fields[19] != 0;
+    settings.alarms.action = match fields[20] {
+        1 => AlarmAction::Beep,
+        2 => AlarmAction::Both,
+        _ => AlarmAction::Pause,
+    };
+    settings.turbo_every = fields[21].into();
+    settings.echo = fields[22].into();
+    settings.see_through = fields[23].into();
+    settings.pacing = if fields[24] != 0 { Pacing::Frames } else { Pacing::Timer };
+    settings.plane = fields[25] != 0;
+    settings.adapt = fields[26].into();
+    settings.noise_cells = fields[27].into();
+    settings.noise_every = fields[28].max(1).into();
+}
+
+pub fn save(save: &mut SaveManager, settings: &Settings) -> Result<(), Error> {
+    let mut access = save.access()?;
+    let mut bytes = Vec::from([PREFERENCES_MAGIC, VERSION]);
+    bytes.extend_from_slice(&encode(settings));
+    access.prepare_write(PREFERENCES_OFFSET..PREFERENCES_OFFSET + bytes.len())?
+          .write(PREFERENCES_OFFSET, &bytes)?;
+    Ok(())
+}
+
+// Leaves the settings alone when there is no chunk or it is from a newer
+// version than this one knows
+pub fn load(save: &mut SaveManager, settings: &mut Settings) -> Result<(), Error> {
+    let mut access = save.access()?;
+    let mut header = [0u8; HEADER_BYTES];
+    access.read(PREFERENCES_OFFSET, &mut header)?;
+    let version = header[1];
+    if header[0] != PREFERENCES_MAGIC || version == 0 || version > VERSION {
+        return Ok(());
+    }
+
+    let mut fields = vec![0; SIZES[version as usize - 1]];
+    access.read(PREFERENCES_OFFSET + HEADER_BYTES, &mut fields)?;
+    for migrate in &MIGRATIONS[version as usize - 1..] {
+        migrate(&mut fields, settings);
+    }
+    decode(&fields, settings);
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use::alloc::{vec, vec::Vec};
+
+use agb::input::{Button, ButtonController, Tri};
+use agb::save::{SaveManager, Error};
+
+use crate::prng::Prng;
+
+// The last half minute of buttons is kept so a stuck cursor or a menu out
+// of step can be saved from the memory page and played back later. The
+// generator state goes with it so anything random comes out the same.
+pub const RECORD_FRAMES: usize = 30 * 60;
+const RECORDING_OFFSET: usize = 12288;
+const RECORDING_MAGIC: u8 = b'I';
+// Magic, a spare byte, the frame count and the generator state
+const HEADER_BYTES: usize = 8;
+
+// Bit of each button in a recorded frame
+const BUTTONS: [Button; 10] = [
+    Button::A, Button::B, Button::SELECT, Button::START, Button::RIGHT,
+    Button::LEFT, Button::UP, Button::DOWN, Button::R, Button::L,
+];
+
+pub struct Recording {
+    pub seed: u32,
+    pub frames: Vec<u16>,
+}
+
+// Stands in for the button controller, reading the buttons or a recording
+// and remembering what it read
+pub struct Input {
+    controller: ButtonController,
+    current: u16,
+    previous: u16,
+    frames: Vec<u16>,
+    seeds: Vec<u32>,
+    next: usize,
+    len: usize,
+    playback: Option<(Recording, usize)>,
+}
+
+impl Input {
+
+    pub fn new() -> Self {
+        Input {
+            controller: ButtonController::new(),
+            current: 0,
+            previous: 0,
+            frames: vec![0; RECORD_FRAMES],
+            seeds: vec![0; RECORD_FRAMES],
+            next: 0,
+            len: 0,
+            playback: None,
+        }
+    }
+
+    // Once a frame, with the generator as it is before the frame uses it
+    pub fn update(&mut self, rng: &Prng) {
+        self.controller.update();
+        self.previous = self.current;
+        self.current = match &mut self.playback {
+            Some((recording, i)) => {
+                *i += 1;
+                recording.frames[*i - 1]
+            },
+            None => BUTTONS.iter().enumerate()
+                .filter(|(_, b)| self.controller.is_pressed(**b))
+                .fold(0, |bits, (i, _)| bits | 1 << i),
+        };
+        if matches!(&self.playback, Some((recording, i)) if *i == recording.frames.len()) {
+            self.playback = None;
+        }
+
+        self.frames[self.next] = self.current;
+        self.seeds[self.next] = rng.state();
+        self.next = (self.next + 1) % RECORD_FRAMES;
+        self.len = (self.len + 1).min(RECORD_FRAMES);
+    }
+
+    // The buttons are ignored until the recording runs out
+    pub fn play(&mut self, recording: Recording) {
+        if !recording.frames.is_empty() {
+            self.playback = Some((recording, 0));
+        }
+    }
+
+    pub fn replaying(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    // Oldest frame first, with the generator state from before it
+    pub fn recording(&self) -> Recording {
+        let start = (self.next + RECORD_FRAMES - self.len) % RECORD_FRAMES;
+        Recording {
+            seed: self.seeds[start],
+            frames: (0..self.len).map(|i| self.frames[(start + i) % RECORD_FRAMES]).collect(),
+        }
+    }
+
+    pub fn is_pressed(&self, button: Button) -> bool {
+        self.current & mask(button) != 0
+    }
+
+    pub fn is_just_pressed(&self, button: Button) -> bool {
+        self.current & !self.previous & mask(button) != 0
+    }
+
+    pub fn is_just_released(&self, button: Button) -> bool {
+        !self.current & self.previous & mask(button) != 0
+    }
+
+    pub fn just_pressed_x_tri(&self) -> Tri {
+        (self.is_just_pressed(Button::LEFT), self.is_just_pressed(Button::RIGHT)).into()
+    }
+
+    pub fn just_pressed_y_tri(&self) -> Tri {
+        (self.is_just_pressed(Button::UP), self.is_just_pressed(Button::DOWN)).into()
+    }
+}
+
+fn mask(button: Button) -> u16 {
+    BUTTONS.iter().enumerate()
+        .filter(|(_, b)| **b == button)
+        .fold(0, |bits, (i, _)| bits | 1 << i)
+}
+
+pub fn save(save: &mut SaveManager, recording: &Recording) -> Result<(), Error> {
+    let mut access = save.access()?;
+    let mut bytes = Vec::with_capacity(HEADER_BYTES + 2 * recording.frames.len());
+    bytes.extend_from_slice(&[RECORDING_MAGIC, 0]);
+    bytes.extend_from_slice(&(recording.frames.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&recording.seed.to_le_bytes());
+    for frame in &recording.frames {
+        bytes.extend_from_slice(&frame.to_le_bytes());
+    }
+    access.prepare_write(RECORDING_OFFSET..RECORDING_OFFSET + bytes.len())?
+          .write(RECORDING_OFFSET, &bytes)?;
+    Ok(())
+}
+
+pub fn load(save: &mut SaveManager) -> Result<Option<Recording>, Error> {
+    let mut access = save.access()?;
+    let mut header = [0u8; HEADER_BYTES];
+    access.read(RECORDING_OFFSET, &mut header)?;
+    let len = u16::from_le_bytes([header[2], header[3]]) as usize;
+    if header[0] != RECORDING_MAGIC || len > RECORD_FRAMES {
+        return Ok(None);
+    }
+    let mut bytes = vec![0; 2 * len];
+    access.read(RECORDING_OFFSET + HEADER_BYTES, &mut bytes)?;
+    Ok(Some(Recording {
+        seed: u32::from_le_bytes([header[4], header[5], header[6], header[7]]),
+        frames: bytes.chunks(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Played back frames read like the buttons and are recorded again, so
+    // a replay can itself be saved
+    #[test_case]
+    fn replays_are_recorded(_gba: &mut agb::Gba) {
+        let a = mask(Button::A);
+        let mut input = Input::new();
+        input.play(Recording { seed: 7, frames: Vec::from([0, a, a, 0]) });
+        let mut presses = Vec::new();
+        for seed in 7..11 {
+            input.update(&Prng::new(seed));
+            presses.push((input.is_just_pressed(Button::A), input.is_just_released(Button::A)));
+        }
+        assert_eq!(presses, [(false, false), (true, false), (false, false), (false, true)]);
+        assert!(!input.replaying());
+
+        let recording = input.recording();
+        assert_eq!(recording.seed, 7);
+        assert_eq!(recording.frames, [0, a, a, 0]);
+    }
+}
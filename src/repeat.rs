@@ -0,0 +1,58 @@
+use::agb::input::Button;
+
+use crate::recording::Input;
+
+// Held repeats before the interval is halved
+const ACCELERATE_AFTER: u16 = 8;
+
+#[derive(Copy,Clone)]
+pub struct RepeatProfile {
+    // Frames a direction is held before it starts repeating
+    pub delay: u16,
+    // Frames between repeats
+    pub interval: u16,
+}
+
+// Turns a held direction into a stream of cursor moves
+pub struct KeyRepeat {
+    held: Option<Button>,
+    frames: u16,
+    repeats: u16,
+}
+
+impl KeyRepeat {
+
+    pub fn new() -> Self {
+        KeyRepeat { held: None, frames: 0, repeats: 0 }
+    }
+
+    // Call once per frame, returns the direction to move this frame if any
+    pub fn update(&mut self, input: &Input, profile: &RepeatProfile) -> Option<Button> {
+        let pressed = [Button::UP, Button::DOWN, Button::LEFT, Button::RIGHT]
+            .into_iter()
+            .find(|b| input.is_pressed(*b));
+        if pressed != self.held || pressed.is_none() {
+            self.held = pressed;
+            self.frames = 0;
+            self.repeats = 0;
+            return pressed;
+        }
+
+        // Frames since the last move
+        self.frames += 1;
+        let wait = if self.repeats == 0 {
+            profile.delay
+        } else if self.repeats >= ACCELERATE_AFTER {
+            profile.interval / 2
+        } else {
+            profile.interval
+        };
+        if self.frames >= wait.max(1) {
+            self.frames = 0;
+            self.repeats = self.repeats.saturating_add(1);
+            self.held
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use::alloc::vec::Vec;
+
+use agb::save::{SaveManager, Error};
+
+// Population samples are kept in SRAM after the stamp slots so they
+// survive power cycles
+const SAMPLE_OFFSET: usize = 2048;
+const SAMPLE_MAGIC: u8 = b'P';
+const HEADER_BYTES: usize = 8;
+const SAMPLE_BYTES: usize = 6;
+pub const SAMPLE_COUNT: usize = 256;
+
+// Magic byte, index of the next sample and number of samples kept
+fn read_header(save: &mut SaveManager) -> Result<(usize, usize), Error> {
+    let mut access = save.access()?;
+    let mut header = [0u8; HEADER_BYTES];
+    access.read(SAMPLE_OFFSET, &mut header)?;
+    let next = u16::from_le_bytes([header[1], header[2]]) as usize;
+    let len = u16::from_le_bytes([header[3], header[4]]) as usize;
+    if header[0] != SAMPLE_MAGIC || next >= SAMPLE_COUNT || len > SAMPLE_COUNT {
+        return Ok((0, 0));
+    }
+    Ok((next, len))
+}
+
+fn write_header(save: &mut SaveManager, next: usize, len: usize) -> Result<(), Error> {
+    let mut access = save.access()?;
+    let mut header = [0u8; HEADER_BYTES];
+    header[0] = SAMPLE_MAGIC;
+    header[1..3].copy_from_slice(&(next as u16).to_le_bytes());
+    header[3..5].copy_from_slice(&(len as u16).to_le_bytes());
+    access.prepare_write(SAMPLE_OFFSET..SAMPLE_OFFSET + HEADER_BYTES)?
+          .write(SAMPLE_OFFSET, &header)?;
+    Ok(())
+}
+
+pub fn record(save: &mut SaveManager, generation: u32, population: u16) -> Result<(), Error> {
+    let (next, len) = read_header(save)?;
+    let mut bytes = [0u8; SAMPLE_BYTES];
+    bytes[0..4].copy_from_slice(&generation.to_le_bytes());
+    bytes[4..6].copy_from_slice(&population.to_le_bytes());
+    let offset = SAMPLE_OFFSET + HEADER_BYTES + next * SAMPLE_BYTES;
+    {
+        let mut access = save.access()?;
+        access.prepare_write(offset..offset + SAMPLE_BYTES)?
+              .write(offset, &bytes)?;
+    }
+    write_header(save, (next + 1) % SAMPLE_COUNT, (len + 1).min(SAMPLE_COUNT))
+}
+
+pub fn clear(save: &mut SaveManager) -> Result<(), Error> {
+    write_header(save, 0, 0)
+}
+
+// Generation and population of every kept sample, oldest first
+pub fn read_all(save: &mut SaveManager) -> Result<Vec<(u32, u16)>, Error> {
+    let (next, len) = read_header(save)?;
+    let mut access = save.access()?;
+    let mut samples = Vec::with_capacity(len);
+    for i in 0..len {
+        let slot = (next + SAMPLE_COUNT - len + i) % SAMPLE_COUNT;
+        let mut bytes = [0u8; SAMPLE_BYTES];
+        access.read(SAMPLE_OFFSET + HEADER_BYTES + slot * SAMPLE_BYTES, &mut bytes)?;
+        samples.push((
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            u16::from_le_bytes([bytes[4], bytes[5]]),
+        ));
+    }
+    Ok(samples)
+}
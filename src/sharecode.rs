@@ -0,0 +1,130 @@
+use::alloc::vec::Vec;
+
+use crate::pattern::{Stamp, STAMP_SIZE};
+use crate::prng::Prng;
+
+// Five bits per character, leaving out letters easily mistaken for digits
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+const HEADER_BITS: usize = 8;
+
+// Scrambled codes have every character after the size XORed with a stream
+// seeded by the size, so editing one by hand changes cells all over. Their
+// checksum is the plain one plus SCRAMBLED, which catches a changed
+// character as often and keeps the two kinds of code apart.
+const SCRAMBLE_SEED: u32 = 0x5eed_c0de;
+const SCRAMBLED: u8 = 16;
+
+// Characters in the code of the largest stamp, checksum included
+pub const MAX_LENGTH: usize = (HEADER_BITS + (STAMP_SIZE * STAMP_SIZE) as usize).div_ceil(5) + 1;
+
+pub fn to_char(digit: u8) -> char {
+    ALPHABET[digit as usize % 32] as char
+}
+
+fn checksum(digits: &[u8]) -> u8 {
+    (digits.iter().map(|d| *d as usize).sum::<usize>() % 32) as u8
+}
+
+// Its own undoing
+fn scramble(digits: &mut [u8]) {
+    let mut rng = Prng::new(SCRAMBLE_SEED ^ ((digits[0] as u32) << 5 | digits[1] as u32));
+    for digit in &mut digits[2..] {
+        *digit ^= (rng.next_u32() >> 27) as u8;
+    }
+}
+
+// Width-1 and height-1 in four bits each, then the cells row by row,
+// followed by a checksum character
+pub fn encode(stamp: &Stamp, scrambled: bool) -> Vec<u8> {
+    let mut bits = Vec::new();
+    for shift in (0..4).rev() {
+        bits.push((stamp.width - 1) >> shift & 1 != 0);
+    }
+    for shift in (0..4).rev() {
+        bits.push((stamp.height - 1) >> shift & 1 != 0);
+    }
+    for y in 0..stamp.height {
+    for x in 0..stamp.width {
+        bits.push(stamp.is_live(x, y));
+    }}
+
+    let mut digits: Vec<u8> = bits.chunks(5)
+        .map(|chunk| (0..5).fold(0, |d, i| d << 1 | *chunk.get(i).unwrap_or(&false) as u8))
+        .collect();
+    if scrambled {
+        scramble(&mut digits);
+        digits.push((checksum(&digits) + SCRAMBLED) % 32);
+    } else {
+        digits.push(checksum(&digits));
+    }
+    digits
+}
+
+// How long a code is, going by the size in its first two characters
+pub fn length(digits: &[u8]) -> usize {
+    let header = (digits[0] as usize) << 5 | digits[1] as usize;
+    let width = (header >> 6 & 15) + 1;
+    let height = (header >> 2 & 15) + 1;
+    (HEADER_BITS + width * height).div_ceil(5) + 1
+}
+
+pub fn is_scrambled(digits: &[u8]) -> bool {
+    let n = length(digits);
+    digits.len() >= n && (checksum(&digits[..n-1]) + SCRAMBLED) % 32 == digits[n-1]
+}
+
+pub fn decode(digits: &[u8]) -> Option<Stamp> {
+    let n = length(digits);
+    if digits.len() < n {
+        return None;
+    }
+    let mut digits = digits[..n].to_vec();
+    if is_scrambled(&digits) {
+        scramble(&mut digits[..n-1]);
+    } else if checksum(&digits[..n-1]) != digits[n-1] {
+        return None;
+    }
+    let bit = |i: usize| digits[i / 5] >> (4 - i % 5) & 1 != 0;
+    let field = |start: usize| (start..start + 4).fold(0, |v, i| v << 1 | bit(i) as u16);
+
+    let mut stamp = Stamp {
+        width: field(0) + 1,
+        height: field(4) + 1,
+        rows: [0; STAMP_SIZE as usize],
+    };
+    let mut i = HEADER_BITS;
+    for y in 0..stamp.height {
+    for x in 0..stamp.width {
+        if bit(i) {
+            stamp.rows[y as usize] |= 1 << x;
+        }
+        i += 1;
+    }}
+    Some(stamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::GLIDER;
+
+    // Either kind of code gives the stamp back, and a character changed
+    // by hand is caught
+    #[test_case]
+    fn scrambled_codes_round_trip(_gba: &mut agb::Gba) {
+        let glider = Stamp::from(&GLIDER);
+        let plain = encode(&glider, false);
+        let scrambled = encode(&glider, true);
+        assert_ne!(plain, scrambled);
+        assert_eq!(plain[..2], scrambled[..2]);
+        assert!(!is_scrambled(&plain));
+        assert!(is_scrambled(&scrambled));
+        assert!(decode(&plain) == Some(glider));
+        assert!(decode(&scrambled) == Some(glider));
+
+        let mut tampered = scrambled.clone();
+        tampered[2] ^= 1;
+        assert!(decode(&tampered).is_none());
+    }
+}
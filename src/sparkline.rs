@@ -0,0 +1,87 @@
+use::alloc::collections::VecDeque;
+use::alloc::vec::Vec;
+
+use agb::display::tiled::{RegularMap, VRamManager};
+
+use crate::edges::colours;
+
+// The population of the last generations as a line of pixels a tile high,
+// a column a generation with the newest at the right, scaled between the
+// lowest and highest it has been in that time
+pub const TILES: u16 = 4;
+const SAMPLES: usize = TILES as usize * 8;
+
+pub struct Sparkline {
+    samples: VecDeque<u16>,
+}
+
+impl Sparkline {
+
+    pub fn new() -> Self {
+        Sparkline { samples: VecDeque::with_capacity(SAMPLES) }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn push(&mut self, population: u16) {
+        if self.samples.len() == SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(population);
+    }
+
+    // Pixels up from the bottom row for each column, the oldest first and
+    // a flat line along the middle while nothing changes
+    fn heights(&self) -> Vec<u8> {
+        let low = self.samples.iter().copied().min().unwrap_or(0);
+        let high = self.samples.iter().copied().max().unwrap_or(0);
+        self.samples.iter()
+            .map(|s| if high == low { 3 } else { ((s - low) as u32 * 7 / (high - low) as u32) as u8 })
+            .collect()
+    }
+
+    // Drawn into new tiles each time, like the edge preview, right
+    // aligned so the newest column is always at the same place
+    pub fn draw(&self, bg: &mut RegularMap, vram: &mut VRamManager, x: u16, y: u16) {
+        let Some((palette, dead, live)) = colours() else {
+            return;
+        };
+        let heights = self.heights();
+        let start = SAMPLES - heights.len();
+        for tx in 0..TILES {
+            let tile = vram.new_dynamic_tile().fill_with(dead);
+            for (row, data) in tile.tile_data.iter_mut().enumerate() {
+                for pixel in 0..8 {
+                    let column = (tx * 8 + pixel) as usize;
+                    let lit = column >= start && 7 - heights[column - start] as usize == row;
+                    if lit {
+                        *data = *data & !(0xf << (pixel * 4)) | (live as u32) << (pixel * 4);
+                    }
+                }
+            }
+            bg.set_tile(vram, (x + tx, y), &tile.tile_set(), tile.tile_setting().palette(palette));
+            vram.remove_dynamic_tile(tile);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only the last generations are kept, the highest at the top
+    #[test_case]
+    fn sparklines_scale_to_the_recent_range(_gba: &mut agb::Gba) {
+        let mut sparkline = Sparkline::new();
+        sparkline.push(50);
+        assert_eq!(sparkline.heights(), [3]);
+        for population in 0..SAMPLES as u16 {
+            sparkline.push(population * 2);
+        }
+        let heights = sparkline.heights();
+        assert_eq!(heights.len(), SAMPLES);
+        assert_eq!((heights[0], heights[SAMPLES - 1]), (0, 7));
+    }
+}
@@ -0,0 +1,77 @@
+use::agb::display::tiled::{RegularMap, VRamManager};
+
+use crate::background_tiles;
+
+// Tile index of each character in tiles.aseprite, blank window tile otherwise
+pub fn glyph(c: char) -> usize {
+    match c {
+        '0'..='9' => 48 + (c as usize - '0' as usize),
+        'A' => 25,
+        'B' => 8,
+        'C' => 10,
+        'D' => 31,
+        'E' => 27,
+        'F' => 11,
+        'G' => 12,
+        'H' => 13,
+        'I' => 14,
+        'J' => 15,
+        'K' => 16,
+        'L' => 28,
+        'M' => 17,
+        'N' => 32,
+        'O' => 29,
+        'P' => 18,
+        'Q' => 19,
+        'R' => 20,
+        'S' => 24,
+        'T' => 21,
+        'U' => 22,
+        'V' => 26,
+        'W' => 34,
+        'X' => 23,
+        'Y' => 35,
+        'Z' => 36,
+        '/' => 37,
+        ':' => 38,
+        '-' => 39,
+        '.' => 40,
+        '<' => 41,
+        '>' => 42,
+        '%' => 43,
+        '+' => 44,
+        _ => 1,
+    }
+}
+
+pub fn draw_text(bg: &mut RegularMap, vram: &mut VRamManager, x: u16, y: u16, text: &str) {
+    let tileset = background_tiles::tiles.tiles;
+    for (i, c) in text.chars().enumerate() {
+        bg.set_tile(
+            vram,
+            (x + i as u16, y),
+            &tileset,
+            background_tiles::tiles.tile_settings[glyph(c)],
+        );
+    }
+}
+
+// Right aligned so the last digit lands on x+width-1
+pub fn draw_number(bg: &mut RegularMap, vram: &mut VRamManager, x: u16, y: u16, width: u16, n: usize) {
+    let tileset = background_tiles::tiles.tiles;
+    let mut n = n;
+    for i in (0..width).rev() {
+        let tile = if n > 0 || i == width-1 {
+            glyph(char::from(b'0' + (n % 10) as u8))
+        } else {
+            glyph(' ')
+        };
+        bg.set_tile(
+            vram,
+            (x + i, y),
+            &tileset,
+            background_tiles::tiles.tile_settings[tile],
+        );
+        n /= 10;
+    }
+}
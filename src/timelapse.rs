@@ -0,0 +1,98 @@
+use::alloc::vec::Vec;
+
+use crate::{Graph, NodeType::Cell, DEAD, WIDTH, HEIGHT};
+
+// Each snapshot has a bit for every 2x2 block, set if any of its cells are
+// alive, so a long run fits in a few kilobytes
+pub const LAPSE_WIDTH: u16 = WIDTH / 2;
+pub const LAPSE_HEIGHT: u16 = HEIGHT / 2;
+const SNAPSHOT_BYTES: usize = (LAPSE_WIDTH * LAPSE_HEIGHT).div_ceil(8) as usize;
+pub const LAPSE_SNAPSHOTS: usize = 240;
+// Generations between snapshots at the start of a capture
+pub const LAPSE_EVERY: u32 = 16;
+
+pub struct TimeLapse {
+    snapshots: Vec<[u8; SNAPSHOT_BYTES]>,
+    generations: Vec<u32>,
+    every: u32,
+}
+
+impl TimeLapse {
+
+    pub fn new() -> Self {
+        TimeLapse {
+            snapshots: Vec::with_capacity(LAPSE_SNAPSHOTS),
+            generations: Vec::with_capacity(LAPSE_SNAPSHOTS),
+            every: LAPSE_EVERY,
+        }
+    }
+
+    // Call once per generation. Once full every other snapshot is dropped
+    // and they are taken half as often, so the whole run is always there.
+    pub fn step(&mut self, graph: &Graph, generation: u32) {
+        if !generation.is_multiple_of(self.every) {
+            return;
+        }
+        if self.snapshots.len() == LAPSE_SNAPSHOTS {
+            let mut i = 0;
+            self.snapshots.retain(|_| { i += 1; i % 2 == 1 });
+            let mut i = 0;
+            self.generations.retain(|_| { i += 1; i % 2 == 1 });
+            self.every *= 2;
+            if !generation.is_multiple_of(self.every) {
+                return;
+            }
+        }
+        let mut snapshot = [0; SNAPSHOT_BYTES];
+        for (i, n) in graph.nodes.iter().enumerate() {
+            if !matches!(n.state, Cell(s) if s != DEAD) {
+                continue;
+            }
+            let (x, y) = (i as u16 % WIDTH / 2, i as u16 / WIDTH / 2);
+            let bit = (y * LAPSE_WIDTH + x) as usize;
+            snapshot[bit / 8] |= 1 << (bit % 8);
+        }
+        self.snapshots.push(snapshot);
+        self.generations.push(generation);
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn generation(&self, snapshot: usize) -> u32 {
+        self.generations[snapshot]
+    }
+
+    pub fn live(&self, snapshot: usize, x: u16, y: u16) -> bool {
+        let bit = (y * LAPSE_WIDTH + x) as usize;
+        self.snapshots[snapshot][bit / 8] >> (bit % 8) & 1 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{new_world, Boundary, Neighborhood, LIVE};
+
+    #[test_case]
+    fn full_captures_thin_out(_gba: &mut agb::Gba) {
+        let mut graph = new_world(WIDTH, HEIGHT, Boundary::Toroidal, Neighborhood::Moore);
+        graph.nodes[(3 * WIDTH + 5) as usize].state = Cell(LIVE);
+        let mut lapse = TimeLapse::new();
+        for generation in 0..LAPSE_SNAPSHOTS as u32 * LAPSE_EVERY {
+            lapse.step(&graph, generation);
+        }
+        assert_eq!(lapse.len(), LAPSE_SNAPSHOTS);
+        assert!(lapse.live(0, 2, 1));
+        assert!(!lapse.live(0, 1, 1));
+
+        lapse.step(&graph, LAPSE_SNAPSHOTS as u32 * LAPSE_EVERY);
+        assert_eq!(lapse.len(), LAPSE_SNAPSHOTS / 2 + 1);
+        assert_eq!(lapse.generation(1), 2 * LAPSE_EVERY);
+    }
+}